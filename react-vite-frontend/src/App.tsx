@@ -4,7 +4,7 @@ import { ToastContainer, toast } from 'react-toastify';
 import 'react-toastify/dist/ReactToastify.css';
 import { CloseCircleTwoTone, CheckCircleTwoTone } from '@ant-design/icons';
 
-import { MousePosition, Step, SocketType } from './types';
+import { MousePosition, Step, SocketType, JitterDistribution } from './types';
 import StatusBar from './components/StatusBar';
 import ControlPanel from './components/ControlPanel';
 import RecordingPanel from './components/RecordingPanel';
@@ -44,7 +44,9 @@ function App() {
   const [randomTiming, setRandomTiming] = useState({
     enabled: false,
     minFactor: 0.8,
-    maxFactor: 1.2
+    maxFactor: 1.2,
+    jitterRadius: 0,
+    jitterDistribution: 'uniform' as JitterDistribution
   });
 
   // เพิ่ม state เก็บขั้นตอนที่เลือกและที่คัดลอก
@@ -92,7 +94,9 @@ function App() {
             setRandomTiming({
               enabled: data.data.enabled,
               minFactor: data.data.min_factor,
-              maxFactor: data.data.max_factor
+              maxFactor: data.data.max_factor,
+              jitterRadius: data.data.jitter_radius ?? 0,
+              jitterDistribution: data.data.jitter_distribution ?? 'uniform'
             });
 
             // Also update individual state variables
@@ -100,13 +104,13 @@ function App() {
             setRandomTimingMinFactor(data.data.min_factor);
             setRandomTimingMaxFactor(data.data.max_factor);
           } else if (data.type === "status_update") {
-            setIsRunning(data.data.status === 'running');
+            setIsRunning(data.data.status === 'running' || data.data.status === 'paused');
             setIsRecording(data.data.status === 'recording');
             setStatus(data.data.status || "idle");
 
             // Show toast message if present
             if (data.data.message) {
-              showMessage(data.data.message, data.data.status === 'running' ? 'success' : data.data.status === 'recording' ? 'info' : 'error');
+              showMessage(data.data.message, data.data.status === 'running' ? 'success' : data.data.status === 'recording' || data.data.status === 'paused' ? 'info' : 'error');
             }
           } else if (data.type === "step_executing") {
             setCurrentStep(data.data.index);
@@ -129,7 +133,30 @@ function App() {
             // รีเซ็ตค่าเมื่อทำงานเสร็จ
             setCompletedSteps(0);
             setTotalSteps(0);
-            showMessage('Automation completed!', 'success');
+            if (data.data?.aborted) {
+              showMessage(`Automation aborted after a failed step (${data.data.succeeded} succeeded, ${data.data.failed} failed)`, 'error');
+            } else {
+              showMessage(data.data?.stopped ? 'Automation stopped' : 'Automation completed!', data.data?.stopped ? 'info' : 'success');
+            }
+            if (data.data?.released_keys?.length || data.data?.released_buttons?.length) {
+              // คีย์และปุ่มเมาส์ที่ยังกดค้างอยู่ตอนจบรอบ backend ปล่อยให้อัตโนมัติ
+              const released = [...(data.data.released_keys || []), ...(data.data.released_buttons || []).map((button: string) => `${button} button`)];
+              showMessage(`Released held inputs: ${released.join(', ')}`, 'info');
+            }
+          } else if (data.type === "step_failed") {
+            // ขั้นตอนล้มเหลว (อาจมีการลองใหม่ตาม on_error)
+            showMessage(`Step ${data.data.index + 1} failed: ${data.data.error}${data.data.will_retry ? ' (retrying)' : ''}`, 'warning');
+          } else if (data.type === "debug_paused") {
+            // รอบ debug หยุดรอคำสั่งก่อนขั้นตอนนี้
+            setCurrentStep(data.data.index);
+            showMessage(`Debugger stopped before step ${data.data.index + 1} (loop ${data.data.loop_index + 1})`, 'info');
+          } else if (data.type === "simulated_step") {
+            // รอบจำลอง แสดงสิ่งที่จะเกิดขึ้นโดยไม่ส่ง input จริง
+            setCurrentStep(data.data.index);
+            showMessage(`Simulated step ${data.data.index + 1}: ${data.data.description}`, 'info');
+          } else if (data.type === "run_rejected") {
+            // มีรอบอื่นกำลังทำงานอยู่ เซิร์ฟเวอร์ไม่เริ่มรอบใหม่
+            showMessage(data.data?.message || 'Another automation is already running', 'warning');
           }
         } catch (err) {
           console.error("Error processing WebSocket message:", err);
@@ -250,34 +277,15 @@ function App() {
   // Function to run automation
   const runAutomation = () => {
     if (ws && wsConnected && steps.length > 0) {
-      // สร้างรายการขั้นตอนที่จะรัน โดยคลี่กลุ่มออกมา
-      let processedSteps: Step[] = [];
-      
-      // วนลูปผ่านทุกขั้นตอน
-      steps.forEach(step => {
-        // ถ้าเป็นขั้นตอนปกติ ให้เพิ่มเข้าไปตามปกติ
-        if (step.type !== 'group') {
-          processedSteps.push(step);
-        } 
-        // ถ้าเป็นกลุ่ม ให้เพิ่มขั้นตอนในกลุ่มเข้าไปตามจำนวนรอบที่กำหนด
-        else if (step.type === 'group') {
-          const groupSteps = step.data.groupSteps || [];
-          const loopCount = step.data.groupLoopCount || 1;
-          
-          // ทำซ้ำตามจำนวนรอบ
-          for (let i = 0; i < loopCount; i++) {
-            // เพิ่มทุกขั้นตอนในกลุ่ม
-            processedSteps.push(...groupSteps);
-          }
-        }
-      });
+      // ส่ง group ไปทั้งก้อน backend จะทำขั้นตอนภายในตามจำนวนรอบของ group เอง
+      const processedSteps: Step[] = steps;
       
       if (processedSteps.length > 0) {
         ws.send(JSON.stringify({
           type: "run_automation",
           data: { 
             loop_count: loopCount,
-            steps: processedSteps  // ส่งรายการ steps ที่คลี่กลุ่มแล้ว (ทั้ง object ไม่ใช่แค่ id)
+            steps: processedSteps  // ส่งรายการ steps ทั้ง object ไม่ใช่แค่ id
           }
         }));
       }
@@ -317,28 +325,10 @@ function App() {
     if (ws && wsConnected && selectedIds.length > 0) {
       console.log('Running selected steps:', selectedIds);
       
-      // สร้างรายการขั้นตอนที่จะรัน โดยคลี่กลุ่มออกมา
-      let processedSteps: Step[] = [];
-      
-      selectedIds.forEach(stepId => {
-        const step = steps.find(s => s.id === stepId);
-        
-        // ถ้าเป็นขั้นตอนปกติ ให้เพิ่มเข้าไปตามปกติ
-        if (step && step.type !== 'group') {
-          processedSteps.push(step);
-        } 
-        // ถ้าเป็นกลุ่ม ให้เพิ่มขั้นตอนในกลุ่มเข้าไปตามจำนวนรอบที่กำหนด
-        else if (step && step.type === 'group') {
-          const groupSteps = step.data.groupSteps || [];
-          const loopCount = step.data.groupLoopCount || 1;
-          
-          // ทำซ้ำตามจำนวนรอบ
-          for (let i = 0; i < loopCount; i++) {
-            // เพิ่มทุกขั้นตอนในกลุ่ม
-            processedSteps.push(...groupSteps);
-          }
-        }
-      });
+      // ส่งขั้นตอนที่เลือกตามลำดับในรายการ (group ส่งไปทั้งก้อน)
+      const processedSteps: Step[] = selectedIds
+        .map(stepId => steps.find(s => s.id === stepId))
+        .filter((step): step is Step => step !== undefined);
       
       if (processedSteps.length > 0) {
         ws.send(JSON.stringify({
@@ -352,14 +342,22 @@ function App() {
   };
 
   // Function to update random timing settings
-  const updateRandomTiming = (enabled: boolean, minFactor: number, maxFactor: number) => {
+  const updateRandomTiming = (
+    enabled: boolean,
+    minFactor: number,
+    maxFactor: number,
+    jitterRadius: number = randomTiming.jitterRadius,
+    jitterDistribution: JitterDistribution = randomTiming.jitterDistribution
+  ) => {
     if (ws && wsConnected) {
       ws.send(JSON.stringify({
         type: 'update_random_timing',
         data: {
           enabled,
           min_factor: minFactor,
-          max_factor: maxFactor
+          max_factor: maxFactor,
+          jitter_radius: jitterRadius,
+          jitter_distribution: jitterDistribution
         }
       }));
     }
@@ -368,7 +366,9 @@ function App() {
     setRandomTiming({
       enabled,
       minFactor,
-      maxFactor
+      maxFactor,
+      jitterRadius,
+      jitterDistribution
     });
 
     // Also update individual state variables
@@ -35,6 +35,11 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
   const [useCtrl, setUseCtrl] = useState<boolean>(false);
   const [useAlt, setUseAlt] = useState<boolean>(false);
   const [useShift, setUseShift] = useState<boolean>(false);
+  const [textInput, setTextInput] = useState<string>('');
+  const [charDelay, setCharDelay] = useState<number>(0);
+  // การเคลื่อนเมาส์ไปยังตำแหน่งก่อนคลิก ('none' คือคลิกที่ตำแหน่งปัจจุบัน)
+  const [movementProfile, setMovementProfile] = useState<string>('none');
+  const [movementDuration, setMovementDuration] = useState<number>(0.5);
   
   // shortcuts state
   const [shortcuts, setShortcuts] = useState<Shortcut[]>([
@@ -77,6 +82,9 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
         button: button,
         x: mousePosition.x,
         y: mousePosition.y,
+        ...(movementProfile !== 'none' && {
+          movement: { profile: movementProfile, duration: movementDuration }
+        }),
         step_type: 'mouse_click'
       });
       toast.success(`เพิ่มขั้นตอนคลิกเมาส์${button === 'left' ? 'ซ้าย' : button === 'right' ? 'ขวา' : 'กลาง'} ที่ตำแหน่ง (${mousePosition.x}, ${mousePosition.y}) แล้ว`);
@@ -115,15 +123,29 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
     }
   };
 
+  // เพิ่มขั้นตอนพิมพ์ข้อความ (รองรับภาษาไทยและอักขระ Unicode อื่นๆ)
+  const addTypeTextStep = () => {
+    if (onAddStep && textInput) {
+      onAddStep('type_text', {
+        text: textInput,
+        char_delay: charDelay,
+        step_type: 'type_text'
+      });
+      toast.success(`เพิ่มขั้นตอนพิมพ์ข้อความ '${textInput}' แล้ว`);
+      setTextInput('');
+    } else {
+      toast.error('กรุณาระบุข้อความที่ต้องการพิมพ์');
+    }
+  };
+
   // เพิ่มฟังก์ชันใหม่สำหรับ Hotkeys
   const addHotkeySequence = (key: string) => {
     if (!onAddStep) return;
     
     // 1. กดปุ่ม Ctrl ลง
-    onAddStep('key_press', {
+    onAddStep('key_down', {
       key: 'ctrl',
-      action: 'down',
-      step_type: 'key_press',
+      step_type: 'key_down',
       wait_time: 0.1
     });
     
@@ -136,10 +158,9 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
     });
     
     // 3. ปล่อยปุ่ม Ctrl
-    onAddStep('key_press', {
+    onAddStep('key_up', {
       key: 'ctrl',
-      action: 'up',
-      step_type: 'key_press',
+      step_type: 'key_up',
       wait_time: 0.1
     });
     
@@ -339,7 +360,7 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
     return () => {
       window.removeEventListener('keydown', handleKeyDown);
     };
-  }, [isConnected, onAddStep, mousePosition]);
+  }, [isConnected, onAddStep, mousePosition, movementProfile, movementDuration]);
 
   return (
     <Card title="การควบคุมอุปกรณ์" className="w-full">
@@ -377,6 +398,31 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
                 <kbd className="px-2 py-0.5 bg-blue-600 rounded text-xs text-white" title="ปุ่มลัดควบคุม - ไม่ถูกบันทึกขณะกำลังบันทึก">F3</kbd>
               </div>
             </Button>
+            <div className="flex gap-2">
+              <Tooltip title="เลื่อนเมาส์ไปยังตำแหน่งที่บันทึกไว้ก่อนคลิก">
+                <Select
+                  value={movementProfile}
+                  onChange={setMovementProfile}
+                  className="flex-1"
+                >
+                  <Option value="none">คลิกที่ตำแหน่งปัจจุบัน</Option>
+                  <Option value="linear">เลื่อนเป็นเส้นตรง</Option>
+                  <Option value="eased">เลื่อนแบบเร่ง/ชะลอ</Option>
+                  <Option value="bezier">เลื่อนแบบโค้งเหมือนคน</Option>
+                </Select>
+              </Tooltip>
+              <Tooltip title="เวลาที่ใช้เลื่อนเมาส์ (วินาที)">
+                <Input
+                  type="number"
+                  min={0}
+                  step={0.1}
+                  value={movementDuration}
+                  onChange={(e) => setMovementDuration(Math.max(0, Number(e.target.value) || 0))}
+                  disabled={movementProfile === 'none'}
+                  style={{ width: 90 }}
+                />
+              </Tooltip>
+            </div>
           </div>
         </div>
 
@@ -475,6 +521,31 @@ const MouseClickButtons: React.FC<MouseClickButtonsProps> = ({
               เพิ่มคีย์
             </Button>
           </div>
+          <div className="flex gap-2 mt-2">
+            <Input.TextArea
+              placeholder="ระบุข้อความที่ต้องการพิมพ์"
+              value={textInput}
+              onChange={(e) => setTextInput(e.target.value)}
+              autoSize={{ minRows: 1, maxRows: 4 }}
+            />
+            <Tooltip title="เวลารอระหว่างตัวอักษร (วินาที)">
+              <Input
+                type="number"
+                min={0}
+                step={0.01}
+                value={charDelay}
+                onChange={(e) => setCharDelay(Math.max(0, Number(e.target.value) || 0))}
+                style={{ width: 90 }}
+              />
+            </Tooltip>
+            <Button
+              type="primary"
+              onClick={addTypeTextStep}
+              disabled={!isConnected || !onAddStep || !textInput}
+            >
+              เพิ่มข้อความ
+            </Button>
+          </div>
         </div>
       </div>
 
@@ -57,8 +57,24 @@ const ProgressPanel: React.FC<ProgressPanelProps> = ({
       }
       case 'mouse_double_click':
         return 'กำลังดับเบิลคลิกที่ตำแหน่งปัจจุบัน';
+      case 'mouse_down':
+        return `กำลังกดปุ่มเมาส์ ${step.data.button || 'left'} ค้างไว้`;
+      case 'mouse_up':
+        return `กำลังปล่อยปุ่มเมาส์ ${step.data.button || 'left'}`;
+      case 'drag':
+        return `กำลังลากเมาส์ไปที่ X: ${step.data.to?.x}, Y: ${step.data.to?.y}`;
       case 'key_press':
         return `กำลังกดปุ่ม "${step.data.key}"`;
+      case 'key_down':
+        return `กำลังกดปุ่ม "${step.data.key}" ค้างไว้`;
+      case 'key_up':
+        return `กำลังปล่อยปุ่ม "${step.data.key}"`;
+      case 'key_hold':
+        return `กำลังกดปุ่ม "${step.data.key}" ค้างไว้ ${step.data.duration} วินาที`;
+      case 'type_text':
+        return `กำลังพิมพ์ข้อความ "${step.data.text}"`;
+      case 'scroll':
+        return `กำลังเลื่อน wheel X: ${step.data.dx || 0}, Y: ${step.data.dy || 0} (${step.data.repeat || 1} ครั้ง)`;
       case 'wait':
         return `กำลังรอ ${step.data.wait_time} วินาที`;
       case 'group': {
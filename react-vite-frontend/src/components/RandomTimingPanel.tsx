@@ -1,6 +1,7 @@
 import React, { useState, useEffect } from 'react';
-import { Card, Switch, Slider, InputNumber, Typography, Space, Button, Row, Col } from 'antd';
+import { Card, Switch, Slider, InputNumber, Typography, Space, Button, Row, Col, Select } from 'antd';
 import { DashboardOutlined, SaveOutlined } from '@ant-design/icons';
+import { JitterDistribution } from '../types';
 
 const { Title } = Typography;
 
@@ -10,8 +11,16 @@ interface RandomTimingPanelProps {
     enabled: boolean;
     minFactor: number;
     maxFactor: number;
+    jitterRadius: number;
+    jitterDistribution: JitterDistribution;
   };
-  onUpdateRandomTiming: (enabled: boolean, minFactor: number, maxFactor: number) => void;
+  onUpdateRandomTiming: (
+    enabled: boolean,
+    minFactor: number,
+    maxFactor: number,
+    jitterRadius: number,
+    jitterDistribution: JitterDistribution
+  ) => void;
 }
 
 const RandomTimingPanel: React.FC<RandomTimingPanelProps> = ({
@@ -23,16 +32,20 @@ const RandomTimingPanel: React.FC<RandomTimingPanelProps> = ({
   const [enabled, setEnabled] = useState(randomTiming.enabled);
   const [minFactor, setMinFactor] = useState(randomTiming.minFactor);
   const [maxFactor, setMaxFactor] = useState(randomTiming.maxFactor);
+  const [jitterRadius, setJitterRadius] = useState(randomTiming.jitterRadius);
+  const [jitterDistribution, setJitterDistribution] = useState(randomTiming.jitterDistribution);
 
   // Update local state when props change
   useEffect(() => {
     setEnabled(randomTiming.enabled);
     setMinFactor(randomTiming.minFactor);
     setMaxFactor(randomTiming.maxFactor);
+    setJitterRadius(randomTiming.jitterRadius);
+    setJitterDistribution(randomTiming.jitterDistribution);
   }, [randomTiming]);
 
   const handleSave = () => {
-    onUpdateRandomTiming(enabled, minFactor, maxFactor);
+    onUpdateRandomTiming(enabled, minFactor, maxFactor, jitterRadius, jitterDistribution);
   };
 
   return (
@@ -104,7 +117,35 @@ const RandomTimingPanel: React.FC<RandomTimingPanelProps> = ({
             />
           </div>
         </div>
-        
+      </div>
+
+      {/* การสุ่มตำแหน่งเมาส์ใช้ได้แม้ปิดการสุ่มเวลา (รัศมี 0 คือไม่สุ่ม) */}
+      <div className="mb-4">
+        <p className="mb-1">Click/Move Jitter Radius (px):</p>
+        <div className="flex items-center gap-2">
+          <InputNumber
+            min={0}
+            max={100}
+            step={1}
+            value={jitterRadius}
+            onChange={(value) => setJitterRadius(value ?? 0)}
+            disabled={!isConnected}
+            style={{ width: '80px' }}
+          />
+          <Select
+            value={jitterDistribution}
+            onChange={setJitterDistribution}
+            disabled={!isConnected || jitterRadius === 0}
+            className="flex-1"
+            options={[
+              { value: 'uniform', label: 'Uniform' },
+              { value: 'gaussian', label: 'Gaussian' }
+            ]}
+          />
+        </div>
+      </div>
+
+      <div>
         <div className="mt-2">
           <Button
             type="primary"
@@ -122,6 +163,7 @@ const RandomTimingPanel: React.FC<RandomTimingPanelProps> = ({
         <p className="text-xs text-gray-500">
           This setting randomizes wait times to make automations appear more human-like. 
           Wait times will vary between {minFactor.toFixed(2)}x and {maxFactor.toFixed(2)}x the set value.
+          {jitterRadius > 0 && ` Mouse move and click targets will be offset by up to ${jitterRadius}px.`}
         </p>
       </div>
       
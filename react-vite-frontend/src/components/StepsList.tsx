@@ -763,8 +763,16 @@ const StepsList: React.FC<StepsListProps> = ({
         return <AimOutlined />;
       case 'mouse_click':
       case 'mouse_double_click':
+      case 'mouse_down':
+      case 'mouse_up':
+      case 'drag':
+      case 'scroll':
         return <AimOutlined />;
       case 'key_press':
+      case 'key_down':
+      case 'key_up':
+      case 'key_hold':
+      case 'type_text':
         return <KeyOutlined />;
       case 'wait':
         return <ClockCircleOutlined />;
@@ -777,17 +785,42 @@ const StepsList: React.FC<StepsListProps> = ({
   
   const getStepDescription = (step: Step) => {
     switch (step.type) {
-      case 'mouse_move':
-        return `Move to X: ${step.data.x}, Y: ${step.data.y}`;
+      case 'mouse_move': {
+        const movement = step.data.movement ? ` (${step.data.movement.profile} ${step.data.movement.duration ?? 0.5}s)` : '';
+        return `Move to X: ${step.data.x}, Y: ${step.data.y}${movement}`;
+      }
       case 'mouse_click': {
         // Safe handling of button property
         const button = typeof step.data.button === 'string' ? step.data.button : 'Left';
-        return `${button.charAt(0).toUpperCase() + button.slice(1)} click at current position`;
+        const target = step.data.movement
+          ? `X: ${step.data.x}, Y: ${step.data.y} (${step.data.movement.profile} ${step.data.movement.duration ?? 0.5}s)`
+          : 'current position';
+        return `${button.charAt(0).toUpperCase() + button.slice(1)} click at ${target}`;
       }
       case 'mouse_double_click':
         return 'Double click at current position';
+      case 'mouse_down':
+        return `Hold down ${step.data.button || 'left'} button`;
+      case 'mouse_up':
+        return `Release ${step.data.button || 'left'} button`;
+      case 'drag': {
+        const from = step.data.from ? `X: ${step.data.from.x}, Y: ${step.data.from.y}` : 'current position';
+        return `Drag ${step.data.button || 'left'} from ${from} to X: ${step.data.to?.x}, Y: ${step.data.to?.y}`;
+      }
       case 'key_press':
         return `Press key "${step.data.key}"`;
+      case 'key_down':
+        return `Hold down key "${step.data.key}"`;
+      case 'key_up':
+        return `Release key "${step.data.key}"`;
+      case 'key_hold':
+        return `Hold key "${step.data.key}" for ${step.data.duration} seconds`;
+      case 'type_text':
+        return `Type "${step.data.text}"`;
+      case 'scroll': {
+        const repeat = step.data.repeat || 1;
+        return `Scroll X: ${step.data.dx || 0}, Y: ${step.data.dy || 0}${repeat > 1 ? ` x ${repeat}` : ''}`;
+      }
       case 'wait':
         return `Wait for ${step.data.wait_time} seconds`;
       case 'group': {
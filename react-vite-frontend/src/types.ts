@@ -5,6 +5,28 @@ export interface MousePosition {
   y: number;
 }
 
+// การเคลื่อนเมาส์ไปยังเป้าหมายแบบค่อยๆ เลื่อน
+export interface MovementProfile {
+  profile: 'linear' | 'eased' | 'bezier';
+  duration?: number;
+  seed?: number;
+}
+
+export interface ScreenRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+// ตำแหน่งเทียบกับจอที่บันทึก (x, y นับจากมุมซ้ายบนของจอ)
+export interface MonitorAnchor {
+  id: string;
+  x: number;
+  y: number;
+  scale: number;
+}
+
 export interface StepGroup {
   id: string;
   name: string;
@@ -23,10 +45,29 @@ export interface Step {
 export interface StepData {
   wait_time: number;
   randomize: boolean;
+  breakpoint?: boolean;
+  on_error?: string;
   x?: number;
   y?: number;
   button?: string;
+  movement?: MovementProfile;
+  jitter_radius?: number;
+  jitter_distribution?: JitterDistribution;
+  monitor?: MonitorAnchor;
+  // 'normalized' คือ x, y เป็นสัดส่วน 0..1 ของหน้าจอหลัก หรือของ reference
+  coordinates?: 'absolute' | 'normalized';
+  reference?: ScreenRect;
+  from?: MousePosition;
+  to?: MousePosition;
   key?: string;
+  text?: string;
+  duration?: number;
+  char_delay?: number;
+  char_jitter?: number;
+  dx?: number;
+  dy?: number;
+  repeat?: number;
+  interval?: number;
   isGroup?: boolean;
   groupName?: string;
   groupSteps?: Step[];
@@ -34,10 +75,22 @@ export interface StepData {
   [key: string]: any;
 }
 
+export interface StepDiagnostic {
+  index: number;
+  id: string | null;
+  field: string | null;
+  reason: string;
+}
+
 export interface RandomTimingConfig {
   enabled: boolean;
   min_factor: number;
   max_factor: number;
+  // การสุ่มตำแหน่งเมาส์ของ mouse_move และคลิก (รัศมี 0 คือไม่สุ่ม)
+  jitter_radius?: number;
+  jitter_distribution?: JitterDistribution;
 }
 
+export type JitterDistribution = 'uniform' | 'gaussian';
+
 export type SocketType = Socket | null; 
\ No newline at end of file
@@ -1,39 +1,303 @@
+use crate::clipboard::{self, ClipboardProvider};
+use crate::display::{self, DisplayProvider};
+use crate::executor::{DebugCommand, RunRequest};
+use crate::input_actor::{InputActor, TimingProfile};
+use crate::input_backend::{self, InputBackend};
 use crate::models::*;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 use warp::ws::Message;
 
+// ข้อมูลของรอบการทำงานที่กำลังทำงานอยู่ (มีได้ครั้งละหนึ่งรอบเท่านั้น)
+#[derive(Debug, Clone)]
+pub struct RunHandle {
+    pub run_id: String,
+    // token ยกเลิกของรอบนี้ ใช้ตัดการรอและการทำงานที่ค้างอยู่ทันทีเมื่อสั่งหยุด
+    pub cancel: CancellationToken,
+}
+
+// รายการในคิวที่รอทำงานต่อจากรอบปัจจุบัน
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedRun {
+    pub queue_id: String,
+    pub name: Option<String>,
+    pub request: RunRequest,
+}
+
 // ตัวจัดการการทำงานอัตโนมัติ
 pub struct AutomationController {
     pub steps: Vec<MacroStep>,
-    pub is_running: bool,
+    // รอบที่กำลังทำงาน จะถูกล้างเมื่อ executor ทำงานจบจริงเท่านั้น
+    pub current_run: Option<RunHandle>,
+    // คิวของรอบที่รอทำงาน (FIFO) จะเริ่มรายการถัดไปเมื่อรอบปัจจุบันทำงานจบ
+    pub run_queue: VecDeque<QueuedRun>,
+    // สถานะหยุดชั่วคราว executor จะรอระหว่างขั้นตอนจนกว่าค่าจะเป็น false
+    pub pause_signal: watch::Sender<bool>,
+    // ช่องทางส่งคำสั่ง debug ให้รอบที่รันแบบ debug (None ถ้ารอบปัจจุบันไม่ใช่ debug)
+    pub debug_commands: Option<UnboundedSender<DebugCommand>>,
     pub is_recording: bool,
     pub random_enabled: bool,
     pub random_min: f32,
     pub random_max: f32,
+    // การสุ่มตำแหน่งเมาส์ของ mouse_move และคลิก (รัศมี 0 คือไม่สุ่ม)
+    pub jitter: CoordinateJitter,
     pub clients: HashMap<String, UnboundedSender<Message>>,
     pub is_recording_toggle_pending: bool,
+    // thread ส่ง input ที่ executor และคำสั่งจาก WebSocket ใช้ร่วมกัน (ทำตามลำดับที่สั่ง)
+    pub input: InputActor,
+    // คลิปบอร์ดที่ใช้กับ type_text และคำสั่งคลิปบอร์ดจาก WebSocket
+    pub clipboard: Arc<dyn ClipboardProvider>,
+    // การจัดวางจอที่ใช้บันทึกและเล่นตำแหน่งเมาส์เทียบกับจอ
+    pub displays: Arc<dyn DisplayProvider>,
+    // วิธีเล่นขั้นตอนที่บันทึกไว้บนจอซึ่งไม่ได้เชื่อมต่ออยู่
+    pub monitor_fallback: MonitorFallback,
+}
+
+impl Default for AutomationController {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AutomationController {
     pub fn new() -> Self {
+        Self::with_backends(input_backend::from_env(), clipboard::from_env(), display::from_env())
+    }
+
+    // สร้าง controller ที่ใช้ backend ที่กำหนด (เช่น RecordingInputBackend สำหรับทดสอบ) กับคลิปบอร์ดในหน่วยความจำและจอจำลอง
+    #[cfg(test)]
+    pub(crate) fn with_input(input: Arc<dyn InputBackend>) -> Self {
+        Self::with_backends(
+            input,
+            Arc::new(clipboard::MemoryClipboard::new()),
+            Arc::new(display::FakeDisplays::default()),
+        )
+    }
+
+    pub fn with_backends(
+        input: Arc<dyn InputBackend>,
+        clipboard: Arc<dyn ClipboardProvider>,
+        displays: Arc<dyn DisplayProvider>,
+    ) -> Self {
         Self {
             steps: Vec::new(),
-            is_running: false,
+            current_run: None,
+            run_queue: VecDeque::new(),
+            pause_signal: watch::channel(false).0,
+            debug_commands: None,
             is_recording: false,
             random_enabled: false,
             random_min: 0.8,
             random_max: 1.2,
+            jitter: CoordinateJitter::default(),
             clients: HashMap::new(),
             is_recording_toggle_pending: false,
+            input: InputActor::spawn(input, TimingProfile::from_env()),
+            clipboard,
+            displays,
+            monitor_fallback: MonitorFallback::default(),
+        }
+    }
+
+    // ตรวจสอบว่ามีรอบที่กำลังทำงานอยู่หรือไม่
+    pub fn is_running(&self) -> bool {
+        self.current_run.is_some()
+    }
+
+    // ตรวจสอบว่าการทำงานถูกหยุดชั่วคราวอยู่หรือไม่
+    pub fn is_paused(&self) -> bool {
+        *self.pause_signal.borrow()
+    }
+
+    // ตั้งค่าสถานะหยุดชั่วคราว (ใช้ send_replace เพื่อให้ทำงานได้แม้ยังไม่มี executor รอรับ)
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pause_signal.send_replace(paused);
+    }
+
+    // เริ่มรอบการทำงานใหม่ ถ้ามีรอบที่กำลังทำงานอยู่จะไม่เริ่มและคืนค่ารอบเดิมกลับไป
+    pub fn begin_run(&mut self) -> Result<RunHandle, RunHandle> {
+        if let Some(active) = &self.current_run {
+            return Err(active.clone());
+        }
+
+        let run = RunHandle {
+            run_id: Uuid::new_v4().to_string(),
+            cancel: CancellationToken::new(),
+        };
+        self.set_paused(false);
+        self.current_run = Some(run.clone());
+        Ok(run)
+    }
+
+    // สั่งหยุดรอบที่กำลังทำงาน executor จะออกจากการรอทันทีและแจ้ง automation_completed เมื่อหยุดจริง
+    pub fn stop_run(&mut self) {
+        self.set_paused(false);
+        if let Some(run) = &self.current_run {
+            run.cancel.cancel();
+        }
+    }
+
+    // executor เรียกเมื่อทำงานจบแล้ว ล้างเฉพาะรอบของตัวเองเท่านั้น
+    pub fn finish_run(&mut self, run_id: &str) {
+        if self.current_run.as_ref().is_some_and(|run| run.run_id == run_id) {
+            self.current_run = None;
+            self.debug_commands = None;
+            self.set_paused(false);
+        }
+    }
+
+    // เพิ่มรอบใหม่ต่อท้ายคิว คืนค่า id ของรายการในคิว
+    pub fn enqueue_run(&mut self, name: Option<String>, request: RunRequest) -> String {
+        let queue_id = Uuid::new_v4().to_string();
+        self.run_queue.push_back(QueuedRun {
+            queue_id: queue_id.clone(),
+            name,
+            request,
+        });
+        queue_id
+    }
+
+    // ยกเลิกรายการที่ยังรออยู่ในคิว (รอบที่เริ่มทำงานแล้วต้องใช้ stop_run)
+    pub fn cancel_queued_run(&mut self, queue_id: &str) -> bool {
+        let before = self.run_queue.len();
+        self.run_queue.retain(|entry| entry.queue_id != queue_id);
+        self.run_queue.len() != before
+    }
+
+    // เรียงคิวใหม่ตาม id ที่ส่งมา ต้องระบุทุกรายการในคิวครบและไม่ซ้ำกัน
+    pub fn reorder_queue(&mut self, queue_ids: &[String]) -> Result<(), String> {
+        if queue_ids.len() != self.run_queue.len() {
+            return Err(format!(
+                "expected {} queue ids, got {}",
+                self.run_queue.len(),
+                queue_ids.len()
+            ));
+        }
+
+        // ตรวจสอบ id ทั้งหมดก่อน เพื่อไม่ให้คิวเปลี่ยนไปครึ่งทางถ้ามี id ผิด
+        let mut seen = HashSet::new();
+        for queue_id in queue_ids {
+            let known = self.run_queue.iter().any(|entry| &entry.queue_id == queue_id);
+            if !known || !seen.insert(queue_id.as_str()) {
+                return Err(format!("unknown or duplicate queue id: {}", queue_id));
+            }
+        }
+
+        let mut remaining: Vec<QueuedRun> = self.run_queue.drain(..).collect();
+        let mut reordered = VecDeque::with_capacity(remaining.len());
+        for queue_id in queue_ids {
+            if let Some(position) = remaining.iter().position(|entry| &entry.queue_id == queue_id) {
+                reordered.push_back(remaining.remove(position));
+            }
         }
+
+        self.run_queue = reordered;
+        Ok(())
+    }
+
+    // ส่งคำสั่ง debug ให้รอบที่กำลังทำงาน คืนค่า false ถ้าไม่มีรอบ debug
+    pub fn send_debug_command(&self, command: DebugCommand) -> bool {
+        self.debug_commands
+            .as_ref()
+            .is_some_and(|tx| tx.send(command).is_ok())
     }
 
     // ส่งข้อความไปยังผู้ใช้ทั้งหมด
     #[allow(dead_code)]
     pub fn broadcast_message(&self, message: Message) {
-        for (_id, sender) in &self.clients {
+        for sender in self.clients.values() {
             let _ = sender.send(message.clone());
         }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_backend::RecordingInputBackend;
+
+    fn controller() -> AutomationController {
+        AutomationController::with_input(Arc::new(RecordingInputBackend::new()))
+    }
+
+    fn request() -> RunRequest {
+        RunRequest {
+            steps: Vec::new(),
+            loop_count: 1,
+            selected_ids: None,
+            random_timing: None,
+            debug: false,
+            range: Default::default(),
+            on_error: ErrorPolicy::default(),
+            simulate: None,
+        }
+    }
+
+    fn queue_names(controller: &AutomationController) -> Vec<&str> {
+        controller.run_queue.iter().map(|entry| entry.name.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn new_and_stopped_runs_are_never_paused() {
+        let mut controller = controller();
+        controller.set_paused(true);
+        let run = controller.begin_run().unwrap();
+        assert!(!controller.is_paused());
+
+        controller.set_paused(true);
+        controller.stop_run();
+        assert!(!controller.is_paused());
+        assert!(run.cancel.is_cancelled());
+    }
+
+    #[test]
+    fn finishing_an_old_run_keeps_the_current_one() {
+        let mut controller = controller();
+        let run = controller.begin_run().unwrap();
+        assert_eq!(controller.begin_run().unwrap_err().run_id, run.run_id);
+        controller.set_paused(true);
+
+        controller.finish_run("some-older-run");
+        assert!(controller.is_running());
+        assert!(controller.is_paused());
+
+        controller.finish_run(&run.run_id);
+        assert!(!controller.is_running());
+        assert!(!controller.is_paused());
+    }
+
+    #[test]
+    fn cancels_only_the_given_queue_entry() {
+        let mut controller = controller();
+        let first = controller.enqueue_run(Some("first".to_string()), request());
+        controller.enqueue_run(Some("second".to_string()), request());
+
+        assert!(controller.cancel_queued_run(&first));
+        assert!(!controller.cancel_queued_run(&first));
+        assert_eq!(queue_names(&controller), vec!["second"]);
+    }
+
+    #[test]
+    fn reorders_the_queue_only_with_every_id_once() {
+        let mut controller = controller();
+        let ids: Vec<String> = ["a", "b", "c"]
+            .iter()
+            .map(|name| controller.enqueue_run(Some(name.to_string()), request()))
+            .collect();
+
+        let reversed: Vec<String> = ids.iter().rev().cloned().collect();
+        controller.reorder_queue(&reversed).unwrap();
+        assert_eq!(queue_names(&controller), vec!["c", "b", "a"]);
+
+        // id ไม่ครบ ซ้ำ หรือไม่รู้จัก จะไม่เปลี่ยนลำดับเดิม
+        assert!(controller.reorder_queue(&ids[..2]).is_err());
+        assert!(controller.reorder_queue(&[ids[0].clone(), ids[0].clone(), ids[1].clone()]).is_err());
+        assert!(controller.reorder_queue(&[ids[0].clone(), ids[1].clone(), "other".to_string()]).is_err());
+        assert_eq!(queue_names(&controller), vec!["c", "b", "a"]);
+    }
+}
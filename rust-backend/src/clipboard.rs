@@ -0,0 +1,527 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+pub type ClipboardResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// รูปแบบข้อมูลในคลิปบอร์ดที่รองรับ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardFormat {
+    Text,
+    Html,
+    Image,
+}
+
+impl ClipboardFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipboardFormat::Text => "text",
+            ClipboardFormat::Html => "html",
+            ClipboardFormat::Image => "image",
+        }
+    }
+}
+
+impl FromStr for ClipboardFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ClipboardFormat::Text),
+            "html" => Ok(ClipboardFormat::Html),
+            "image" => Ok(ClipboardFormat::Image),
+            _ => Err(format!("unknown clipboard format `{}` (expected text, html or image)", s)),
+        }
+    }
+}
+
+impl fmt::Display for ClipboardFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ข้อมูลในคลิปบอร์ด
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardContent {
+    Text(String),
+    // HTML พร้อมข้อความธรรมดาสำหรับโปรแกรมที่วาง HTML ไม่ได้
+    Html { html: String, alt_text: Option<String> },
+    // รูปภาพเป็นไฟล์ตามชนิด MIME ที่ระบบเก็บไว้ (เช่น image/png หรือ image/bmp) ไม่มีการแปลงรูปแบบ
+    Image { mime: String, bytes: Vec<u8> },
+}
+
+impl ClipboardContent {
+    pub fn format(&self) -> ClipboardFormat {
+        match self {
+            ClipboardContent::Text(_) => ClipboardFormat::Text,
+            ClipboardContent::Html { .. } => ClipboardFormat::Html,
+            ClipboardContent::Image { .. } => ClipboardFormat::Image,
+        }
+    }
+}
+
+// ตัวอ่าน/เขียนคลิปบอร์ด ทุกฟังก์ชันเป็นแบบ blocking ให้เรียกผ่าน spawn_blocking
+pub trait ClipboardProvider: Send + Sync {
+    // ชื่อของ provider สำหรับ log และการแจ้งสถานะ
+    fn name(&self) -> &'static str;
+
+    // อ่านข้อมูลในรูปแบบที่กำหนด คืน None ถ้าคลิปบอร์ดไม่มีข้อมูลรูปแบบนี้
+    fn get(&self, format: ClipboardFormat) -> ClipboardResult<Option<ClipboardContent>>;
+
+    // แทนที่ข้อมูลทั้งหมดในคลิปบอร์ด
+    fn set(&self, content: &ClipboardContent) -> ClipboardResult<()>;
+
+    // ข้อความในคลิปบอร์ด (ข้อความว่างถ้าไม่มี)
+    fn get_text(&self) -> ClipboardResult<String> {
+        match self.get(ClipboardFormat::Text)? {
+            Some(ClipboardContent::Text(text)) => Ok(text),
+            _ => Ok(String::new()),
+        }
+    }
+
+    fn set_text(&self, text: &str) -> ClipboardResult<()> {
+        self.set(&ClipboardContent::Text(text.to_string()))
+    }
+
+    // ข้อมูลปัจจุบันสำหรับคืนค่าภายหลัง (เลือก HTML, รูปภาพ แล้วจึงข้อความ ตามลำดับ)
+    fn snapshot(&self) -> ClipboardResult<Option<ClipboardContent>> {
+        for format in [ClipboardFormat::Html, ClipboardFormat::Image, ClipboardFormat::Text] {
+            if let Some(content) = self.get(format)? {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// เลือก provider ตาม environment variable AUTOMATION_CLIPBOARD (windows, x11, wayland หรือ memory)
+// ถ้าไม่ระบุจะเลือกตามระบบ: Windows ใช้ windows, Linux ใช้ wayland ถ้ามี WAYLAND_DISPLAY หรือ x11 ถ้ามี DISPLAY
+pub fn from_env() -> Arc<dyn ClipboardProvider> {
+    let provider: Arc<dyn ClipboardProvider> = match std::env::var("AUTOMATION_CLIPBOARD").as_deref() {
+        Ok("memory") => Arc::new(MemoryClipboard::new()),
+        Ok("x11") => Arc::new(CommandClipboard::x11()),
+        Ok("wayland") => Arc::new(CommandClipboard::wayland()),
+        #[cfg(target_os = "windows")]
+        Ok("windows") => Arc::new(WindowsClipboard),
+        Ok(other) => {
+            println!("Unknown clipboard provider `{}`, detecting from the environment", other);
+            detect()
+        },
+        Err(_) => detect(),
+    };
+    println!("Using {} clipboard", provider.name());
+    provider
+}
+
+#[cfg(target_os = "windows")]
+fn detect() -> Arc<dyn ClipboardProvider> {
+    Arc::new(WindowsClipboard)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect() -> Arc<dyn ClipboardProvider> {
+    let has_var = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+    if has_var("WAYLAND_DISPLAY") {
+        Arc::new(CommandClipboard::wayland())
+    } else if has_var("DISPLAY") {
+        Arc::new(CommandClipboard::x11())
+    } else {
+        // ไม่มีหน้าจอ (เช่น CI) ใช้คลิปบอร์ดในหน่วยความจำแทน
+        println!("No display found, clipboard contents stay inside this process");
+        Arc::new(MemoryClipboard::new())
+    }
+}
+
+// คลิปบอร์ดในหน่วยความจำ ไม่แตะคลิปบอร์ดของระบบ ใช้กับรอบจำลองและการทดสอบ
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    content: Mutex<Option<ClipboardContent>>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ClipboardProvider for MemoryClipboard {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, format: ClipboardFormat) -> ClipboardResult<Option<ClipboardContent>> {
+        let content = self.content.lock().unwrap();
+        Ok(match (format, content.as_ref()) {
+            (ClipboardFormat::Text, Some(ClipboardContent::Html { alt_text: Some(text), .. })) => {
+                Some(ClipboardContent::Text(text.clone()))
+            },
+            (format, Some(content)) if content.format() == format => Some(content.clone()),
+            _ => None,
+        })
+    }
+
+    fn set(&self, content: &ClipboardContent) -> ClipboardResult<()> {
+        *self.content.lock().unwrap() = Some(content.clone());
+        Ok(())
+    }
+}
+
+// คลิปบอร์ดของ Linux ผ่านโปรแกรม xclip (X11) หรือ wl-copy/wl-paste (Wayland)
+// โปรแกรมเหล่านี้ตั้งข้อมูลได้ครั้งละชนิดเดียว HTML ที่ตั้งจึงไม่มีข้อความสำรองสำหรับโปรแกรมที่วาง HTML ไม่ได้
+pub struct CommandClipboard {
+    tool: ClipboardTool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClipboardTool {
+    Xclip,
+    WlClipboard,
+}
+
+// ชนิดของข้อความที่ลองอ่านตามลำดับ
+const TEXT_TYPES: [&str; 5] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain", "STRING", "TEXT"];
+
+// เลือกชนิด MIME ที่จะอ่านสำหรับรูปแบบที่ขอ จากชนิดทั้งหมดที่มีในคลิปบอร์ด (None ถ้าไม่มีชนิดที่ใช้ได้)
+fn select_mime(format: ClipboardFormat, types: &[String]) -> Option<String> {
+    match format {
+        ClipboardFormat::Text => TEXT_TYPES.iter().find(|mime| types.iter().any(|t| t == *mime)).map(|mime| mime.to_string()),
+        ClipboardFormat::Html => types.iter().find(|t| *t == "text/html").cloned(),
+        ClipboardFormat::Image => types
+            .iter()
+            .find(|t| *t == "image/png")
+            .or_else(|| types.iter().find(|t| t.starts_with("image/")))
+            .cloned(),
+    }
+}
+
+impl CommandClipboard {
+    pub fn x11() -> Self {
+        Self { tool: ClipboardTool::Xclip }
+    }
+
+    pub fn wayland() -> Self {
+        Self { tool: ClipboardTool::WlClipboard }
+    }
+
+    // ชนิดข้อมูลทั้งหมดที่มีในคลิปบอร์ด
+    fn types(&self) -> ClipboardResult<Vec<String>> {
+        let output = match self.tool {
+            ClipboardTool::Xclip => run_output("xclip", &["-selection", "clipboard", "-o", "-t", "TARGETS"])?,
+            ClipboardTool::WlClipboard => run_output("wl-paste", &["--list-types"])?,
+        };
+        Ok(output
+            .map(|output| {
+                String::from_utf8_lossy(&output)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn read(&self, mime: &str) -> ClipboardResult<Option<Vec<u8>>> {
+        match self.tool {
+            ClipboardTool::Xclip => run_output("xclip", &["-selection", "clipboard", "-o", "-t", mime]),
+            ClipboardTool::WlClipboard => run_output("wl-paste", &["--no-newline", "--type", mime]),
+        }
+    }
+
+    fn write(&self, mime: &str, data: &[u8]) -> ClipboardResult<()> {
+        match self.tool {
+            ClipboardTool::Xclip => run_input("xclip", &["-selection", "clipboard", "-i", "-t", mime], data),
+            ClipboardTool::WlClipboard => run_input("wl-copy", &["--type", mime], data),
+        }
+    }
+}
+
+impl ClipboardProvider for CommandClipboard {
+    fn name(&self) -> &'static str {
+        match self.tool {
+            ClipboardTool::Xclip => "x11",
+            ClipboardTool::WlClipboard => "wayland",
+        }
+    }
+
+    fn get(&self, format: ClipboardFormat) -> ClipboardResult<Option<ClipboardContent>> {
+        let types = self.types()?;
+        let Some(mime) = select_mime(format, &types) else {
+            return Ok(None);
+        };
+        let Some(data) = self.read(&mime)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(match format {
+            ClipboardFormat::Text => ClipboardContent::Text(String::from_utf8_lossy(&data).into_owned()),
+            ClipboardFormat::Html => {
+                // โปรแกรมที่คัดลอก HTML มักมีข้อความธรรมดาคู่กันมาด้วย
+                let alt_text = match select_mime(ClipboardFormat::Text, &types) {
+                    Some(text_mime) => self.read(&text_mime)?.map(|text| String::from_utf8_lossy(&text).into_owned()),
+                    None => None,
+                };
+                ClipboardContent::Html { html: String::from_utf8_lossy(&data).into_owned(), alt_text }
+            },
+            ClipboardFormat::Image => ClipboardContent::Image { mime, bytes: data },
+        }))
+    }
+
+    fn set(&self, content: &ClipboardContent) -> ClipboardResult<()> {
+        match content {
+            ClipboardContent::Text(text) => {
+                let mime = match self.tool {
+                    ClipboardTool::Xclip => "UTF8_STRING",
+                    ClipboardTool::WlClipboard => "text/plain;charset=utf-8",
+                };
+                self.write(mime, text.as_bytes())
+            },
+            ClipboardContent::Html { html, .. } => self.write("text/html", html.as_bytes()),
+            ClipboardContent::Image { mime, bytes } => self.write(mime, bytes),
+        }
+    }
+
+    // set คืนค่าได้ชนิดเดียว จึงเลือกข้อความก่อน HTML เพื่อให้ทุกโปรแกรมยังวางข้อมูลเดิมได้
+    fn snapshot(&self) -> ClipboardResult<Option<ClipboardContent>> {
+        for format in [ClipboardFormat::Text, ClipboardFormat::Image, ClipboardFormat::Html] {
+            if let Some(content) = self.get(format)? {
+                return Ok(Some(content));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// รันคำสั่งแล้วคืน stdout (None ถ้าคำสั่งจบด้วยสถานะผิดพลาด ซึ่งเกิดเมื่อคลิปบอร์ดว่างหรือไม่มีชนิดที่ขอ)
+fn run_output(program: &str, args: &[&str]) -> ClipboardResult<Option<Vec<u8>>> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .map_err(|e| tool_error(program, e))?;
+    Ok(output.status.success().then_some(output.stdout))
+}
+
+// ส่งข้อมูลให้คำสั่งทาง stdin (xclip และ wl-copy จะแยก process ไปเก็บข้อมูลไว้เองแล้วจบทันที)
+fn run_input(program: &str, args: &[&str], data: &[u8]) -> ClipboardResult<()> {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| tool_error(program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data)?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("`{}` exited with {}", program, status).into());
+    }
+    Ok(())
+}
+
+fn tool_error(program: &str, error: std::io::Error) -> Box<dyn std::error::Error + Send + Sync> {
+    if error.kind() == std::io::ErrorKind::NotFound {
+        format!("`{}` is not installed", program).into()
+    } else {
+        format!("failed to run `{}`: {}", program, error).into()
+    }
+}
+
+// คลิปบอร์ดของ Windows ผ่าน clipboard-win
+#[cfg(target_os = "windows")]
+pub struct WindowsClipboard;
+
+#[cfg(target_os = "windows")]
+impl WindowsClipboard {
+    fn registered_format(name: &str) -> ClipboardResult<u32> {
+        clipboard_win::register_format(name)
+            .map(|id| id.get())
+            .ok_or_else(|| format!("cannot register clipboard format `{}`", name).into())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn windows_error(error: impl fmt::Display) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("Clipboard error: {}", error).into()
+}
+
+#[cfg(target_os = "windows")]
+impl ClipboardProvider for WindowsClipboard {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn get(&self, format: ClipboardFormat) -> ClipboardResult<Option<ClipboardContent>> {
+        use clipboard_win::{formats, raw, Clipboard};
+
+        let html_format = Self::registered_format("HTML Format")?;
+        let png_format = Self::registered_format("PNG")?;
+        let _clipboard = Clipboard::new_attempts(10).map_err(windows_error)?;
+
+        let text = || -> ClipboardResult<Option<String>> {
+            if !raw::is_format_avail(formats::CF_UNICODETEXT) {
+                return Ok(None);
+            }
+            clipboard_win::get::<String, _>(formats::Unicode).map(Some).map_err(windows_error)
+        };
+
+        Ok(match format {
+            ClipboardFormat::Text => text()?.map(ClipboardContent::Text),
+            ClipboardFormat::Html if raw::is_format_avail(html_format) => {
+                let mut data = Vec::new();
+                raw::get_vec(html_format, &mut data).map_err(windows_error)?;
+                Some(ClipboardContent::Html { html: parse_cf_html(&data), alt_text: text()? })
+            },
+            ClipboardFormat::Image if raw::is_format_avail(png_format) => {
+                let mut bytes = Vec::new();
+                raw::get_vec(png_format, &mut bytes).map_err(windows_error)?;
+                Some(ClipboardContent::Image { mime: "image/png".to_string(), bytes })
+            },
+            ClipboardFormat::Image if raw::is_format_avail(formats::CF_BITMAP) => {
+                let bytes = clipboard_win::get::<Vec<u8>, _>(formats::Bitmap).map_err(windows_error)?;
+                Some(ClipboardContent::Image { mime: "image/bmp".to_string(), bytes })
+            },
+            ClipboardFormat::Html | ClipboardFormat::Image => None,
+        })
+    }
+
+    fn set(&self, content: &ClipboardContent) -> ClipboardResult<()> {
+        use clipboard_win::{formats, raw, Clipboard};
+
+        let html_format = Self::registered_format("HTML Format")?;
+        let png_format = Self::registered_format("PNG")?;
+        let _clipboard = Clipboard::new_attempts(10).map_err(windows_error)?;
+        raw::empty().map_err(windows_error)?;
+
+        match content {
+            ClipboardContent::Text(text) => clipboard_win::set(formats::Unicode, text).map_err(windows_error),
+            ClipboardContent::Html { html, alt_text } => {
+                // set ของ Unicode จะล้างคลิปบอร์ดก่อน จึงต้องตั้งข้อความสำรองก่อน HTML
+                if let Some(text) = alt_text {
+                    clipboard_win::set(formats::Unicode, text).map_err(windows_error)?;
+                }
+                raw::set_without_clear(html_format, cf_html(html).as_bytes()).map_err(windows_error)
+            },
+            ClipboardContent::Image { mime, bytes } => match mime.as_str() {
+                "image/png" => raw::set_without_clear(png_format, bytes).map_err(windows_error),
+                "image/bmp" => raw::set_bitmap(bytes).map_err(windows_error),
+                other => Err(format!("unsupported image type `{}` (expected image/png or image/bmp)", other).into()),
+            },
+        }
+    }
+}
+
+// สร้างข้อมูลรูปแบบ CF_HTML ของ Windows (header บอกตำแหน่ง byte ของ HTML และ fragment)
+#[cfg(target_os = "windows")]
+fn cf_html(fragment: &str) -> String {
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+    let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| {
+        format!(
+            "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+            start_html, end_html, start_fragment, end_fragment
+        )
+    };
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + SUFFIX.len();
+    format!("{}{}{}{}", header(start_html, end_html, start_fragment, end_fragment), PREFIX, fragment, SUFFIX)
+}
+
+// ดึง fragment ออกจากข้อมูล CF_HTML (ถ้าไม่มีตำแหน่ง fragment จะใช้ทั้งเอกสาร)
+#[cfg(target_os = "windows")]
+fn parse_cf_html(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    let offset = |name: &str| -> Option<usize> {
+        let start = text.find(name)? + name.len();
+        let digits: String = text[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    };
+    let range = match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) => Some((start, end)),
+        _ => offset("StartHTML:").zip(offset("EndHTML:")),
+    };
+    match range {
+        Some((start, end)) if start <= end && end <= data.len() => String::from_utf8_lossy(&data[start..end]).into_owned(),
+        _ => text.trim_end_matches('\0').to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html(alt_text: Option<&str>) -> ClipboardContent {
+        ClipboardContent::Html { html: "<b>hi</b>".to_string(), alt_text: alt_text.map(str::to_string) }
+    }
+
+    fn types(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_clipboard_formats() {
+        assert_eq!("text".parse(), Ok(ClipboardFormat::Text));
+        assert_eq!("HTML".parse(), Ok(ClipboardFormat::Html));
+        assert_eq!("Image".parse(), Ok(ClipboardFormat::Image));
+        assert!("rtf".parse::<ClipboardFormat>().is_err());
+        for format in [ClipboardFormat::Text, ClipboardFormat::Html, ClipboardFormat::Image] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+    }
+
+    #[test]
+    fn memory_clipboard_reads_html_alt_text_as_text() {
+        let clipboard = MemoryClipboard::new();
+        clipboard.set(&html(Some("hi"))).unwrap();
+        assert_eq!(clipboard.get(ClipboardFormat::Text).unwrap(), Some(ClipboardContent::Text("hi".to_string())));
+        assert_eq!(clipboard.get(ClipboardFormat::Html).unwrap(), Some(html(Some("hi"))));
+        assert_eq!(clipboard.snapshot().unwrap(), Some(html(Some("hi"))));
+    }
+
+    #[test]
+    fn memory_clipboard_has_nothing_for_missing_formats() {
+        let clipboard = MemoryClipboard::new();
+        assert_eq!(clipboard.get(ClipboardFormat::Text).unwrap(), None);
+        assert_eq!(clipboard.get_text().unwrap(), "");
+        assert_eq!(clipboard.snapshot().unwrap(), None);
+
+        // HTML ที่ไม่มีข้อความสำรองอ่านเป็นข้อความไม่ได้
+        clipboard.set(&html(None)).unwrap();
+        assert_eq!(clipboard.get(ClipboardFormat::Text).unwrap(), None);
+        assert_eq!(clipboard.get(ClipboardFormat::Image).unwrap(), None);
+
+        clipboard.set_text("plain").unwrap();
+        assert_eq!(clipboard.get(ClipboardFormat::Html).unwrap(), None);
+        assert_eq!(clipboard.get_text().unwrap(), "plain");
+    }
+
+    #[test]
+    fn selects_the_preferred_text_type() {
+        // xclip
+        let x11 = types(&["TIMESTAMP", "TARGETS", "STRING", "UTF8_STRING", "text/html"]);
+        assert_eq!(select_mime(ClipboardFormat::Text, &x11).as_deref(), Some("UTF8_STRING"));
+        // wl-paste
+        let wayland = types(&["text/plain", "text/plain;charset=utf-8", "text/html"]);
+        assert_eq!(select_mime(ClipboardFormat::Text, &wayland).as_deref(), Some("text/plain;charset=utf-8"));
+        assert_eq!(select_mime(ClipboardFormat::Html, &wayland).as_deref(), Some("text/html"));
+        assert_eq!(select_mime(ClipboardFormat::Image, &wayland), None);
+    }
+
+    #[test]
+    fn selects_png_before_other_image_types() {
+        let both = types(&["image/bmp", "image/png", "text/html"]);
+        assert_eq!(select_mime(ClipboardFormat::Image, &both).as_deref(), Some("image/png"));
+        let jpeg = types(&["TARGETS", "image/jpeg"]);
+        assert_eq!(select_mime(ClipboardFormat::Image, &jpeg).as_deref(), Some("image/jpeg"));
+        assert_eq!(select_mime(ClipboardFormat::Text, &jpeg), None);
+        assert_eq!(select_mime(ClipboardFormat::Html, &types(&[])), None);
+    }
+}
@@ -0,0 +1,464 @@
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::models::{CoordinateSpace, MonitorAnchor, MonitorFallback, Point, Position, Rect};
+
+pub type DisplayResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+// จอหนึ่งจอในพื้นที่พิกัดเดียวกับที่ backend ใช้ส่ง input (pixel จริงของจอ)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Monitor {
+    pub id: String,
+    // มุมซ้ายบนของจอ (จอที่อยู่ซ้ายหรือบนจอหลักอาจติดลบ)
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    // อัตราขยาย UI ของจอ (1.5 คือ 150%)
+    pub scale: f64,
+    pub primary: bool,
+}
+
+impl Monitor {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect { x: self.x, y: self.y, width: self.width, height: self.height }
+    }
+}
+
+// ตัวอ่านการจัดวางจอของระบบ ทุกฟังก์ชันเป็นแบบ blocking ให้เรียกผ่าน spawn_blocking
+pub trait DisplayProvider: Send + Sync {
+    // ชื่อของ provider สำหรับ log
+    fn name(&self) -> &'static str;
+
+    fn monitors(&self) -> DisplayResult<Vec<Monitor>>;
+}
+
+// การจัดวางจอของรอบการทำงานพร้อมวิธีจัดการเมื่อจอที่บันทึกไว้ไม่ได้เชื่อมต่อ
+#[derive(Debug, Clone, Default)]
+pub struct DisplayLayout {
+    pub monitors: Vec<Monitor>,
+    pub fallback: MonitorFallback,
+}
+
+impl DisplayLayout {
+    // ตำแหน่งของจุดเทียบกับจอที่จุดนั้นอยู่ (ใช้ตอนบันทึก)
+    pub fn anchor(&self, x: i32, y: i32) -> Option<MonitorAnchor> {
+        let monitor = self.monitors.iter().find(|monitor| monitor.contains(x, y))?;
+        Some(MonitorAnchor { id: monitor.id.clone(), x: x - monitor.x, y: y - monitor.y, scale: monitor.scale })
+    }
+
+    // อ่านการจัดวางจอปัจจุบันจาก provider ถ้าอ่านไม่ได้จะถือว่าไม่รู้การจัดวางจอ (ใช้ตำแหน่งเดิมของขั้นตอน)
+    pub async fn query(provider: &Arc<dyn DisplayProvider>, fallback: MonitorFallback) -> Self {
+        let provider = provider.clone();
+        let monitors = match tokio::task::spawn_blocking(move || provider.monitors()).await {
+            Ok(Ok(monitors)) => monitors,
+            Ok(Err(e)) => {
+                println!("Failed to read the display layout: {}", e);
+                Vec::new()
+            },
+            Err(e) => {
+                println!("Failed to read the display layout: {}", e);
+                Vec::new()
+            },
+        };
+        Self { monitors, fallback }
+    }
+
+    pub fn primary(&self) -> Option<&Monitor> {
+        self.monitors.iter().find(|monitor| monitor.primary).or_else(|| self.monitors.first())
+    }
+
+    // แปลงตำแหน่งของขั้นตอนเป็นพิกัดบนการจัดวางจอปัจจุบัน
+    // ตำแหน่งแบบสัดส่วนคิดจากจอใน anchor (ไม่ระบุคือจอหลัก) หรือจาก reference ส่วนตำแหน่งเทียบกับจอจะปรับระยะจากมุมจอ
+    // ตามอัตราขยายที่ต่างกัน เพื่อให้ชี้ส่วนเดียวกันของ UI แล้วจำกัดให้อยู่ในจอ
+    pub fn resolve(&self, position: Position, anchor: Option<&MonitorAnchor>) -> Result<Point, String> {
+        if let Position::Normalized { reference: Some(reference), .. } = position {
+            return Ok(position.to_absolute(reference));
+        }
+        let monitor = match anchor {
+            Some(anchor) => match self.monitors.iter().find(|monitor| monitor.id == anchor.id) {
+                Some(monitor) => Some(monitor),
+                None => match self.fallback {
+                    MonitorFallback::Absolute => None,
+                    MonitorFallback::Fail => return Err(format!("Monitor `{}` is not connected", anchor.id)),
+                    // ถ้าไม่รู้การจัดวางจอเลยจะใช้ตำแหน่งเดิม
+                    MonitorFallback::Primary => self.primary(),
+                },
+            },
+            None => None,
+        };
+        match (position, monitor, anchor) {
+            (Position::Absolute(_), Some(monitor), Some(anchor)) => {
+                let ratio = monitor.scale / anchor.scale;
+                let x = ((anchor.x as f64 * ratio).round() as i32).clamp(0, (monitor.width - 1).max(0));
+                let y = ((anchor.y as f64 * ratio).round() as i32).clamp(0, (monitor.height - 1).max(0));
+                Ok(Point { x: monitor.x + x, y: monitor.y + y })
+            },
+            (Position::Absolute(point), _, _) => Ok(point),
+            (Position::Normalized { .. }, Some(monitor), _) => Ok(position.to_absolute(monitor.rect())),
+            (Position::Normalized { .. }, None, _) => {
+                let screen = self.primary().ok_or("The screen size is unknown, cannot use normalized coordinates")?;
+                Ok(position.to_absolute(screen.rect()))
+            },
+        }
+    }
+
+    // แปลงตำแหน่งของขั้นตอนเป็นหน่วยที่กำหนดบนการจัดวางจอนี้ (ใช้กับ Step::convert_coordinates)
+    // สัดส่วนคิดจากจอที่จุดนั้นอยู่และเก็บจอไว้ใน anchor หรือคิดจาก reference ถ้าระบุ (ไม่มี anchor)
+    // จุดที่ไม่อยู่บนจอใดหรืออยู่นอก reference จะคืน error แทนการจำกัดไว้ที่ขอบ
+    pub fn convert(
+        &self,
+        position: Position,
+        anchor: &mut Option<MonitorAnchor>,
+        to: CoordinateSpace,
+        reference: Option<Rect>,
+    ) -> Result<Position, String> {
+        let point = self.resolve(position, anchor.as_ref())?;
+        if let (CoordinateSpace::Normalized, Some(reference)) = (to, reference) {
+            let (x, y) = reference
+                .fraction_of(point)
+                .ok_or_else(|| format!("{},{} is outside the reference {}", point.x, point.y, reference))?;
+            *anchor = None;
+            return Ok(Position::Normalized { x, y, reference: Some(reference) });
+        }
+        let monitor = self
+            .monitors
+            .iter()
+            .find(|monitor| monitor.contains(point.x, point.y))
+            .ok_or_else(|| format!("{},{} is not on any monitor", point.x, point.y))?;
+        *anchor = self.anchor(point.x, point.y);
+        match to {
+            CoordinateSpace::Absolute => Ok(Position::Absolute(point)),
+            CoordinateSpace::Normalized => {
+                let (x, y) = monitor.rect().fraction_of(point).ok_or("the point is outside its monitor")?;
+                Ok(Position::Normalized { x, y, reference: None })
+            },
+        }
+    }
+}
+
+// อัตราขยายที่ใช้กับ provider ซึ่งอ่านค่าจากระบบไม่ได้ ตาม AUTOMATION_DISPLAY_SCALE (ค่าเริ่มต้น 1)
+fn scale_from_env() -> f64 {
+    std::env::var("AUTOMATION_DISPLAY_SCALE")
+        .ok()
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|scale| scale.is_finite() && *scale > 0.0)
+        .unwrap_or(1.0)
+}
+
+// จอเดียวขนาดคงที่ตาม AUTOMATION_SCREEN_SIZE (ใช้บน Wayland ซึ่ง uinput ใช้ขนาดเดียวกัน)
+pub struct FixedDisplay {
+    monitor: Monitor,
+}
+
+impl FixedDisplay {
+    pub fn from_env() -> Self {
+        let (width, height) = crate::evdev::screen_size_from_env();
+        Self {
+            monitor: Monitor { id: "screen".to_string(), x: 0, y: 0, width, height, scale: scale_from_env(), primary: true },
+        }
+    }
+}
+
+impl DisplayProvider for FixedDisplay {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn monitors(&self) -> DisplayResult<Vec<Monitor>> {
+        Ok(vec![self.monitor.clone()])
+    }
+}
+
+// การจัดวางจอที่กำหนดเองและเปลี่ยนได้ระหว่างทำงาน (สำหรับทดสอบ)
+#[allow(dead_code)]
+pub struct FakeDisplays {
+    monitors: Arc<Mutex<Vec<Monitor>>>,
+}
+
+#[allow(dead_code)]
+impl FakeDisplays {
+    pub fn new(monitors: Vec<Monitor>) -> Self {
+        Self { monitors: Arc::new(Mutex::new(monitors)) }
+    }
+
+    pub fn set_monitors(&self, monitors: Vec<Monitor>) {
+        *self.monitors.lock().unwrap() = monitors;
+    }
+}
+
+impl Default for FakeDisplays {
+    // จอหลักจอเดียวขนาด 1920x1080 ที่ 100%
+    fn default() -> Self {
+        Self::new(vec![Monitor { id: "fake".to_string(), x: 0, y: 0, width: 1920, height: 1080, scale: 1.0, primary: true }])
+    }
+}
+
+impl DisplayProvider for FakeDisplays {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn monitors(&self) -> DisplayResult<Vec<Monitor>> {
+        Ok(self.monitors.lock().unwrap().clone())
+    }
+}
+
+// X11: อ่านจาก `xrandr --listmonitors` (X11 ไม่มีอัตราขยายแยกต่อจอ จึงใช้ AUTOMATION_DISPLAY_SCALE กับทุกจอ)
+pub struct XrandrDisplays {
+    scale: f64,
+}
+
+impl XrandrDisplays {
+    pub fn new() -> Self {
+        Self { scale: scale_from_env() }
+    }
+}
+
+impl Default for XrandrDisplays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DisplayProvider for XrandrDisplays {
+    fn name(&self) -> &'static str {
+        "xrandr"
+    }
+
+    fn monitors(&self) -> DisplayResult<Vec<Monitor>> {
+        let output = Command::new("xrandr").arg("--listmonitors").output().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                "`xrandr` is not installed".to_string()
+            } else {
+                format!("Failed to run xrandr: {}", e)
+            }
+        })?;
+        if !output.status.success() {
+            return Err(format!("xrandr failed: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+        }
+        Ok(parse_xrandr_monitors(&String::from_utf8_lossy(&output.stdout), self.scale))
+    }
+}
+
+// แปลงผลของ `xrandr --listmonitors` เช่น " 0: +*eDP-1 1920/344x1080/194+0+0  eDP-1"
+fn parse_xrandr_monitors(output: &str, scale: f64) -> Vec<Monitor> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let flags_name = fields.next()?;
+            let geometry = fields.next()?;
+            let (width, rest) = geometry.split_once('x')?;
+            let (height, rest) = rest.split_once('/')?;
+            // ข้ามความสูงจริง (มม.) ส่วนที่เหลือคือตำแหน่ง เช่น "194+1920+0" หรือ "194-1920+0"
+            let offset = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+            let split = offset.get(1..)?.find(['+', '-'])? + 1;
+            Some(Monitor {
+                id: flags_name.trim_start_matches(['+', '*']).to_string(),
+                x: offset[..split].parse().ok()?,
+                y: offset[split..].parse().ok()?,
+                width: width.split('/').next()?.parse().ok()?,
+                height: height.parse().ok()?,
+                scale,
+                primary: flags_name.contains('*'),
+            })
+        })
+        .collect()
+}
+
+// Windows: อ่านทุกจอพร้อม DPI ของแต่ละจอ
+// ตั้งให้โปรแกรมรับรู้ DPI รายจอ เพื่อให้ตำแหน่งเมาส์ที่อ่านและส่งเป็น pixel จริงทุกจอ
+#[cfg(target_os = "windows")]
+pub struct WindowsDisplays;
+
+#[cfg(target_os = "windows")]
+impl WindowsDisplays {
+    pub fn new() -> Self {
+        use windows::Win32::UI::HiDpi::{SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2};
+
+        unsafe {
+            let _ = SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+        }
+        Self
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Default for WindowsDisplays {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl DisplayProvider for WindowsDisplays {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn monitors(&self) -> DisplayResult<Vec<Monitor>> {
+        use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+        use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW};
+        use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+        const MONITORINFOF_PRIMARY: u32 = 1;
+
+        unsafe extern "system" fn collect(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+            let monitors = &mut *(data.0 as *mut Vec<Monitor>);
+            let mut info = MONITORINFOEXW::default();
+            info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+            if !GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+                return BOOL(1);
+            }
+            // 96 DPI คือ 100%
+            let (mut dpi_x, mut dpi_y) = (96, 96);
+            let _ = GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+            let rect = info.monitorInfo.rcMonitor;
+            let name_len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+            monitors.push(Monitor {
+                id: String::from_utf16_lossy(&info.szDevice[..name_len]),
+                x: rect.left,
+                y: rect.top,
+                width: rect.right - rect.left,
+                height: rect.bottom - rect.top,
+                scale: dpi_x as f64 / 96.0,
+                primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+            BOOL(1)
+        }
+
+        let mut monitors: Vec<Monitor> = Vec::new();
+        let ok = unsafe { EnumDisplayMonitors(HDC::default(), None, Some(collect), LPARAM(&mut monitors as *mut Vec<Monitor> as isize)) };
+        if !ok.as_bool() {
+            return Err("Failed to enumerate monitors".into());
+        }
+        Ok(monitors)
+    }
+}
+
+// เลือก provider ตาม environment variable AUTOMATION_DISPLAY_PROVIDER (windows, xrandr หรือ fixed)
+// ถ้าไม่ระบุจะใช้ windows บน Windows, xrandr เมื่อมี X11 และ fixed ในกรณีอื่น (เช่น Wayland)
+pub fn from_env() -> Arc<dyn DisplayProvider> {
+    let requested = std::env::var("AUTOMATION_DISPLAY_PROVIDER").ok();
+    let name = requested.as_deref().unwrap_or(if cfg!(target_os = "windows") {
+        "windows"
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_none() && std::env::var_os("DISPLAY").is_some() {
+        "xrandr"
+    } else {
+        "fixed"
+    });
+    let provider: Arc<dyn DisplayProvider> = match name {
+        #[cfg(target_os = "windows")]
+        "windows" => Arc::new(WindowsDisplays::new()),
+        "xrandr" => Arc::new(XrandrDisplays::new()),
+        "fixed" => Arc::new(FixedDisplay::from_env()),
+        other => {
+            println!("Unknown display provider `{}`, falling back to fixed", other);
+            Arc::new(FixedDisplay::from_env())
+        },
+    };
+    println!("Using {} display provider", provider.name());
+    provider
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(id: &str, x: i32, width: i32, height: i32, scale: f64, primary: bool) -> Monitor {
+        Monitor { id: id.to_string(), x, y: 0, width, height, scale, primary }
+    }
+
+    #[test]
+    fn parses_xrandr_monitor_list() {
+        let output = "Monitors: 2\n 0: +*eDP-1 2880/344x1800/194+0+0  eDP-1\n 1: +HDMI-1 1920/597x1080/336-1920+0  HDMI-1\n";
+        assert_eq!(parse_xrandr_monitors(output, 1.0), vec![
+            monitor("eDP-1", 0, 2880, 1800, 1.0, true),
+            monitor("HDMI-1", -1920, 1920, 1080, 1.0, false),
+        ]);
+    }
+
+    #[test]
+    fn maps_anchor_onto_monitor_with_different_scale() {
+        let recorded = DisplayLayout { monitors: vec![monitor("laptop", 0, 2880, 1800, 1.5, true)], fallback: MonitorFallback::Primary };
+        let anchor = recorded.anchor(300, 150).unwrap();
+        assert_eq!(anchor, MonitorAnchor { id: "laptop".to_string(), x: 300, y: 150, scale: 1.5 });
+
+        // จอเดิมอยู่ทางขวาของจอใหม่ที่ 100%
+        let current = DisplayLayout {
+            monitors: vec![monitor("external", 0, 1920, 1080, 1.0, true), monitor("laptop", 1920, 1920, 1200, 1.0, false)],
+            fallback: MonitorFallback::Primary,
+        };
+        assert_eq!(current.resolve(Position::Absolute(Point { x: 300, y: 150 }), Some(&anchor)), Ok(Point { x: 2120, y: 100 }));
+    }
+
+    #[test]
+    fn missing_monitor_follows_fallback_policy() {
+        let anchor = MonitorAnchor { id: "gone".to_string(), x: 2000, y: 100, scale: 1.0 };
+        let point = Position::Absolute(Point { x: 4000, y: 100 });
+        let mut layout = DisplayLayout { monitors: vec![monitor("main", 0, 1920, 1080, 1.25, true)], fallback: MonitorFallback::Primary };
+        // ระยะถูกขยายตามอัตราขยายแล้วจำกัดให้อยู่ในจอหลัก
+        assert_eq!(layout.resolve(point, Some(&anchor)), Ok(Point { x: 1919, y: 125 }));
+        layout.fallback = MonitorFallback::Absolute;
+        assert_eq!(layout.resolve(point, Some(&anchor)), Ok(Point { x: 4000, y: 100 }));
+        layout.fallback = MonitorFallback::Fail;
+        assert!(layout.resolve(point, Some(&anchor)).is_err());
+    }
+
+    #[test]
+    fn normalized_positions_use_primary_monitor_or_reference() {
+        let layout = DisplayLayout {
+            monitors: vec![monitor("left", -1280, 1280, 1024, 1.0, false), monitor("main", 0, 2560, 1440, 1.0, true)],
+            fallback: MonitorFallback::Primary,
+        };
+        let centre = Position::Normalized { x: 0.5, y: 0.5, reference: None };
+        assert_eq!(layout.resolve(centre, None), Ok(Point { x: 1280, y: 720 }));
+        let corner = Position::Normalized { x: 1.0, y: 1.0, reference: None };
+        assert_eq!(layout.resolve(corner, None), Ok(Point { x: 2559, y: 1439 }));
+
+        let window = Rect { x: 100, y: 50, width: 800, height: 600 };
+        let in_window = Position::Normalized { x: 0.25, y: 0.5, reference: Some(window) };
+        assert_eq!(layout.resolve(in_window, None), Ok(Point { x: 300, y: 350 }));
+        assert!(DisplayLayout::default().resolve(centre, None).is_err());
+    }
+
+    #[test]
+    fn normalizes_against_the_monitor_of_each_step() {
+        let layout = DisplayLayout {
+            monitors: vec![monitor("main", 0, 1920, 1080, 1.0, true), monitor("side", 1920, 1280, 1024, 1.0, false)],
+            fallback: MonitorFallback::Fail,
+        };
+
+        // จุดบนจอที่สองเป็นสัดส่วนของจอนั้น ไม่ใช่ถูกจำกัดไว้ที่ขอบจอหลัก
+        let mut anchor = None;
+        let normalized = layout
+            .convert(Position::Absolute(Point { x: 2560, y: 256 }), &mut anchor, CoordinateSpace::Normalized, None)
+            .unwrap();
+        assert_eq!(normalized, Position::Normalized { x: 0.5, y: 0.25, reference: None });
+        assert_eq!(anchor, Some(MonitorAnchor { id: "side".to_string(), x: 640, y: 256, scale: 1.0 }));
+        assert_eq!(layout.resolve(normalized, anchor.as_ref()), Ok(Point { x: 2560, y: 256 }));
+
+        // แปลงกลับเป็น pixel ได้ตำแหน่งเดิมและยังเก็บจอไว้
+        let absolute = layout.convert(normalized, &mut anchor, CoordinateSpace::Absolute, None).unwrap();
+        assert_eq!(absolute, Position::Absolute(Point { x: 2560, y: 256 }));
+        assert_eq!(anchor.as_ref().map(|anchor| anchor.id.as_str()), Some("side"));
+
+        // จุดที่ไม่อยู่บนจอใดหรือนอก reference ถูกปฏิเสธ
+        let outside = Position::Absolute(Point { x: 2500, y: 1500 });
+        assert!(layout.convert(outside, &mut None, CoordinateSpace::Normalized, None).is_err());
+        let window = Rect { x: 0, y: 0, width: 800, height: 600 };
+        let mut anchor = None;
+        assert!(layout.convert(Position::Absolute(Point { x: 900, y: 10 }), &mut anchor, CoordinateSpace::Normalized, Some(window)).is_err());
+        let in_window = layout.convert(Position::Absolute(Point { x: 200, y: 150 }), &mut anchor, CoordinateSpace::Normalized, Some(window));
+        assert_eq!(in_window, Ok(Position::Normalized { x: 0.25, y: 0.25, reference: Some(window) }));
+        assert_eq!(anchor, None);
+    }
+}
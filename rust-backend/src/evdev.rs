@@ -0,0 +1,806 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+use device_query::{DeviceQuery, Keycode, MouseState};
+use tokio::sync::broadcast;
+
+use crate::key_chord::Key;
+use crate::wheel::{WheelEvent, WHEEL_DELTA};
+
+// ชนิดและรหัสของเหตุการณ์ตาม linux/input-event-codes.h
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+pub const EV_ABS: u16 = 0x03;
+pub const SYN_REPORT: u16 = 0x00;
+pub const REL_X: u16 = 0x00;
+pub const REL_Y: u16 = 0x01;
+pub const REL_HWHEEL: u16 = 0x06;
+pub const REL_WHEEL: u16 = 0x08;
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+pub const BTN_LEFT: u16 = 0x110;
+pub const BTN_RIGHT: u16 = 0x111;
+pub const BTN_MIDDLE: u16 = 0x112;
+pub const BTN_SIDE: u16 = 0x113;
+pub const BTN_EXTRA: u16 = 0x114;
+pub const BTN_TOOL_PEN: u16 = 0x140;
+pub const BTN_TOOL_FINGER: u16 = 0x145;
+pub const BTN_TOUCH: u16 = 0x14a;
+
+const KEY_LEFTSHIFT: u16 = 42;
+
+// เหตุการณ์ evdev หนึ่งรายการ (ไม่รวมเวลา)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEvent {
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl InputEvent {
+    pub fn new(kind: u16, code: u16, value: i32) -> Self {
+        Self { kind, code, value }
+    }
+
+    // ปิดกลุ่มเหตุการณ์ที่เกิดพร้อมกัน
+    pub fn sync() -> Self {
+        Self::new(EV_SYN, SYN_REPORT, 0)
+    }
+}
+
+// ที่มาของเหตุการณ์สำหรับ EvdevMonitor ค่า ABS_X/ABS_Y ต้องเป็นพิกัดบนจอแล้ว (ดู AbsAxes)
+pub trait EventSource: Send {
+    // รอเหตุการณ์ไม่เกิน timeout คืนรายการว่างถ้าไม่มีเหตุการณ์
+    fn read_events(&mut self, timeout: Duration) -> std::io::Result<Vec<InputEvent>>;
+}
+
+// ปลายทางของเหตุการณ์ที่ UinputBackend ส่งออก
+pub trait EventSink: Send + Sync {
+    fn emit(&self, events: &[InputEvent]) -> std::io::Result<()>;
+}
+
+// อุปกรณ์ evdev จำลอง เหตุการณ์ที่ emit จะอ่านได้จาก read_events ของทุกสำเนา (clone ใช้คิวเดียวกัน)
+// ใช้ทดสอบ UinputBackend และ EvdevMonitor บนเครื่องที่ไม่มี /dev/uinput
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+pub struct FakeEvdev {
+    queue: Arc<(Mutex<VecDeque<InputEvent>>, Condvar)>,
+}
+
+#[allow(dead_code)]
+impl FakeEvdev {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventSink for FakeEvdev {
+    fn emit(&self, events: &[InputEvent]) -> std::io::Result<()> {
+        let (queue, ready) = &*self.queue;
+        queue.lock().unwrap().extend(events.iter().copied());
+        ready.notify_all();
+        Ok(())
+    }
+}
+
+impl EventSource for FakeEvdev {
+    fn read_events(&mut self, timeout: Duration) -> std::io::Result<Vec<InputEvent>> {
+        let (queue, ready) = &*self.queue;
+        let (mut queue, _) = ready
+            .wait_timeout_while(queue.lock().unwrap(), timeout, |queue| queue.is_empty())
+            .unwrap();
+        Ok(queue.drain(..).collect())
+    }
+}
+
+// ขนาดหน้าจอจาก AUTOMATION_SCREEN_SIZE (เช่น 2560x1440) ใช้กำหนดช่วงพิกัดของ uinput และ evdev
+// Wayland ไม่ให้โปรแกรมถามขนาดหน้าจอเอง ถ้าไม่ระบุจะใช้ 1920x1080
+pub fn screen_size_from_env() -> (i32, i32) {
+    let size = std::env::var("AUTOMATION_SCREEN_SIZE").ok().and_then(|value| {
+        let (width, height) = value.trim().split_once(['x', 'X'])?;
+        Some((width.trim().parse::<i32>().ok()?, height.trim().parse::<i32>().ok()?))
+    });
+    match size {
+        Some((width, height)) if width > 0 && height > 0 => (width, height),
+        Some(_) | None => (1920, 1080),
+    }
+}
+
+// สถานะเมาส์และคีย์บอร์ดที่สะสมจากเหตุการณ์ evdev
+#[derive(Debug, Default)]
+struct EvdevState {
+    position: (i32, i32),
+    // position มาจากอุปกรณ์ ABS จริง ไม่ใช่ค่าประมาณจากการรวม REL
+    position_exact: bool,
+    // index เดียวกับ device_query บน Windows: 1 ซ้าย, 2 ขวา, 3 กลาง, 4 X1, 5 X2
+    buttons: [bool; 6],
+    // รหัสคีย์ของ Linux ที่กดอยู่ตามลำดับที่กด
+    keys: Vec<u16>,
+}
+
+// ติดตามเมาส์/คีย์บอร์ดจากเหตุการณ์ evdev ใช้แทน device_query บน Wayland สำหรับการบันทึก
+// evdev ไม่รู้ตำแหน่งจริงของ cursor: ตำแหน่งจะถูกต้องเฉพาะหลังเหตุการณ์ ABS ของ tablet/จอสัมผัส
+// ซึ่งถือว่าพื้นที่ของอุปกรณ์ตรงกับทั้งจอ ส่วนเมาส์และ touchpad (REL) เป็นแค่ค่าประมาณจากการรวมระยะ
+// โดยเริ่มที่กลางจอและไม่คิด pointer acceleration ของ compositor (ดู knows_cursor_position)
+pub struct EvdevMonitor {
+    state: Arc<Mutex<EvdevState>>,
+    wheel: broadcast::Sender<WheelEvent>,
+}
+
+impl EvdevMonitor {
+    // อ่านจากอุปกรณ์ทั้งหมดใน /dev/input (ต้องมีสิทธิ์อ่าน เช่น อยู่ในกลุ่ม input)
+    #[cfg(target_os = "linux")]
+    pub fn open(screen: (i32, i32)) -> std::io::Result<Self> {
+        Ok(Self::with_source(EvdevDevices::open(screen)?, screen))
+    }
+
+    // อ่านเหตุการณ์จาก source ใน thread แยกจนกว่า monitor จะถูก drop
+    pub fn with_source(mut source: impl EventSource + 'static, screen: (i32, i32)) -> Self {
+        let state = Arc::new(Mutex::new(EvdevState {
+            position: (screen.0 / 2, screen.1 / 2),
+            ..EvdevState::default()
+        }));
+        let (wheel, _) = broadcast::channel(256);
+
+        let weak: Weak<Mutex<EvdevState>> = Arc::downgrade(&state);
+        let wheel_tx = wheel.clone();
+        thread::spawn(move || loop {
+            let events = match source.read_events(Duration::from_millis(100)) {
+                Ok(events) => events,
+                Err(e) => {
+                    println!("Stopped reading evdev events: {}", e);
+                    break;
+                },
+            };
+            let Some(state) = weak.upgrade() else {
+                break;
+            };
+            let mut state = state.lock().unwrap();
+            for event in events {
+                if let Some(wheel) = apply_event(&mut state, event, screen) {
+                    // ไม่มีผู้รับ (ไม่ได้บันทึกอยู่) ก็ทิ้งไป
+                    let _ = wheel_tx.send(wheel);
+                }
+            }
+        });
+
+        Self { state, wheel }
+    }
+
+    pub fn subscribe_wheel_events(&self) -> broadcast::Receiver<WheelEvent> {
+        self.wheel.subscribe()
+    }
+
+    // ตำแหน่งจาก get_mouse เป็นตำแหน่งจริงหรือไม่ (false เมื่อเมาส์ขยับด้วย REL หลัง ABS ครั้งล่าสุด)
+    pub fn knows_cursor_position(&self) -> bool {
+        self.state.lock().unwrap().position_exact
+    }
+
+    // คีย์ที่กดอยู่ รวมคีย์ที่ device_query ไม่มี (F13-F24, PrintScreen, จุดของ numpad ฯลฯ)
+    pub fn pressed_keys(&self) -> Vec<Key> {
+        self.state.lock().unwrap().keys.iter().filter_map(|&code| key_for_code(code)).collect()
+    }
+}
+
+impl DeviceQuery for EvdevMonitor {
+    fn get_mouse(&self) -> MouseState {
+        let state = self.state.lock().unwrap();
+        MouseState {
+            coords: state.position,
+            button_pressed: state.buttons.to_vec(),
+        }
+    }
+
+    fn get_keys(&self) -> Vec<Keycode> {
+        self.state.lock().unwrap().keys.iter().filter_map(|&code| keycode_for_code(code)).collect()
+    }
+}
+
+// ปรับสถานะตามเหตุการณ์ คืน WheelEvent ถ้าเป็นการเลื่อน wheel
+fn apply_event(state: &mut EvdevState, event: InputEvent, screen: (i32, i32)) -> Option<WheelEvent> {
+    match (event.kind, event.code) {
+        (EV_KEY, BTN_LEFT..=BTN_EXTRA) => {
+            let index = match event.code {
+                BTN_LEFT => 1,
+                BTN_RIGHT => 2,
+                BTN_MIDDLE => 3,
+                BTN_SIDE => 4,
+                _ => 5,
+            };
+            state.buttons[index] = event.value != 0;
+        },
+        (EV_KEY, code) => {
+            key_for_code(code)?;
+            match event.value {
+                0 => state.keys.retain(|&key| key != code),
+                // 2 คือ auto-repeat ของคีย์ที่กดค้าง
+                1 if !state.keys.contains(&code) => state.keys.push(code),
+                _ => {},
+            }
+        },
+        (EV_REL, REL_X) => {
+            state.position.0 = (state.position.0 + event.value).clamp(0, screen.0 - 1);
+            state.position_exact = false;
+        },
+        (EV_REL, REL_Y) => {
+            state.position.1 = (state.position.1 + event.value).clamp(0, screen.1 - 1);
+            state.position_exact = false;
+        },
+        (EV_ABS, ABS_X) => {
+            state.position.0 = event.value.clamp(0, screen.0 - 1);
+            state.position_exact = true;
+        },
+        (EV_ABS, ABS_Y) => {
+            state.position.1 = event.value.clamp(0, screen.1 - 1);
+            state.position_exact = true;
+        },
+        // evdev ใช้ค่าบวกสำหรับเลื่อนขึ้น แต่ WheelEvent ใช้ค่าบวกสำหรับเลื่อนลง
+        (EV_REL, REL_WHEEL) => return Some(WheelEvent { dx: 0, dy: -event.value * WHEEL_DELTA }),
+        (EV_REL, REL_HWHEEL) => return Some(WheelEvent { dx: event.value * WHEEL_DELTA, dy: 0 }),
+        _ => {},
+    }
+    None
+}
+
+// รหัสคีย์ของ Linux กับ Keycode ที่ recorder ใช้
+const KEYCODES: [(u16, Keycode); 96] = [
+    (1, Keycode::Escape),
+    (2, Keycode::Key1),
+    (3, Keycode::Key2),
+    (4, Keycode::Key3),
+    (5, Keycode::Key4),
+    (6, Keycode::Key5),
+    (7, Keycode::Key6),
+    (8, Keycode::Key7),
+    (9, Keycode::Key8),
+    (10, Keycode::Key9),
+    (11, Keycode::Key0),
+    (12, Keycode::Minus),
+    (13, Keycode::Equal),
+    (14, Keycode::Backspace),
+    (15, Keycode::Tab),
+    (16, Keycode::Q),
+    (17, Keycode::W),
+    (18, Keycode::E),
+    (19, Keycode::R),
+    (20, Keycode::T),
+    (21, Keycode::Y),
+    (22, Keycode::U),
+    (23, Keycode::I),
+    (24, Keycode::O),
+    (25, Keycode::P),
+    (26, Keycode::LeftBracket),
+    (27, Keycode::RightBracket),
+    (28, Keycode::Enter),
+    (29, Keycode::LControl),
+    (30, Keycode::A),
+    (31, Keycode::S),
+    (32, Keycode::D),
+    (33, Keycode::F),
+    (34, Keycode::G),
+    (35, Keycode::H),
+    (36, Keycode::J),
+    (37, Keycode::K),
+    (38, Keycode::L),
+    (39, Keycode::Semicolon),
+    (40, Keycode::Apostrophe),
+    (41, Keycode::Grave),
+    (42, Keycode::LShift),
+    (43, Keycode::BackSlash),
+    (44, Keycode::Z),
+    (45, Keycode::X),
+    (46, Keycode::C),
+    (47, Keycode::V),
+    (48, Keycode::B),
+    (49, Keycode::N),
+    (50, Keycode::M),
+    (51, Keycode::Comma),
+    (52, Keycode::Dot),
+    (53, Keycode::Slash),
+    (54, Keycode::RShift),
+    (55, Keycode::NumpadMultiply),
+    (56, Keycode::LAlt),
+    (57, Keycode::Space),
+    (58, Keycode::CapsLock),
+    (59, Keycode::F1),
+    (60, Keycode::F2),
+    (61, Keycode::F3),
+    (62, Keycode::F4),
+    (63, Keycode::F5),
+    (64, Keycode::F6),
+    (65, Keycode::F7),
+    (66, Keycode::F8),
+    (67, Keycode::F9),
+    (68, Keycode::F10),
+    (71, Keycode::Numpad7),
+    (72, Keycode::Numpad8),
+    (73, Keycode::Numpad9),
+    (74, Keycode::NumpadSubtract),
+    (75, Keycode::Numpad4),
+    (76, Keycode::Numpad5),
+    (77, Keycode::Numpad6),
+    (78, Keycode::NumpadAdd),
+    (79, Keycode::Numpad1),
+    (80, Keycode::Numpad2),
+    (81, Keycode::Numpad3),
+    (82, Keycode::Numpad0),
+    (87, Keycode::F11),
+    (88, Keycode::F12),
+    (97, Keycode::RControl),
+    (98, Keycode::NumpadDivide),
+    (100, Keycode::RAlt),
+    (102, Keycode::Home),
+    (103, Keycode::Up),
+    (104, Keycode::PageUp),
+    (105, Keycode::Left),
+    (106, Keycode::Right),
+    (107, Keycode::End),
+    (108, Keycode::Down),
+    (109, Keycode::PageDown),
+    (110, Keycode::Insert),
+    (111, Keycode::Delete),
+    (125, Keycode::Meta),
+];
+
+pub fn keycode_for_code(code: u16) -> Option<Keycode> {
+    KEYCODES.iter().find(|(c, _)| *c == code).map(|(_, keycode)| *keycode)
+}
+
+// คีย์ของ chord สำหรับรหัสคีย์ของ Linux (กลับด้านของ key_code) รวมคีย์ที่ไม่มีใน Keycode
+pub fn key_for_code(code: u16) -> Option<Key> {
+    let key = match code {
+        183..=194 => Key::F((code - 170) as u8),
+        69 => Key::NumLock,
+        70 => Key::ScrollLock,
+        83 => Key::NumpadDecimal,
+        96 => Key::NumpadEnter,
+        99 => Key::PrintScreen,
+        119 => Key::Pause,
+        127 => Key::Menu,
+        _ => return keycode_for_code(code).map(crate::key_chord::key_for_keycode),
+    };
+    Some(key)
+}
+
+// รหัสคีย์ของ Linux สำหรับคีย์ และต้องกด shift ด้วยหรือไม่ (อักขระตามตำแหน่งบนคีย์บอร์ด US)
+pub fn key_code(key: Key) -> Option<(u16, bool)> {
+    let code = match key {
+        Key::Char(c) => return char_code(c),
+        Key::Tab => 15,
+        Key::Enter => 28,
+        Key::Backspace => 14,
+        Key::Delete => 111,
+        Key::Insert => 110,
+        Key::Home => 102,
+        Key::End => 107,
+        Key::PageUp => 104,
+        Key::PageDown => 109,
+        Key::Up => 103,
+        Key::Down => 108,
+        Key::Left => 105,
+        Key::Right => 106,
+        Key::Space => 57,
+        Key::Escape => 1,
+        Key::CapsLock => 58,
+        Key::NumLock => 69,
+        Key::ScrollLock => 70,
+        Key::PrintScreen => 99,
+        Key::Pause => 119,
+        Key::Menu => 127,
+        Key::Shift => KEY_LEFTSHIFT,
+        Key::Control => 29,
+        Key::Alt => 56,
+        Key::Meta => 125,
+        Key::F(n @ 1..=10) => 58 + u16::from(n),
+        Key::F(11) => 87,
+        Key::F(12) => 88,
+        Key::F(n @ 13..=24) => 170 + u16::from(n),
+        Key::F(_) => return None,
+        Key::Numpad(0) => 82,
+        Key::Numpad(n @ 1..=3) => 78 + u16::from(n),
+        Key::Numpad(n @ 4..=6) => 71 + u16::from(n),
+        Key::Numpad(n @ 7..=9) => 64 + u16::from(n),
+        Key::Numpad(_) => return None,
+        Key::NumpadAdd => 78,
+        Key::NumpadSubtract => 74,
+        Key::NumpadMultiply => 55,
+        Key::NumpadDivide => 98,
+        Key::NumpadDecimal => 83,
+        Key::NumpadEnter => 96,
+    };
+    Some((code, false))
+}
+
+fn char_code(c: char) -> Option<(u16, bool)> {
+    const UNSHIFTED: &str = "1234567890-=qwertyuiop[]asdfghjkl;'`\\zxcvbnm,./";
+    const SHIFTED: &str = "!@#$%^&*()_+QWERTYUIOP{}ASDFGHJKL:\"~|ZXCVBNM<>?";
+    // รหัสของอักขระแต่ละตัวใน UNSHIFTED/SHIFTED ตามลำดับ
+    const CODES: [u16; 47] = [
+        2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 30, 31, 32, 33, 34,
+        35, 36, 37, 38, 39, 40, 41, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53,
+    ];
+    match c {
+        ' ' => Some((57, false)),
+        '\n' => Some((28, false)),
+        '\t' => Some((15, false)),
+        _ => {
+            if let Some(index) = UNSHIFTED.chars().position(|u| u == c) {
+                Some((CODES[index], false))
+            } else {
+                SHIFTED.chars().position(|s| s == c).map(|index| (CODES[index], true))
+            }
+        },
+    }
+}
+
+// เหตุการณ์กดและปล่อยคีย์ (รวม shift ถ้าจำเป็น)
+pub(crate) fn key_events(code: u16, shift: bool, value: i32) -> Vec<InputEvent> {
+    let key = InputEvent::new(EV_KEY, code, value);
+    match (shift, value) {
+        (false, _) => vec![key],
+        (true, 0) => vec![key, InputEvent::new(EV_KEY, KEY_LEFTSHIFT, 0)],
+        (true, _) => vec![InputEvent::new(EV_KEY, KEY_LEFTSHIFT, value), key],
+    }
+}
+
+// แกน ABS_X/ABS_Y ของอุปกรณ์หนึ่งตัว ใช้แปลงค่าของอุปกรณ์ก่อนส่งให้ EvdevMonitor
+// tablet/จอสัมผัส: ย่อช่วงค่าของอุปกรณ์ให้เป็นพิกัดบนจอ
+// touchpad: ตำแหน่งนิ้วบน pad ไม่ใช่ตำแหน่ง cursor จึงแปลงเป็นระยะ REL (หน่วยของ pad ไม่คิด acceleration)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub(crate) struct AbsAxes {
+    x: (i32, i32),
+    y: (i32, i32),
+    touchpad: bool,
+    // ค่าล่าสุดระหว่างที่นิ้วแตะ touchpad อยู่
+    last: (Option<i32>, Option<i32>),
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl AbsAxes {
+    // ช่วงค่า (min, max) ของ ABS_X และ ABS_Y คืน None ถ้าใช้ไม่ได้
+    pub(crate) fn new(x: (i32, i32), y: (i32, i32), touchpad: bool) -> Option<Self> {
+        if x.1 <= x.0 || y.1 <= y.0 {
+            return None;
+        }
+        Some(Self { x, y, touchpad, last: (None, None) })
+    }
+
+    // แปลงเหตุการณ์ ABS ของอุปกรณ์ เหตุการณ์อื่นคืนตามเดิม
+    pub(crate) fn convert(&mut self, event: InputEvent, screen: (i32, i32)) -> Option<InputEvent> {
+        match (event.kind, event.code) {
+            (EV_ABS, ABS_X) if self.touchpad => {
+                let delta = self.last.0.map(|last| event.value - last);
+                self.last.0 = Some(event.value);
+                delta.map(|delta| InputEvent::new(EV_REL, REL_X, delta))
+            },
+            (EV_ABS, ABS_Y) if self.touchpad => {
+                let delta = self.last.1.map(|last| event.value - last);
+                self.last.1 = Some(event.value);
+                delta.map(|delta| InputEvent::new(EV_REL, REL_Y, delta))
+            },
+            (EV_ABS, ABS_X) => Some(InputEvent::new(EV_ABS, ABS_X, scale_axis(event.value, self.x, screen.0))),
+            (EV_ABS, ABS_Y) => Some(InputEvent::new(EV_ABS, ABS_Y, scale_axis(event.value, self.y, screen.1))),
+            (EV_KEY, BTN_TOUCH) if event.value == 0 => {
+                // ยกนิ้วแล้ว การแตะครั้งต่อไปเริ่มนับระยะใหม่
+                self.last = (None, None);
+                Some(event)
+            },
+            _ => Some(event),
+        }
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn scale_axis(value: i32, (min, max): (i32, i32), size: i32) -> i32 {
+    let offset = i64::from(value.clamp(min, max) - min);
+    (offset * i64::from(size - 1) / i64::from(max - min)) as i32
+}
+
+// หมายเลข ioctl แบบ _IOC ของ Linux (ใช้กับ x86 และ ARM)
+#[cfg(target_os = "linux")]
+pub(crate) const fn ioc(dir: u32, kind: u8, nr: u8, size: usize) -> u32 {
+    (dir << 30) | ((size as u32) << 16) | ((kind as u32) << 8) | nr as u32
+}
+
+// อุปกรณ์ทั้งหมดใน /dev/input ที่อ่านได้ (ยกเว้นอุปกรณ์ uinput ของโปรแกรมเอง)
+#[cfg(target_os = "linux")]
+pub struct EvdevDevices {
+    devices: Vec<(std::fs::File, Option<AbsAxes>)>,
+    screen: (i32, i32),
+}
+
+#[cfg(target_os = "linux")]
+impl EvdevDevices {
+    pub fn open(screen: (i32, i32)) -> std::io::Result<Self> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut devices = Vec::new();
+        for entry in std::fs::read_dir("/dev/input")? {
+            let path = entry?.path();
+            if !path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("event")) {
+                continue;
+            }
+            let file = match std::fs::OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(&path) {
+                Ok(file) => file,
+                // ไม่มีสิทธิ์อ่านอุปกรณ์นี้
+                Err(_) => continue,
+            };
+            if device_name(&file).as_deref() == Some(crate::uinput::UINPUT_DEVICE_NAME) {
+                continue;
+            }
+            let axes = abs_axes(&file);
+            devices.push((file, axes));
+        }
+        if devices.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "no readable devices in /dev/input (is the user in the `input` group?)",
+            ));
+        }
+        println!("Reading input from {} evdev devices", devices.len());
+        Ok(Self { devices, screen })
+    }
+}
+
+// ช่วงค่าของ ABS_X/ABS_Y (EVIOCGABS) และดูว่าเป็น touchpad หรือไม่ (มี BTN_TOOL_FINGER แต่ไม่มีปากกา)
+#[cfg(target_os = "linux")]
+fn abs_axes(file: &std::fs::File) -> Option<AbsAxes> {
+    use std::os::unix::io::AsRawFd;
+
+    let range = |axis: u16| {
+        let mut info: libc::input_absinfo = unsafe { std::mem::zeroed() };
+        let request = ioc(2, b'E', 0x40 + axis as u8, std::mem::size_of::<libc::input_absinfo>());
+        let result = unsafe { libc::ioctl(file.as_raw_fd(), request as _, &mut info) };
+        (result >= 0).then_some((info.minimum, info.maximum))
+    };
+    let (x, y) = (range(ABS_X)?, range(ABS_Y)?);
+
+    // EVIOCGBIT(EV_KEY)
+    let mut keys = [0u8; 96];
+    let len = unsafe { libc::ioctl(file.as_raw_fd(), ioc(2, b'E', 0x20 + EV_KEY as u8, keys.len()) as _, keys.as_mut_ptr()) };
+    let has = |code: u16| len > 0 && keys[usize::from(code / 8)] & (1 << (code % 8)) != 0;
+    AbsAxes::new(x, y, has(BTN_TOOL_FINGER) && !has(BTN_TOOL_PEN))
+}
+
+#[cfg(target_os = "linux")]
+fn device_name(file: &std::fs::File) -> Option<String> {
+    use std::os::unix::io::AsRawFd;
+
+    // EVIOCGNAME
+    let mut name = [0u8; 256];
+    let len = unsafe { libc::ioctl(file.as_raw_fd(), ioc(2, b'E', 0x06, name.len()) as _, name.as_mut_ptr()) };
+    if len <= 0 {
+        return None;
+    }
+    let name = &name[..len as usize];
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    Some(String::from_utf8_lossy(&name[..end]).into_owned())
+}
+
+#[cfg(target_os = "linux")]
+impl EventSource for EvdevDevices {
+    fn read_events(&mut self, timeout: Duration) -> std::io::Result<Vec<InputEvent>> {
+        use std::io::Read;
+        use std::os::unix::io::AsRawFd;
+
+        let mut fds: Vec<libc::pollfd> = self
+            .devices
+            .iter()
+            .map(|(file, _)| libc::pollfd { fd: file.as_raw_fd(), events: libc::POLLIN, revents: 0 })
+            .collect();
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout.as_millis() as libc::c_int) };
+        if ready < 0 {
+            let error = std::io::Error::last_os_error();
+            return if error.kind() == std::io::ErrorKind::Interrupted { Ok(Vec::new()) } else { Err(error) };
+        }
+
+        let size = std::mem::size_of::<libc::input_event>();
+        let mut buffer = vec![0u8; size * 64];
+        let mut events = Vec::new();
+        let mut unplugged = Vec::new();
+        for (index, fd) in fds.iter().enumerate() {
+            if fd.revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) != 0 {
+                unplugged.push(index);
+                continue;
+            }
+            if fd.revents & libc::POLLIN == 0 {
+                continue;
+            }
+            loop {
+                let (device, axes) = &mut self.devices[index];
+                let read = match device.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(_) => {
+                        unplugged.push(index);
+                        break;
+                    },
+                };
+                for chunk in buffer[..read].chunks_exact(size) {
+                    let raw: libc::input_event = unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const libc::input_event) };
+                    let event = InputEvent::new(raw.type_, raw.code, raw.value);
+                    let event = match axes {
+                        Some(axes) => axes.convert(event, self.screen),
+                        None => Some(event),
+                    };
+                    events.extend(event);
+                }
+            }
+        }
+        // อุปกรณ์ที่ถูกถอดออก
+        for index in unplugged.into_iter().rev() {
+            self.devices.remove(index);
+        }
+        if self.devices.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "all evdev devices were removed"));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_backend::InputBackend;
+    use crate::models::MouseButton;
+    use crate::uinput::UinputBackend;
+
+    fn wait_for(monitor: &EvdevMonitor, check: impl Fn(&MouseState, &[Keycode]) -> bool) -> bool {
+        for _ in 0..100 {
+            if check(&monitor.get_mouse(), &monitor.get_keys()) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn uinput_backend_writes_absolute_moves_and_wheel() {
+        let mut device = FakeEvdev::new();
+        let backend = UinputBackend::with_sink(device.clone(), (800, 600));
+        backend.move_to(900, 20).unwrap();
+        backend.scroll(0, 2).unwrap();
+
+        let events = device.read_events(Duration::ZERO).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                InputEvent::new(EV_ABS, ABS_X, 799),
+                InputEvent::new(EV_ABS, ABS_Y, 20),
+                InputEvent::sync(),
+                InputEvent::new(EV_REL, REL_WHEEL, -2),
+                InputEvent::sync(),
+            ]
+        );
+        assert_eq!(backend.cursor_position().unwrap(), (799, 20));
+    }
+
+    #[test]
+    fn monitor_sees_keys_and_buttons_from_uinput_backend() {
+        let device = FakeEvdev::new();
+        let monitor = EvdevMonitor::with_source(device.clone(), (800, 600));
+        let backend = UinputBackend::with_sink(device, (800, 600));
+
+        backend.key_down("ctrl").unwrap();
+        backend.key_down("A").unwrap();
+        backend.button_down(MouseButton::Right).unwrap();
+        assert!(wait_for(&monitor, |mouse, keys| {
+            mouse.button_pressed[2] && keys == [Keycode::LControl, Keycode::LShift, Keycode::A]
+        }));
+
+        backend.key_up("A").unwrap();
+        backend.key_up("ctrl").unwrap();
+        backend.button_up(MouseButton::Right).unwrap();
+        assert!(wait_for(&monitor, |mouse, keys| !mouse.button_pressed[2] && keys.is_empty()));
+    }
+
+    #[test]
+    fn monitor_tracks_relative_motion_and_wheel() {
+        let device = FakeEvdev::new();
+        let monitor = EvdevMonitor::with_source(device.clone(), (800, 600));
+        let mut wheel = monitor.subscribe_wheel_events();
+
+        device
+            .emit(&[
+                InputEvent::new(EV_REL, REL_X, 50),
+                InputEvent::new(EV_REL, REL_Y, -1000),
+                InputEvent::new(EV_REL, REL_WHEEL, 1),
+                InputEvent::sync(),
+            ])
+            .unwrap();
+        assert!(wait_for(&monitor, |mouse, _| mouse.coords == (450, 0)));
+        assert_eq!(wheel.try_recv().unwrap(), WheelEvent { dx: 0, dy: -WHEEL_DELTA });
+    }
+
+    #[test]
+    fn monitor_knows_position_only_after_absolute_events() {
+        let device = FakeEvdev::new();
+        let monitor = EvdevMonitor::with_source(device.clone(), (800, 600));
+        let backend = UinputBackend::with_sink(device.clone(), (800, 600));
+        assert!(!monitor.knows_cursor_position());
+
+        backend.move_to(120, 340).unwrap();
+        assert!(wait_for(&monitor, |mouse, _| mouse.coords == (120, 340)));
+        assert!(monitor.knows_cursor_position());
+
+        device.emit(&[InputEvent::new(EV_REL, REL_X, 5), InputEvent::sync()]).unwrap();
+        assert!(wait_for(&monitor, |mouse, _| mouse.coords == (125, 340)));
+        assert!(!monitor.knows_cursor_position());
+    }
+
+    #[test]
+    fn tablet_axes_scale_to_the_screen() {
+        let mut axes = AbsAxes::new((0, 20000), (-100, 100), false).unwrap();
+        let x = axes.convert(InputEvent::new(EV_ABS, ABS_X, 10000), (1920, 1080));
+        let y = axes.convert(InputEvent::new(EV_ABS, ABS_Y, 100), (1920, 1080));
+        assert_eq!(x, Some(InputEvent::new(EV_ABS, ABS_X, 959)));
+        assert_eq!(y, Some(InputEvent::new(EV_ABS, ABS_Y, 1079)));
+        assert_eq!(axes.convert(InputEvent::sync(), (1920, 1080)), Some(InputEvent::sync()));
+        assert!(AbsAxes::new((0, 0), (0, 100), false).is_none());
+    }
+
+    #[test]
+    fn touchpad_axes_become_relative_motion() {
+        let mut axes = AbsAxes::new((0, 4000), (0, 3000), true).unwrap();
+        let screen = (1920, 1080);
+        // การแตะครั้งแรกยังไม่มีระยะ
+        assert_eq!(axes.convert(InputEvent::new(EV_ABS, ABS_X, 1000), screen), None);
+        assert_eq!(axes.convert(InputEvent::new(EV_ABS, ABS_X, 1030), screen), Some(InputEvent::new(EV_REL, REL_X, 30)));
+        assert_eq!(axes.convert(InputEvent::new(EV_ABS, ABS_Y, 500), screen), None);
+        assert_eq!(axes.convert(InputEvent::new(EV_ABS, ABS_Y, 480), screen), Some(InputEvent::new(EV_REL, REL_Y, -20)));
+
+        // ยกนิ้วแล้วแตะใหม่ที่อื่น cursor ไม่กระโดด
+        axes.convert(InputEvent::new(EV_KEY, BTN_TOUCH, 0), screen);
+        assert_eq!(axes.convert(InputEvent::new(EV_ABS, ABS_X, 3000), screen), None);
+    }
+
+    #[test]
+    fn typed_characters_follow_us_layout() {
+        assert_eq!(key_code(Key::Char('a')), Some((30, false)));
+        assert_eq!(key_code(Key::Char('?')), Some((53, true)));
+        assert_eq!(key_code(Key::Char('ก')), None);
+        assert_eq!(key_code(Key::F(13)), Some((183, false)));
+        assert_eq!(key_code(Key::Numpad(7)), Some((71, false)));
+    }
+
+    #[test]
+    fn linux_key_codes_map_both_ways() {
+        // คีย์ด้านขวาเล่นซ้ำด้วยรหัสของคีย์ด้านซ้าย
+        const RIGHT_MODIFIERS: [(u16, u16); 3] = [(54, KEY_LEFTSHIFT), (97, 29), (100, 56)];
+        for code in 0..=255u16 {
+            let Some(key) = key_for_code(code) else {
+                continue;
+            };
+            let (played, _) = key_code(key).unwrap_or_else(|| panic!("{:?} from code {} cannot be played", key, code));
+            let expected = RIGHT_MODIFIERS.iter().find(|(right, _)| *right == code).map_or(code, |(_, left)| *left);
+            assert_eq!(played, expected, "{:?}", key);
+        }
+
+        let keys = (1..=24).map(Key::F).chain([Key::PrintScreen, Key::NumpadDecimal, Key::NumpadEnter, Key::Pause]);
+        for key in keys {
+            let (code, _) = key_code(key).unwrap();
+            assert_eq!(key_for_code(code), Some(key), "code {}", code);
+        }
+    }
+
+    #[test]
+    fn monitor_reports_keys_outside_device_query() {
+        let device = FakeEvdev::new();
+        let monitor = EvdevMonitor::with_source(device.clone(), (800, 600));
+        let backend = UinputBackend::with_sink(device, (800, 600));
+
+        backend.key_down("shift").unwrap();
+        backend.key_down("f13").unwrap();
+        backend.key_down("printscreen").unwrap();
+        assert!(wait_for(&monitor, |_, _| monitor.pressed_keys().len() == 3));
+        assert_eq!(monitor.pressed_keys(), [Key::Shift, Key::F(13), Key::PrintScreen]);
+        // device_query ไม่มี F13 และ PrintScreen
+        assert_eq!(monitor.get_keys(), [Keycode::LShift]);
+    }
+}
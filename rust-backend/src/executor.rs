@@ -0,0 +1,1351 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use warp::ws::Message;
+
+use crate::automation::{AutomationController, RunHandle};
+use crate::clipboard::{ClipboardProvider, MemoryClipboard};
+use crate::display::DisplayLayout;
+use crate::input_actor::{InputActor, TimingProfile};
+use crate::input_backend::RecordingInputBackend;
+use crate::models::{
+    CoordinateJitter, ErrorPolicy, GroupStep, MacroStep, MouseClickStep, MovementProfile, Point, RandomTimingConfig, Step,
+};
+use crate::mouse_keyboard::{release_held_inputs, HeldInputs};
+use crate::websocket::{broadcast_to_clients, create_message};
+
+type Clients = HashMap<String, mpsc::UnboundedSender<Message>>;
+
+// ตำแหน่งของขั้นตอนในแต่ละชั้น ชั้นแรกคือรายการหลัก ชั้นถัดไปคือขั้นตอนภายใน group
+#[derive(Debug, Clone, Serialize)]
+pub struct StepPathSegment {
+    // ตำแหน่งของขั้นตอนในรายการของชั้นนี้
+    pub index: usize,
+    // รอบปัจจุบันของรายการในชั้นนี้ (ชั้นแรกคือรอบของ macro ชั้นถัดไปคือรอบของ group)
+    pub loop_index: u32,
+    pub total_loops: u32,
+}
+
+// คำขอเริ่มรอบการทำงาน
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRequest {
+    pub steps: Vec<MacroStep>,
+    pub loop_count: i32,
+    // ถ้าระบุ จะทำเฉพาะขั้นตอนที่มี id ตรงกัน (index ที่แจ้งยังอ้างอิงตำแหน่งใน steps)
+    pub selected_ids: Option<Vec<String>>,
+    // การสุ่มเวลาเฉพาะของรอบนี้ ถ้าไม่ระบุจะใช้ค่าปัจจุบันของ controller
+    pub random_timing: Option<RandomTimingConfig>,
+    // รันแบบ debug จะหยุดก่อนขั้นตอนที่มี breakpoint และรอคำสั่ง debug
+    pub debug: bool,
+    pub range: StepRange,
+    // วิธีจัดการข้อผิดพลาดของขั้นตอนที่ไม่ได้กำหนด on_error ไว้
+    pub on_error: ErrorPolicy,
+    // ถ้าระบุ จะจำลองการทำงานโดยไม่ส่ง input จริง และแจ้งสิ่งที่จะเกิดขึ้นผ่าน simulated_step
+    pub simulate: Option<Simulation>,
+}
+
+// การตั้งค่าการจำลองการทำงาน
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    // ตัวคูณเวลารอของทุกขั้นตอน (1.0 = เวลาจริง, 0.1 = เร็วขึ้น 10 เท่า, 0 = ไม่รอ)
+    pub time_scale: f64,
+}
+
+// จุดเริ่มและจุดสิ้นสุดของรอบการทำงาน (index อ้างอิงตำแหน่งในรายการหลัก เริ่มที่ 0)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StepRange {
+    // ขั้นตอนที่เริ่มทำในรอบแรก (start_loop) รอบถัดไปจะเริ่มจากขั้นตอนแรกตามปกติ
+    pub start_index: usize,
+    // ขั้นตอนสุดท้ายที่ทำในทุกรอบ (รวมขั้นตอนนี้ด้วย)
+    pub end_index: Option<usize>,
+    // ข้ามรอบก่อนหน้านี้ ใช้ทำงานต่อจากจุดที่หยุดไป
+    pub start_loop: u32,
+}
+
+// คำสั่งที่ส่งให้รอบ debug ขณะหยุดอยู่ที่ breakpoint (การยกเลิกใช้ stop_run เหมือนรอบปกติ)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugCommand {
+    // ทำขั้นตอนถัดไปหนึ่งขั้นตอนแล้วหยุดอีกครั้ง
+    Step,
+    // ทำงานต่อจนถึง breakpoint ถัดไป
+    Continue,
+}
+
+// สถานะของรอบ debug
+struct DebugState {
+    commands: mpsc::UnboundedReceiver<DebugCommand>,
+    // true หลังจาก debug_step เพื่อหยุดก่อนขั้นตอนถัดไปแม้ไม่มี breakpoint
+    stepping: bool,
+}
+
+// ผลการทำงานของขั้นตอน
+enum Flow {
+    Completed,
+    // ผู้ใช้สั่งหยุด
+    Stopped,
+    // ขั้นตอนล้มเหลวและ on_error สั่งให้หยุดรอบการทำงาน
+    Aborted,
+}
+
+// ข้อมูลที่ใช้ร่วมกันตลอดรอบการทำงาน ส่งต่อไปยังขั้นตอนใน group ทุกชั้น
+struct RunContext {
+    controller: Arc<Mutex<AutomationController>>,
+    clients: Clients,
+    input: InputActor,
+    // backend ที่บันทึก input แทนการส่งจริง (เฉพาะรอบจำลอง)
+    simulation: Option<Arc<RecordingInputBackend>>,
+    clipboard: Arc<dyn ClipboardProvider>,
+    // การจัดวางจอตอนเริ่มรอบ ใช้แปลงตำแหน่งที่บันทึกเทียบกับจอ
+    displays: DisplayLayout,
+    time_scale: f64,
+    // คีย์และปุ่มเมาส์ที่ขั้นตอนกดค้างไว้ จะถูกปล่อยทั้งหมดเมื่อรอบการทำงานจบ
+    held_inputs: HeldInputs,
+    run_id: String,
+    cancel: CancellationToken,
+    pause_rx: watch::Receiver<bool>,
+    random_timing: Option<RandomTimingConfig>,
+    // การสุ่มตำแหน่งเมาส์ของรอบนี้ (ขั้นตอนที่กำหนด jitter เองจะใช้ค่าของขั้นตอน)
+    jitter: CoordinateJitter,
+    debug: Option<DebugState>,
+    total_steps: usize,
+    // จำนวนขั้นตอนในรายการหลักที่ทำเสร็จแล้วในรอบนี้
+    completed_steps: usize,
+    // จำนวนครั้งที่ขั้นตอน (ไม่นับ group) ทำงานสำเร็จ/ล้มเหลวตลอดรอบการทำงาน
+    succeeded: usize,
+    failed: usize,
+}
+
+// แจ้งสถานะ running แล้วเริ่มทำงานรอบที่ได้ RunHandle มาแล้ว
+pub(crate) fn launch_run(
+    automation_controller: &Arc<Mutex<AutomationController>>,
+    controller: &mut AutomationController,
+    request: RunRequest,
+    run: RunHandle,
+    description: String,
+    queue_id: Option<&str>,
+) {
+    // รอบ debug จะรับคำสั่ง debug_step/debug_continue ผ่าน channel ที่เก็บไว้ใน controller
+    let debug = if request.debug {
+        let (tx, rx) = mpsc::unbounded_channel();
+        controller.debug_commands = Some(tx);
+        Some(DebugState { commands: rx, stepping: false })
+    } else {
+        controller.debug_commands = None;
+        None
+    };
+
+    // แจ้งการอัปเดต
+    let status_msg = create_message("status_update", json!({
+        "status": "running",
+        "message": description,
+        "run_id": run.run_id,
+        "queue_id": queue_id,
+        "debug": request.debug,
+        "simulate": request.simulate.is_some()
+    }));
+    broadcast_to_clients(&controller.clients, status_msg);
+    println!("Started run {}: {}", run.run_id, description);
+
+    execute_automation(automation_controller.clone(), request, run, debug);
+}
+
+// เริ่มรายการแรกในคิวถ้าไม่มีรอบที่กำลังทำงานอยู่ คืนค่า true ถ้าเริ่มรอบใหม่
+pub(crate) fn start_next_queued(
+    automation_controller: &Arc<Mutex<AutomationController>>,
+    controller: &mut AutomationController,
+) -> bool {
+    if controller.is_running() {
+        return false;
+    }
+    let entry = match controller.run_queue.pop_front() {
+        Some(entry) => entry,
+        None => return false,
+    };
+    let run = match controller.begin_run() {
+        Ok(run) => run,
+        Err(_) => {
+            controller.run_queue.push_front(entry);
+            return false;
+        }
+    };
+
+    let description = match &entry.name {
+        Some(name) => format!("Running queued automation \"{}\" with {} loops", name, entry.request.loop_count),
+        None => format!("Running queued automation with {} loops", entry.request.loop_count),
+    };
+    broadcast_queue(controller);
+    launch_run(automation_controller, controller, entry.request, run, description, Some(&entry.queue_id));
+    true
+}
+
+// แจ้งรายการในคิวปัจจุบันให้ผู้ใช้ทั้งหมด
+pub(crate) fn broadcast_queue(controller: &AutomationController) {
+    let queue_msg = create_message("queue_updated", json!({ "queue": controller.run_queue }));
+    broadcast_to_clients(&controller.clients, queue_msg);
+}
+
+// ฟังก์ชันสำหรับทำงานตามขั้นตอนที่กำหนด (สร้าง task แยกแล้วคืนค่าทันที)
+fn execute_automation(
+    controller: Arc<Mutex<AutomationController>>,
+    request: RunRequest,
+    run: RunHandle,
+    debug: Option<DebugState>,
+) {
+    println!("Starting execute_automation function (run {})", run.run_id);
+    let RunRequest { steps, loop_count, selected_ids, random_timing, range, on_error, simulate, .. } = request;
+    // สร้าง task ใหม่เพื่อป้องกันการบล็อค thread
+    tokio::spawn(async move {
+        // เก็บ clients ไว้เพื่อส่งอัพเดทสถานะ
+        let (clients, pause_rx, input, clipboard, jitter, display_provider, monitor_fallback) = {
+            let controller = controller.lock().await;
+            let jitter = random_timing.as_ref().map_or(controller.jitter, |config| config.jitter);
+            (
+                controller.clients.clone(),
+                controller.pause_signal.subscribe(),
+                controller.input.clone(),
+                controller.clipboard.clone(),
+                jitter,
+                controller.displays.clone(),
+                controller.monitor_fallback,
+            )
+        };
+        let displays = DisplayLayout::query(&display_provider, monitor_fallback).await;
+
+        // รอบจำลองใช้ backend ที่บันทึก input ไว้แทนเมาส์/คีย์บอร์ดจริง และไม่แตะคลิปบอร์ดของระบบ
+        // ไม่มีโปรแกรมปลายทางต้องรอ input จึงหน่วงระหว่าง input ด้วย profile ที่เร็วที่สุด
+        let simulation = simulate.as_ref().map(|_| Arc::new(RecordingInputBackend::new()));
+        let (input, clipboard): (InputActor, Arc<dyn ClipboardProvider>) = match &simulation {
+            Some(recorder) => (InputActor::spawn(recorder.clone(), TimingProfile::Fast), Arc::new(MemoryClipboard::new())),
+            None => (input, clipboard),
+        };
+        let time_scale = simulate.as_ref().map_or(1.0, |simulate| simulate.time_scale);
+
+        // กรองเฉพาะขั้นตอนที่เลือก (ถ้ามีการระบุ) โดยเก็บ index ในรายการเดิมไว้ใช้แจ้งสถานะ
+        let end_index = range.end_index.unwrap_or(usize::MAX);
+        let filtered_steps: Vec<(usize, MacroStep)> = match &selected_ids {
+            Some(ids) => {
+                println!("Filtering steps by selected IDs: {:?}", ids);
+                steps.into_iter()
+                    .enumerate()
+                    .filter(|(index, step)| *index <= end_index && ids.contains(&step.id))
+                    .collect()
+            },
+            None => steps.into_iter().enumerate().filter(|(index, _)| *index <= end_index).collect(),
+        };
+
+        println!("Executing {} steps with {} loops", filtered_steps.len(), loop_count);
+
+        let mut ctx = RunContext {
+            controller,
+            clients,
+            input,
+            simulation,
+            clipboard,
+            displays,
+            time_scale,
+            held_inputs: HeldInputs::default(),
+            run_id: run.run_id,
+            cancel: run.cancel,
+            pause_rx,
+            random_timing,
+            jitter,
+            debug,
+            total_steps: filtered_steps.len(),
+            completed_steps: 0,
+            succeeded: 0,
+            failed: 0,
+        };
+        // นับรอบที่ข้ามไปด้วย เพื่อให้ทำงานต่อจาก completed_loops ได้ตรงรอบ
+        let mut completed_loops = range.start_loop;
+        let mut aborted = false;
+
+        if range.start_index > 0 || range.start_loop > 0 {
+            println!("Starting from step {} of loop {}", range.start_index + 1, range.start_loop + 1);
+        }
+
+        'run: for loop_index in range.start_loop as i32..loop_count {
+            println!("Starting loop {}/{}", loop_index + 1, loop_count);
+
+            // เช็คว่ายังทำงานอยู่หรือไม่
+            if ctx.cancel.is_cancelled() {
+                println!("Automation stopped");
+                break;
+            }
+
+            // รอบแรกเริ่มจาก start_index ส่วนรอบถัดไปเริ่มจากขั้นตอนแรก
+            let first_index = if loop_index == range.start_loop as i32 { range.start_index } else { 0 };
+
+            for (position, (index, step)) in filtered_steps.iter().enumerate() {
+                if *index < first_index {
+                    continue;
+                }
+                ctx.completed_steps = position;
+                let mut path = vec![StepPathSegment {
+                    index: *index,
+                    loop_index: loop_index as u32,
+                    total_loops: loop_count as u32,
+                }];
+
+                match run_step(&mut ctx, step, &mut path, on_error).await {
+                    Flow::Completed => {},
+                    Flow::Stopped => break 'run,
+                    Flow::Aborted => {
+                        aborted = true;
+                        break 'run;
+                    },
+                }
+            }
+
+            completed_loops += 1;
+        }
+
+        let stopped = ctx.cancel.is_cancelled();
+
+        // ปล่อยคีย์และปุ่มเมาส์ที่ยังค้างอยู่ ไม่ว่ารอบการทำงานจะจบ ล้มเหลว หรือถูกหยุด
+        let (released_keys, released_buttons) = release_held_inputs(&ctx.input, &ctx.held_inputs).await;
+        if !released_keys.is_empty() || !released_buttons.is_empty() {
+            println!("Released held keys [{}] and mouse buttons [{}]", released_keys.join(", "), released_buttons.join(", "));
+        }
+
+        // แจ้งว่าการทำงานเสร็จสิ้น (รวมกรณีที่ถูกหยุด เพื่อให้ client รู้ว่าการทำงานจบแล้วจริง)
+        let complete_msg = create_message("automation_completed", json!({
+            "run_id": ctx.run_id,
+            "stopped": stopped,
+            "aborted": aborted,
+            "simulated": ctx.simulation.is_some(),
+            "total_loops": loop_count,
+            "completed_loops": completed_loops,
+            "succeeded": ctx.succeeded,
+            "failed": ctx.failed,
+            "released_keys": released_keys,
+            "released_buttons": released_buttons
+        }));
+        broadcast_to_clients(&ctx.clients, complete_msg);
+
+        // รีเซ็ตสถานะการทำงาน
+        {
+            let mut controller = ctx.controller.lock().await;
+            controller.finish_run(&ctx.run_id);
+
+            // แจ้งสถานะล่าสุด
+            let message = if stopped {
+                "Automation stopped".to_string()
+            } else if aborted {
+                format!("Automation aborted after a failed step ({} succeeded, {} failed)", ctx.succeeded, ctx.failed)
+            } else if ctx.failed > 0 {
+                format!("Automation completed with {} failed steps", ctx.failed)
+            } else {
+                "Automation completed".to_string()
+            };
+            let status_msg = create_message("status_update", json!({
+                "status": "idle",
+                "message": message,
+                "run_id": ctx.run_id
+            }));
+            broadcast_to_clients(&controller.clients, status_msg);
+            println!("Automation run {}: {}", ctx.run_id, message);
+
+            // เริ่มรายการถัดไปในคิว (ถ้าถูกหยุดหรือยกเลิกเพราะข้อผิดพลาด คิวจะรอจนกว่าจะสั่ง start_queue)
+            if !stopped && !aborted {
+                start_next_queued(&ctx.controller, &mut controller);
+            }
+        }
+    });
+}
+
+// ทำงานหนึ่งขั้นตอน (ถ้าเป็น group จะทำขั้นตอนภายในตามจำนวนรอบของ group)
+// inherited_policy คือ on_error ของ group ที่อยู่ หรือค่าเริ่มต้นของรอบการทำงาน
+async fn run_step(
+    ctx: &mut RunContext,
+    step: &MacroStep,
+    path: &mut Vec<StepPathSegment>,
+    inherited_policy: ErrorPolicy,
+) -> Flow {
+    let top = path[0].clone();
+
+    // ถ้าถูกหยุดชั่วคราว ให้รอก่อนเริ่มขั้นตอนนี้
+    wait_while_paused(ctx, path).await;
+
+    // รอบ debug หยุดก่อนขั้นตอนที่มี breakpoint หรือขั้นตอนถัดไปหลัง debug_step
+    if let Flow::Stopped = wait_at_breakpoint(ctx, step, path).await {
+        return Flow::Stopped;
+    }
+
+    // ส่งสถานะปัจจุบัน index/loop_index เป็นของรายการหลัก ส่วน path บอกตำแหน่งภายใน group
+    let step_msg = create_message("step_executing", json!({
+        "run_id": ctx.run_id,
+        "index": top.index,
+        "total_steps": ctx.total_steps,
+        "completed_steps": ctx.completed_steps,
+        "loop_index": top.loop_index,
+        "total_loops": top.total_loops,
+        "path": path
+    }));
+    broadcast_to_clients(&ctx.clients, step_msg);
+
+    // เช็คว่ายังทำงานอยู่หรือไม่
+    if ctx.cancel.is_cancelled() {
+        println!("Automation stopped during execution");
+        return Flow::Stopped;
+    }
+
+    // ทำงานตามประเภทของขั้นตอน
+    println!("Executing step {}: {}", describe_path(path), step.action.type_name());
+
+    let policy = step.on_error.unwrap_or(inherited_policy);
+    let flow = match &step.action {
+        Step::Group(group) => run_group(ctx, group, path, policy).await,
+        _ => run_action(ctx, step, path, policy).await,
+    };
+    if !matches!(flow, Flow::Completed) {
+        return flow;
+    }
+
+    // รอตามเวลาที่กำหนดในขั้นตอน
+    let wait_time = step.timing.wait_time;
+    let mut actual_wait = wait_time;
+
+    // ปรับเวลารอถ้าเปิดใช้งานการสุ่ม (รอบที่กำหนดการสุ่มเองและปิดไว้จะไม่สุ่ม)
+    let random_range = match &ctx.random_timing {
+        _ if !step.timing.randomize => None,
+        Some(config) if !config.enabled => None,
+        Some(config) => Some((config.min_factor as f64, config.max_factor as f64)),
+        None => {
+            // ดึงค่าจาก controller
+            let controller = ctx.controller.lock().await;
+            Some((controller.random_min as f64, controller.random_max as f64))
+        }
+    };
+
+    if let Some((min_factor, max_factor)) = random_range {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let factor = min_factor + rng.gen::<f64>() * (max_factor - min_factor);
+
+        actual_wait *= factor;
+        println!("Randomized wait time: {:.2}s (base: {:.2}s, factor: {:.2})",
+                 actual_wait, wait_time, factor);
+    } else {
+        println!("Waiting for {:.2}s", wait_time);
+    }
+
+    // รอบจำลองปรับเวลารอตาม time_scale
+    actual_wait *= ctx.time_scale;
+
+    // รอตามเวลาที่คำนวณไว้ (ตัดการรอทันทีถ้ามีการสั่งหยุด)
+    tokio::select! {
+        _ = ctx.cancel.cancelled() => {
+            println!("Automation stopped during wait");
+            Flow::Stopped
+        }
+        _ = tokio::time::sleep(Duration::from_secs_f64(actual_wait)) => Flow::Completed,
+    }
+}
+
+// ทำขั้นตอนภายใน group ตามจำนวนรอบของ group (group ซ้อนกันได้หลายชั้น)
+async fn run_group(
+    ctx: &mut RunContext,
+    group: &GroupStep,
+    path: &mut Vec<StepPathSegment>,
+    policy: ErrorPolicy,
+) -> Flow {
+    println!(
+        "Running group {} with {} steps x {} loops",
+        group.name.as_deref().unwrap_or("(unnamed)"),
+        group.steps.len(),
+        group.loop_count
+    );
+
+    for inner_loop in 0..group.loop_count {
+        for (inner_index, inner) in group.steps.iter().enumerate() {
+            path.push(StepPathSegment {
+                index: inner_index,
+                loop_index: inner_loop,
+                total_loops: group.loop_count,
+            });
+            let flow = Box::pin(run_step(ctx, inner, path, policy)).await;
+            path.pop();
+
+            if !matches!(flow, Flow::Completed) {
+                return flow;
+            }
+        }
+    }
+
+    Flow::Completed
+}
+
+// ทำงานของขั้นตอนที่ไม่ใช่ group ตาม on_error ที่กำหนด แจ้ง step_failed ทุกครั้งที่ล้มเหลว
+async fn run_action(ctx: &mut RunContext, step: &MacroStep, path: &[StepPathSegment], policy: ErrorPolicy) -> Flow {
+    let (retries, backoff) = match policy {
+        ErrorPolicy::Retry { attempts, backoff } => (attempts, backoff),
+        ErrorPolicy::Abort | ErrorPolicy::Continue => (0, 0.0),
+    };
+    let mut attempt = 1;
+
+    let error = loop {
+        // ไม่รอ input ที่ค้างอยู่ถ้ามีการสั่งหยุด
+        let result = tokio::select! {
+            _ = ctx.cancel.cancelled() => {
+                println!("Automation stopped while executing step {}", describe_path(path));
+                return Flow::Stopped;
+            }
+            result = perform_action(&ctx.input, &ctx.clipboard, &ctx.displays, &step.action, ctx.time_scale, ctx.jitter, &ctx.held_inputs) => result,
+        };
+        report_simulated_inputs(ctx, step, path);
+
+        let error = match result {
+            Ok(()) => {
+                ctx.succeeded += 1;
+                return Flow::Completed;
+            },
+            Err(error) => error,
+        };
+
+        let will_retry = attempt <= retries;
+        let top = &path[0];
+        let failed_msg = create_message("step_failed", json!({
+            "run_id": ctx.run_id,
+            "index": top.index,
+            "loop_index": top.loop_index,
+            "path": path,
+            "step_id": step.id,
+            "error": error,
+            "attempt": attempt,
+            "on_error": policy,
+            "will_retry": will_retry
+        }));
+        broadcast_to_clients(&ctx.clients, failed_msg);
+        println!("Step {} failed (attempt {}): {}", describe_path(path), attempt, error);
+
+        if !will_retry {
+            break error;
+        }
+
+        // รอก่อนลองใหม่ เวลารอเพิ่มเป็นสองเท่าทุกครั้ง (รอบจำลองปรับตาม time_scale)
+        let delay = backoff * 2f64.powi(attempt as i32 - 1) * ctx.time_scale;
+        tokio::select! {
+            _ = ctx.cancel.cancelled() => return Flow::Stopped,
+            _ = tokio::time::sleep(Duration::from_secs_f64(delay)) => {}
+        }
+        attempt += 1;
+    };
+
+    ctx.failed += 1;
+    match policy {
+        ErrorPolicy::Continue => {
+            println!("Continuing after failed step {}", describe_path(path));
+            Flow::Completed
+        },
+        ErrorPolicy::Abort | ErrorPolicy::Retry { .. } => {
+            println!("Aborting run after failed step {}: {}", describe_path(path), error);
+            Flow::Aborted
+        },
+    }
+}
+
+// แจ้ง input ที่ขั้นตอนนี้จะส่งออกไป (เฉพาะรอบจำลอง)
+fn report_simulated_inputs(ctx: &RunContext, step: &MacroStep, path: &[StepPathSegment]) {
+    let recorder = match &ctx.simulation {
+        Some(recorder) => recorder,
+        None => return,
+    };
+
+    let inputs: Vec<String> = recorder.take_actions().iter().map(|action| action.to_string()).collect();
+    let description = step.action.describe();
+    let top = &path[0];
+    let simulated_msg = create_message("simulated_step", json!({
+        "run_id": ctx.run_id,
+        "index": top.index,
+        "loop_index": top.loop_index,
+        "path": path,
+        "step_id": step.id,
+        "description": description,
+        "inputs": inputs
+    }));
+    broadcast_to_clients(&ctx.clients, simulated_msg);
+    println!("Simulated step {}: {} [{}]", describe_path(path), description, inputs.join(", "));
+}
+
+// เลื่อนเมาส์ไปยังตำแหน่ง (สุ่มรอบตำแหน่งถ้ามี jitter) แบบทันทีหรือตาม movement
+async fn move_to_target(
+    input: &InputActor,
+    target: Point,
+    movement: Option<&MovementProfile>,
+    jitter: CoordinateJitter,
+    time_scale: f64,
+) -> Result<(), String> {
+    let (x, y) = crate::movement::jitter_point((target.x, target.y), &jitter);
+    match movement {
+        Some(movement) => crate::mouse_keyboard::mouse_move_along(input, x, y, movement, time_scale).await,
+        None => crate::mouse_keyboard::mouse_move(input, x, y).await,
+    }
+    .map_err(|e| e.to_string())
+}
+
+// เลื่อนเมาส์ไปยังตำแหน่งของขั้นตอนคลิกก่อนคลิก (เฉพาะขั้นตอนที่มีตำแหน่งและระบุ movement หรือมีการสุ่มตำแหน่ง)
+async fn move_before_click(
+    input: &InputActor,
+    displays: &DisplayLayout,
+    click: &MouseClickStep,
+    jitter: CoordinateJitter,
+    time_scale: f64,
+) -> Result<(), String> {
+    let jitter = click.jitter.unwrap_or(jitter);
+    match click.target {
+        Some(target) if click.movement.is_some() || jitter.is_enabled() => {
+            let target = displays.resolve(target, click.monitor.as_ref())?;
+            move_to_target(input, target, click.movement.as_ref(), jitter, time_scale).await
+        },
+        _ => Ok(()),
+    }
+}
+
+// สั่งงานเมาส์/คีย์บอร์ดตามประเภทของขั้นตอน
+async fn perform_action(
+    input: &InputActor,
+    clipboard: &Arc<dyn ClipboardProvider>,
+    displays: &DisplayLayout,
+    action: &Step,
+    time_scale: f64,
+    jitter: CoordinateJitter,
+    held_inputs: &HeldInputs,
+) -> Result<(), String> {
+    match action {
+        Step::MouseMove(target) => {
+            // ตำแหน่งที่บันทึกเทียบกับจอจะถูกแปลงตามการจัดวางจอปัจจุบัน
+            let point = displays.resolve(target.position, target.monitor.as_ref())?;
+            println!("Moving mouse to position: ({}, {})", point.x, point.y);
+
+            // เรียกใช้ฟังก์ชันควบคุมเมาส์
+            let jitter = target.jitter.unwrap_or(jitter);
+            move_to_target(input, point, target.movement.as_ref(), jitter, time_scale).await?;
+            println!("Mouse move completed");
+        },
+        Step::MouseClick(click) => {
+            println!("Clicking {} mouse button", click.button.as_str());
+            move_before_click(input, displays, click, jitter, time_scale).await?;
+
+            // เรียกใช้ฟังก์ชันคลิกเมาส์
+            crate::mouse_keyboard::mouse_click(input, click.button).await.map_err(|e| e.to_string())?;
+            println!("Mouse click completed");
+        },
+        Step::MouseDoubleClick(click) => {
+            println!("Double clicking {} mouse button", click.button.as_str());
+            move_before_click(input, displays, click, jitter, time_scale).await?;
+
+            // เรียกใช้ฟังก์ชันดับเบิลคลิกเมาส์
+            crate::mouse_keyboard::mouse_double_click(input, click.button).await.map_err(|e| e.to_string())?;
+            println!("Mouse double click completed");
+        },
+        Step::KeyPress(press) => {
+            println!("Pressing key: {}", press.key);
+
+            // เรียกใช้ฟังก์ชันกดคีย์บอร์ด
+            crate::mouse_keyboard::keyboard_press_key(input, &press.key).await.map_err(|e| e.to_string())?;
+            println!("Key press completed");
+        },
+        Step::MouseDown(click) => {
+            println!("Pressing {} mouse button", click.button.as_str());
+            move_before_click(input, displays, click, jitter, time_scale).await?;
+            crate::mouse_keyboard::mouse_down(input, click.button, held_inputs).await.map_err(|e| e.to_string())?;
+        },
+        Step::MouseUp(click) => {
+            println!("Releasing {} mouse button", click.button.as_str());
+            move_before_click(input, displays, click, jitter, time_scale).await?;
+            crate::mouse_keyboard::mouse_up(input, click.button, held_inputs).await.map_err(|e| e.to_string())?;
+        },
+        Step::Drag(drag) => {
+            println!("Dragging with {} mouse button to ({}, {})", drag.button.as_str(), drag.to.x, drag.to.y);
+            let from = drag.from.map(|from| (from.x, from.y));
+            crate::mouse_keyboard::mouse_drag(input, from, (drag.to.x, drag.to.y), drag.button, drag.duration, time_scale, held_inputs)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!("Drag completed");
+        },
+        Step::KeyDown(press) => {
+            println!("Key down: {}", press.key);
+            crate::mouse_keyboard::key_down(input, &press.key, held_inputs).await.map_err(|e| e.to_string())?;
+        },
+        Step::KeyUp(press) => {
+            println!("Key up: {}", press.key);
+            crate::mouse_keyboard::key_up(input, &press.key, held_inputs).await.map_err(|e| e.to_string())?;
+        },
+        Step::KeyHold(hold) => {
+            println!("Holding key {} for {} seconds", hold.key, hold.duration);
+            crate::mouse_keyboard::key_hold(input, &hold.key, hold.duration, time_scale, held_inputs)
+                .await
+                .map_err(|e| e.to_string())?;
+        },
+        Step::TypeText(typing) => {
+            println!("Typing text: {:?}", typing.text);
+
+            crate::mouse_keyboard::type_text(input, clipboard, &typing.text, typing.char_delay, typing.char_jitter, time_scale)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!("Type text completed");
+        },
+        Step::Scroll(scroll) => {
+            println!("Scrolling {},{} x {}", scroll.dx, scroll.dy, scroll.repeat);
+            crate::mouse_keyboard::mouse_scroll(input, scroll.dx, scroll.dy, scroll.repeat, scroll.interval, time_scale)
+                .await
+                .map_err(|e| e.to_string())?;
+        },
+        Step::Wait => {
+            // เป็นขั้นตอนการรอ ไม่ต้องทำอะไรเพิ่มเติม เพราะทุก step มีการรอตามเวลาที่กำหนดอยู่แล้ว
+            println!("Wait step - will continue with normal wait time");
+        },
+        Step::Group(_) => unreachable!("groups are executed by run_group"),
+    }
+    Ok(())
+}
+
+// รอระหว่างขั้นตอนขณะที่การทำงานถูกหยุดชั่วคราว
+async fn wait_while_paused(ctx: &mut RunContext, path: &[StepPathSegment]) {
+    if !*ctx.pause_rx.borrow_and_update() {
+        return;
+    }
+
+    let top = &path[0];
+    let paused_msg = create_message("status_update", json!({
+        "status": "paused",
+        "run_id": ctx.run_id,
+        "message": format!("Automation paused before step {} (loop {})", describe_path(path), top.loop_index + 1),
+        "index": top.index,
+        "loop_index": top.loop_index,
+        "path": path
+    }));
+    broadcast_to_clients(&ctx.clients, paused_msg);
+    println!("Automation paused before step {} (loop {})", describe_path(path), top.loop_index + 1);
+
+    // รอจนกว่าจะสั่งทำงานต่อหรือสั่งหยุด (ถ้า sender ถูกทำลายไปแล้วให้ทำงานต่อ)
+    tokio::select! {
+        _ = ctx.cancel.cancelled() => {}
+        _ = ctx.pause_rx.wait_for(|paused| !*paused) => {
+            println!("Automation continuing from step {}", describe_path(path));
+        }
+    }
+}
+
+// หยุดรอคำสั่ง debug ก่อนขั้นตอนนี้ (ไม่ทำอะไรถ้าไม่ใช่รอบ debug หรือไม่ต้องหยุดที่ขั้นตอนนี้)
+async fn wait_at_breakpoint(ctx: &mut RunContext, step: &MacroStep, path: &[StepPathSegment]) -> Flow {
+    let reason = match &ctx.debug {
+        Some(_) if step.breakpoint => "breakpoint",
+        Some(debug) if debug.stepping => "step",
+        _ => return Flow::Completed,
+    };
+
+    let top = &path[0];
+    let debug_msg = create_message("debug_paused", json!({
+        "run_id": ctx.run_id,
+        "reason": reason,
+        "index": top.index,
+        "loop_index": top.loop_index,
+        "total_loops": top.total_loops,
+        "path": path,
+        "step": step
+    }));
+    broadcast_to_clients(&ctx.clients, debug_msg);
+    println!("Debugger stopped before step {} ({}, loop {})", describe_path(path), reason, top.loop_index + 1);
+
+    let debug = match ctx.debug.as_mut() {
+        Some(debug) => debug,
+        None => return Flow::Completed,
+    };
+
+    // ทิ้งคำสั่งที่ส่งมาก่อนหน้านี้ขณะที่ยังไม่ได้หยุด
+    while debug.commands.try_recv().is_ok() {}
+
+    // รอคำสั่งถัดไป ถ้า channel ถูกปิดให้ทำงานต่อแบบปกติ
+    let command = tokio::select! {
+        _ = ctx.cancel.cancelled() => {
+            println!("Debug run aborted at step {}", describe_path(path));
+            return Flow::Stopped;
+        }
+        command = debug.commands.recv() => command.unwrap_or(DebugCommand::Continue),
+    };
+
+    debug.stepping = command == DebugCommand::Step;
+    println!("Debugger resumed with {:?}", command);
+    Flow::Completed
+}
+
+// แปลง path เป็นข้อความสำหรับ log เช่น "3" หรือ "3 > 2"
+fn describe_path(path: &[StepPathSegment]) -> String {
+    path.iter()
+        .map(|segment| (segment.index + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_actor::TimingProfile;
+    use crate::input_backend::InputAction;
+    use crate::models::MouseButton;
+    use serde_json::Value;
+
+    // controller ที่ส่ง input ไปยัง RecordingInputBackend และเก็บข้อความที่ส่งถึง client ไว้ตรวจสอบ
+    struct Harness {
+        controller: Arc<Mutex<AutomationController>>,
+        recorder: Arc<RecordingInputBackend>,
+        events: mpsc::UnboundedReceiver<Message>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let recorder = Arc::new(RecordingInputBackend::new());
+            let mut controller = AutomationController::with_input(recorder.clone());
+            controller.input.set_profile(TimingProfile::Fast);
+            let (tx, events) = mpsc::unbounded_channel();
+            controller.clients.insert("test".to_string(), tx);
+            Self { controller: Arc::new(Mutex::new(controller)), recorder, events }
+        }
+
+        async fn start(&self, request: RunRequest) {
+            let mut controller = self.controller.lock().await;
+            let run = controller.begin_run().expect("another run is active");
+            launch_run(&self.controller, &mut controller, request, run, "test run".to_string(), None);
+        }
+
+        // รอข้อความชนิดที่กำหนดแล้วคืนค่า data (ข้ามข้อความชนิดอื่น)
+        async fn next_event(&mut self, event_type: &str) -> Value {
+            let events = &mut self.events;
+            let wait = async {
+                loop {
+                    let message = events.recv().await.expect("client channel closed");
+                    let value: Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+                    if value["type"] == event_type {
+                        return value["data"].clone();
+                    }
+                }
+            };
+            tokio::time::timeout(Duration::from_secs(5), wait)
+                .await
+                .unwrap_or_else(|_| panic!("no {} event", event_type))
+        }
+
+        // รอ status_update ที่มีสถานะตามที่กำหนด
+        async fn next_status(&mut self, status: &str) -> Value {
+            loop {
+                let update = self.next_event("status_update").await;
+                if update["status"] == status {
+                    return update;
+                }
+            }
+        }
+
+        // รอจนกว่า backend จะได้รับ input ครบตามจำนวนที่กำหนด
+        async fn wait_for_inputs(&self, count: usize) {
+            let wait = async {
+                while self.recorder.actions().len() < count {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            };
+            tokio::time::timeout(Duration::from_secs(5), wait).await.expect("inputs were not sent");
+        }
+
+        async fn run(&mut self, request: RunRequest) -> Value {
+            self.start(request).await;
+            self.next_event("automation_completed").await
+        }
+    }
+
+    // ขั้นตอนที่ไม่ระบุ wait_time จะรอ 1 วินาที จึงตั้งเป็น 0 ทุกขั้นตอน (รวมใน group) ให้ทดสอบได้เร็ว
+    fn steps(values: Value) -> Vec<MacroStep> {
+        fn without_waits(steps: &mut [MacroStep]) {
+            for step in steps {
+                step.timing.wait_time = 0.0;
+                if let Step::Group(group) = &mut step.action {
+                    without_waits(&mut group.steps);
+                }
+            }
+        }
+        let mut steps = crate::validation::parse_steps(values.as_array().unwrap()).unwrap();
+        without_waits(&mut steps);
+        steps
+    }
+
+    fn request(steps: Vec<MacroStep>) -> RunRequest {
+        RunRequest {
+            steps,
+            loop_count: 1,
+            selected_ids: None,
+            random_timing: None,
+            debug: false,
+            range: StepRange::default(),
+            on_error: ErrorPolicy::default(),
+            simulate: None,
+        }
+    }
+
+    fn key_down(key: &str) -> InputAction {
+        InputAction::KeyDown { key: key.to_string() }
+    }
+
+    fn key_up(key: &str) -> InputAction {
+        InputAction::KeyUp { key: key.to_string() }
+    }
+
+    fn key_click(key: &str) -> InputAction {
+        InputAction::KeyClick { key: key.to_string() }
+    }
+
+    #[tokio::test]
+    async fn plays_moves_and_clicks() {
+        let mut harness = Harness::new();
+        let completed = harness.run(request(steps(json!([
+            { "id": "1", "type": "mouse_move", "data": { "x": 100, "y": 200 } },
+            { "id": "2", "type": "mouse_click", "data": { "button": "right" } },
+        ])))).await;
+
+        assert_eq!(completed["succeeded"], 2);
+        assert_eq!(harness.recorder.take_actions(), vec![
+            InputAction::MoveTo { x: 100, y: 200 },
+            InputAction::Click { button: MouseButton::Right },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn presses_key_chords_with_modifiers_around_the_key() {
+        let mut harness = Harness::new();
+        harness.run(request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "ctrl+shift+t" } },
+        ])))).await;
+
+        assert_eq!(harness.recorder.take_actions(), vec![
+            key_down("ctrl"),
+            key_down("shift"),
+            key_click("t"),
+            key_up("shift"),
+            key_up("ctrl"),
+        ]);
+    }
+
+
+    #[tokio::test]
+    async fn repeats_group_steps_for_each_group_loop() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "g", "type": "group", "data": { "isGroup": true, "groupLoopCount": 2, "groupSteps": [
+                { "id": "g-1", "type": "key_press", "data": { "key": "a" } },
+                { "id": "g-2", "type": "mouse_click", "data": {} },
+            ] } },
+            { "id": "2", "type": "key_press", "data": { "key": "enter" } },
+        ])));
+        run.loop_count = 2;
+        let completed = harness.run(run).await;
+
+        let group_pass = [key_click("a"), InputAction::Click { button: MouseButton::Left }];
+        let macro_pass: Vec<InputAction> = group_pass.iter().chain(&group_pass).cloned().chain([key_click("enter")]).collect();
+        assert_eq!(harness.recorder.take_actions(), [macro_pass.clone(), macro_pass].concat());
+        assert_eq!(completed["completed_loops"], 2);
+        assert_eq!(completed["succeeded"], 10);
+    }
+
+    #[tokio::test]
+    async fn retries_a_failed_step_before_moving_on() {
+        let mut harness = Harness::new();
+        harness.recorder.fail_next("device busy");
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "mouse_click", "data": {} },
+            { "id": "2", "type": "key_press", "data": { "key": "a" } },
+        ])));
+        run.on_error = ErrorPolicy::Retry { attempts: 2, backoff: 0.0 };
+        harness.start(run).await;
+
+        let failed = harness.next_event("step_failed").await;
+        assert_eq!((failed["step_id"].as_str(), failed["attempt"].as_u64()), (Some("1"), Some(1)));
+        assert_eq!(failed["error"], "device busy");
+        assert_eq!(failed["will_retry"], true);
+
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!((completed["aborted"].as_bool(), completed["failed"].as_u64()), (Some(false), Some(0)));
+        assert_eq!(harness.recorder.take_actions(), vec![InputAction::Click { button: MouseButton::Left }, key_click("a")]);
+    }
+
+    #[tokio::test]
+    async fn abort_policy_stops_the_run_after_a_failure() {
+        let mut harness = Harness::new();
+        harness.recorder.fail_next("device busy");
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "mouse_click", "data": {} },
+            { "id": "2", "type": "key_press", "data": { "key": "a" } },
+        ])));
+        run.on_error = ErrorPolicy::Abort;
+        harness.start(run).await;
+
+        let failed = harness.next_event("step_failed").await;
+        assert_eq!(failed["will_retry"], false);
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!((completed["aborted"].as_bool(), completed["failed"].as_u64()), (Some(true), Some(1)));
+        assert_eq!(harness.recorder.take_actions(), vec![]);
+        assert!(!harness.controller.lock().await.is_running());
+    }
+
+    #[tokio::test]
+    async fn pauses_between_steps_until_resumed() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b" } },
+        ])));
+        run.steps[0].timing.wait_time = 0.2;
+        harness.start(run).await;
+        harness.next_event("step_executing").await;
+        harness.controller.lock().await.set_paused(true);
+
+        let paused = harness.next_status("paused").await;
+        assert_eq!(paused["index"], 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+
+        harness.controller.lock().await.set_paused(false);
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!(completed["stopped"], false);
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("b")]);
+    }
+
+    #[tokio::test]
+    async fn stopping_a_paused_run_ends_it() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b" } },
+        ])));
+        run.steps[0].timing.wait_time = 0.2;
+        harness.start(run).await;
+        harness.next_event("step_executing").await;
+        harness.controller.lock().await.set_paused(true);
+        harness.next_status("paused").await;
+
+        harness.controller.lock().await.stop_run();
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!(completed["stopped"], true);
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+    }
+
+    #[tokio::test]
+    async fn stop_interrupts_the_wait_after_a_step() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b" } },
+        ])));
+        run.steps[0].timing.wait_time = 30.0;
+        harness.start(run).await;
+        harness.wait_for_inputs(1).await;
+
+        let started = std::time::Instant::now();
+        harness.controller.lock().await.stop_run();
+        let completed = harness.next_event("automation_completed").await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!((completed["stopped"].as_bool(), completed["completed_loops"].as_u64()), (Some(true), Some(0)));
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+    }
+
+    #[tokio::test]
+    async fn stop_interrupts_input_in_progress() {
+        let mut harness = Harness::new();
+        harness.start(request(steps(json!([
+            { "id": "1", "type": "scroll", "data": { "dy": 1, "repeat": 3, "interval": 30 } },
+        ])))).await;
+        harness.wait_for_inputs(1).await;
+
+        let started = std::time::Instant::now();
+        harness.controller.lock().await.stop_run();
+        let completed = harness.next_event("automation_completed").await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(completed["stopped"], true);
+        assert_eq!(harness.recorder.take_actions(), vec![InputAction::Scroll { dx: 0, dy: 1 }]);
+    }
+
+    #[tokio::test]
+    async fn reports_the_path_of_steps_in_nested_groups() {
+        let mut harness = Harness::new();
+        harness.start(request(steps(json!([
+            { "id": "outer", "type": "group", "data": { "isGroup": true, "groupLoopCount": 2, "groupSteps": [
+                { "id": "inner", "type": "group", "data": { "isGroup": true, "groupLoopCount": 2, "groupSteps": [
+                    { "id": "a", "type": "key_press", "data": { "key": "a" } },
+                ] } },
+            ] } },
+        ])))).await;
+
+        // path ของ key_press แต่ละครั้ง เป็น (index, loop_index) ของทุกชั้น
+        let mut paths = Vec::new();
+        loop {
+            let message = harness.events.recv().await.unwrap();
+            let value: Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+            match value["type"].as_str() {
+                Some("step_executing") if value["data"]["path"].as_array().unwrap().len() == 3 => {
+                    let path: Vec<(u64, u64)> = value["data"]["path"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|segment| (segment["index"].as_u64().unwrap(), segment["loop_index"].as_u64().unwrap()))
+                        .collect();
+                    paths.push(path);
+                },
+                Some("automation_completed") => break,
+                _ => {},
+            }
+        }
+
+        assert_eq!(paths, vec![
+            vec![(0, 0), (0, 0), (0, 0)],
+            vec![(0, 0), (0, 0), (0, 1)],
+            vec![(0, 0), (0, 1), (0, 0)],
+            vec![(0, 0), (0, 1), (0, 1)],
+        ]);
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a"); 4]);
+    }
+
+    #[tokio::test]
+    async fn starts_queued_runs_in_order_after_each_run_ends() {
+        let mut harness = Harness::new();
+        {
+            let mut controller = harness.controller.lock().await;
+            for key in ["a", "b"] {
+                let steps = steps(json!([{ "id": key, "type": "key_press", "data": { "key": key } }]));
+                controller.enqueue_run(Some(key.to_string()), request(steps));
+            }
+            assert!(start_next_queued(&harness.controller, &mut controller));
+            // เริ่มได้ครั้งละหนึ่งรอบ รายการที่เหลือรอจนกว่ารอบแรกจะจบ
+            assert!(!start_next_queued(&harness.controller, &mut controller));
+            assert_eq!(controller.run_queue.len(), 1);
+        }
+
+        harness.next_event("automation_completed").await;
+        harness.next_event("automation_completed").await;
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a"), key_click("b")]);
+        assert!(harness.controller.lock().await.run_queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn debug_runs_stop_at_breakpoints_and_follow_commands() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b", "breakpoint": true } },
+            { "id": "3", "type": "key_press", "data": { "key": "c" } },
+            { "id": "4", "type": "key_press", "data": { "key": "d" } },
+        ])));
+        run.debug = true;
+        harness.start(run).await;
+
+        let paused = harness.next_event("debug_paused").await;
+        assert_eq!((paused["reason"].as_str(), paused["index"].as_u64()), (Some("breakpoint"), Some(1)));
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+
+        assert!(harness.controller.lock().await.send_debug_command(DebugCommand::Step));
+        let paused = harness.next_event("debug_paused").await;
+        assert_eq!((paused["reason"].as_str(), paused["index"].as_u64()), (Some("step"), Some(2)));
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("b")]);
+
+        assert!(harness.controller.lock().await.send_debug_command(DebugCommand::Continue));
+        harness.next_event("automation_completed").await;
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("c"), key_click("d")]);
+        // รอบจบแล้วจึงไม่มีรอบ debug ให้รับคำสั่ง
+        harness.next_status("idle").await;
+        assert!(!harness.controller.lock().await.send_debug_command(DebugCommand::Continue));
+    }
+
+    #[tokio::test]
+    async fn normal_runs_ignore_breakpoints() {
+        let mut harness = Harness::new();
+        let completed = harness.run(request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a", "breakpoint": true } },
+        ])))).await;
+
+        assert_eq!(completed["succeeded"], 1);
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+    }
+
+    // ขั้นตอนกดคีย์ที่ไม่รู้จัก (ข้ามการตรวจสอบของ parse_steps) จะล้มเหลวทุกครั้งที่ทำ
+    fn failing_step(id: &str, on_error: Option<&str>) -> MacroStep {
+        let mut step = MacroStep::deserialize_value(&json!({
+            "id": id, "type": "key_press", "data": { "key": "nosuchkey", "on_error": on_error },
+        })).unwrap();
+        step.timing.wait_time = 0.0;
+        step
+    }
+
+    #[tokio::test]
+    async fn continue_policy_reports_the_failure_and_moves_on() {
+        let mut harness = Harness::new();
+        harness.recorder.fail_next("device busy");
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "mouse_click", "data": {} },
+            { "id": "2", "type": "key_press", "data": { "key": "a" } },
+        ])));
+        run.on_error = ErrorPolicy::Continue;
+        harness.start(run).await;
+
+        let failed = harness.next_event("step_failed").await;
+        assert_eq!((failed["step_id"].as_str(), failed["will_retry"].as_bool()), (Some("1"), Some(false)));
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!(completed["aborted"], false);
+        assert_eq!((completed["succeeded"].as_u64(), completed["failed"].as_u64()), (Some(1), Some(1)));
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("a")]);
+    }
+
+    #[tokio::test]
+    async fn retries_up_to_the_step_policy_then_aborts() {
+        let mut harness = Harness::new();
+        let mut run_steps = vec![failing_step("1", Some("retry(2, 0.01)"))];
+        run_steps.extend(steps(json!([{ "id": "2", "type": "key_press", "data": { "key": "a" } }])));
+        harness.start(request(run_steps)).await;
+
+        for (attempt, will_retry) in [(1, true), (2, true), (3, false)] {
+            let failed = harness.next_event("step_failed").await;
+            assert_eq!((failed["attempt"].as_u64(), failed["will_retry"].as_bool()), (Some(attempt), Some(will_retry)));
+        }
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!((completed["aborted"].as_bool(), completed["failed"].as_u64()), (Some(true), Some(1)));
+        assert_eq!(harness.recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn steps_in_a_group_inherit_the_group_policy() {
+        let mut harness = Harness::new();
+        let mut run_steps = steps(json!([
+            { "id": "g", "type": "group", "data": { "isGroup": true, "on_error": "abort", "groupSteps": [] } },
+            { "id": "2", "type": "key_press", "data": { "key": "a" } },
+        ]));
+        if let Step::Group(group) = &mut run_steps[0].action {
+            group.steps.push(failing_step("g-1", None));
+        }
+        let mut run = request(run_steps);
+        run.on_error = ErrorPolicy::Continue;
+        let completed = harness.run(run).await;
+
+        assert_eq!(completed["aborted"], true);
+        assert_eq!(harness.recorder.take_actions(), vec![]);
+    }
+
+
+    #[tokio::test]
+    async fn first_loop_starts_at_the_start_index_and_later_loops_from_the_top() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b" } },
+            { "id": "3", "type": "key_press", "data": { "key": "c" } },
+            { "id": "4", "type": "key_press", "data": { "key": "d" } },
+        ])));
+        run.loop_count = 3;
+        run.range = StepRange { start_index: 2, end_index: Some(2), start_loop: 1 };
+        let completed = harness.run(run).await;
+
+        assert_eq!(harness.recorder.take_actions(), vec![key_click("c"), key_click("a"), key_click("b"), key_click("c")]);
+        // รอบที่ข้ามไปนับว่าทำเสร็จแล้ว
+        assert_eq!((completed["completed_loops"].as_u64(), completed["total_loops"].as_u64()), (Some(3), Some(3)));
+    }
+
+    #[tokio::test]
+    async fn ranges_apply_to_the_selected_steps_by_their_original_index() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "key_press", "data": { "key": "a" } },
+            { "id": "2", "type": "key_press", "data": { "key": "b" } },
+            { "id": "3", "type": "key_press", "data": { "key": "c" } },
+            { "id": "4", "type": "key_press", "data": { "key": "d" } },
+            { "id": "5", "type": "key_press", "data": { "key": "e" } },
+        ])));
+        run.loop_count = 2;
+        run.selected_ids = Some(vec!["1".to_string(), "3".to_string(), "4".to_string(), "5".to_string()]);
+        run.range = StepRange { start_index: 2, end_index: Some(3), start_loop: 0 };
+        harness.start(run).await;
+
+        let first = harness.next_event("step_executing").await;
+        // ขั้นตอนที่ 5 อยู่หลัง end_index จึงเหลือ 3 ขั้นตอน และ index ยังเป็นตำแหน่งในรายการเดิม
+        assert_eq!(
+            (first["index"].as_u64(), first["completed_steps"].as_u64(), first["total_steps"].as_u64()),
+            (Some(2), Some(1), Some(3)),
+        );
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!(completed["completed_loops"], 2);
+        assert_eq!(harness.recorder.take_actions(), vec![
+            key_click("c"),
+            key_click("d"),
+            key_click("a"),
+            key_click("c"),
+            key_click("d"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn simulated_runs_report_inputs_without_sending_them() {
+        let mut harness = Harness::new();
+        let mut run = request(steps(json!([
+            { "id": "1", "type": "mouse_move", "data": { "x": 10, "y": 20 } },
+            { "id": "2", "type": "key_press", "data": { "key": "a" } },
+        ])));
+        run.steps[0].timing.wait_time = 30.0;
+        run.simulate = Some(Simulation { time_scale: 0.0 });
+        harness.start(run).await;
+
+        for inputs in [json!(["move to 10,20"]), json!(["press a"])] {
+            assert_eq!(harness.next_event("simulated_step").await["inputs"], inputs);
+        }
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!((completed["simulated"].as_bool(), completed["succeeded"].as_u64()), (Some(true), Some(2)));
+        assert_eq!(harness.recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn simulated_runs_skip_retry_backoff_and_input_pacing() {
+        let mut harness = Harness::new();
+        // profile ของ input จริงหน่วง 50ms ระหว่างคีย์ ซึ่งรอบจำลองไม่ควรใช้
+        harness.controller.lock().await.input.set_profile(TimingProfile::LegacySafe);
+        let chords: Vec<Value> = (0..10)
+            .map(|i| json!({ "id": format!("chord-{}", i), "type": "key_press", "data": { "key": "ctrl+shift+alt+a" } }))
+            .collect();
+        let mut run_steps = steps(Value::Array(chords));
+        // ลองใหม่สองครั้งโดยรอ 30 และ 60 วินาทีถ้าไม่ปรับตาม time_scale
+        run_steps.push(failing_step("last", Some("retry(2, 30)")));
+        let mut run = request(run_steps);
+        run.simulate = Some(Simulation { time_scale: 0.0 });
+
+        let started = std::time::Instant::now();
+        let completed = harness.run(run).await;
+        assert!(started.elapsed() < Duration::from_secs(1), "took {:?}", started.elapsed());
+        assert_eq!((completed["succeeded"].as_u64(), completed["failed"].as_u64()), (Some(10), Some(1)));
+        assert_eq!(harness.recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn stopping_during_a_key_hold_releases_the_key() {
+        let mut harness = Harness::new();
+        harness.start(request(steps(json!([
+            { "id": "1", "type": "key_hold", "data": { "key": "shift", "duration": 30 } },
+        ])))).await;
+        harness.wait_for_inputs(1).await;
+
+        harness.controller.lock().await.stop_run();
+        let completed = harness.next_event("automation_completed").await;
+        assert_eq!(completed["released_keys"], json!(["shift"]));
+        assert_eq!(harness.recorder.take_actions(), vec![key_down("shift"), key_up("shift")]);
+    }
+
+    #[tokio::test]
+    async fn jittered_clicks_move_near_the_target_first() {
+        let mut harness = Harness::new();
+        harness.run(request(steps(json!([
+            { "id": "1", "type": "mouse_click", "data": { "x": 300, "y": 200, "jitter_radius": 5.0 } },
+        ])))).await;
+
+        let actions = harness.recorder.take_actions();
+        match actions[..] {
+            [InputAction::MoveTo { x, y }, InputAction::Click { button: MouseButton::Left }] => {
+                assert!((x - 300).abs() <= 5 && (y - 200).abs() <= 5, "moved to {},{}", x, y);
+            },
+            _ => panic!("unexpected inputs {:?}", actions),
+        }
+    }
+}
@@ -19,11 +19,19 @@ pub async fn get_status(
     automation_controller: Arc<Mutex<AutomationController>>,
 ) -> Result<impl Reply, warp::Rejection> {
     let controller = automation_controller.lock().await;
-    let is_running = controller.is_running;
+    let is_running = controller.is_running();
+    let run_id = controller.current_run.as_ref().map(|run| run.run_id.clone());
+    let is_paused = controller.is_paused();
     let is_recording = controller.is_recording;
+    let queued_runs = controller.run_queue.len();
     
     let response = json!({
         "is_running": is_running,
+        "run_id": run_id,
+        "queued_runs": queued_runs,
+        "input_backend": controller.input.name(),
+        "timing_profile": controller.input.profile(),
+        "is_paused": is_paused,
         "is_recording": is_recording
     });
     
@@ -72,12 +80,15 @@ pub async fn get_random_timing(
         enabled: controller.random_enabled,
         min_factor: controller.random_min,
         max_factor: controller.random_max,
+        jitter: controller.jitter,
     };
     
     let response = json!({
         "enabled": config.enabled,
         "min_factor": config.min_factor,
-        "max_factor": config.max_factor
+        "max_factor": config.max_factor,
+        "jitter_radius": config.jitter.radius,
+        "jitter_distribution": config.jitter.distribution
     });
     
     Ok(json(&response))
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::input_backend::InputBackend;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// คำสั่งที่รอในคิวได้สูงสุด ถ้าเต็มผู้ส่งจะรอจนกว่า worker จะทำคำสั่งก่อนหน้าเสร็จ
+const QUEUE_CAPACITY: usize = 64;
+
+// ชุดเวลาหน่วงระหว่างเหตุการณ์ input ภายในคำสั่งเดียวกัน (เช่น ระหว่าง modifier กับคีย์หลัก)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimingProfile {
+    // หน่วงน้อยที่สุดเท่าที่โปรแกรมส่วนใหญ่ยังรับได้
+    Fast,
+    // หน่วงใกล้เคียงคนจริงและสุ่มเล็กน้อย
+    Human,
+    // ค่าหน่วงแบบเดิม (50ms ระหว่างคีย์, 300ms หลัง shortcut) สำหรับโปรแกรมที่รับ input เร็วไม่ได้
+    #[default]
+    LegacySafe,
+}
+
+// ช่วงหน่วงแต่ละชนิดที่ profile กำหนด
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gap {
+    // ระหว่างคีย์ใน chord และหลังกด chord
+    Key,
+    // ระหว่างคลิกสองครั้งของดับเบิลคลิก
+    DoubleClick,
+    // หลังกดปุ่มเมาส์ก่อนเริ่มลาก
+    DragStart,
+    // หลังส่ง shortcut (copy, paste, select all) ให้โปรแกรมปลายทางทำงานเสร็จ
+    Settle,
+}
+
+impl TimingProfile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimingProfile::Fast => "fast",
+            TimingProfile::Human => "human",
+            TimingProfile::LegacySafe => "legacy-safe",
+        }
+    }
+
+    // เวลาหน่วง (วินาที) และช่วงสุ่ม ± ของแต่ละ gap
+    fn delay(self, gap: Gap) -> (f64, f64) {
+        match (self, gap) {
+            (TimingProfile::Fast, Gap::Key) => (0.005, 0.0),
+            (TimingProfile::Fast, Gap::DoubleClick) => (0.03, 0.0),
+            (TimingProfile::Fast, Gap::DragStart) => (0.02, 0.0),
+            (TimingProfile::Fast, Gap::Settle) => (0.1, 0.0),
+            (TimingProfile::Human, Gap::Key) => (0.06, 0.03),
+            (TimingProfile::Human, Gap::DoubleClick) => (0.11, 0.03),
+            (TimingProfile::Human, Gap::DragStart) => (0.09, 0.04),
+            (TimingProfile::Human, Gap::Settle) => (0.3, 0.1),
+            (TimingProfile::LegacySafe, Gap::Settle) => (0.3, 0.0),
+            (TimingProfile::LegacySafe, _) => (0.05, 0.0),
+        }
+    }
+
+    // เลือก profile ตาม environment variable AUTOMATION_TIMING_PROFILE (ค่าเริ่มต้น legacy-safe)
+    pub fn from_env() -> Self {
+        match std::env::var("AUTOMATION_TIMING_PROFILE") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                println!("{}, using legacy-safe", e);
+                TimingProfile::LegacySafe
+            }),
+            Err(_) => TimingProfile::LegacySafe,
+        }
+    }
+}
+
+impl FromStr for TimingProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "fast" => Ok(TimingProfile::Fast),
+            "human" => Ok(TimingProfile::Human),
+            "legacy-safe" | "legacy" => Ok(TimingProfile::LegacySafe),
+            _ => Err(format!("Unknown timing profile `{}` (expected fast, human or legacy-safe)", s)),
+        }
+    }
+}
+
+impl fmt::Display for TimingProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ตัวจับเวลาของคำสั่งที่กำลังทำ ใช้หน่วงตาม profile หรือรอถึงเวลาที่กำหนดไว้
+// ถ้าผู้สั่งยกเลิก (future ถูก drop เช่น หยุดรอบการทำงาน) การรอจะคืน error เพื่อหยุดคำสั่งทันที
+pub struct Pacer {
+    profile: TimingProfile,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Pacer {
+    // หน่วงตาม gap ของ profile
+    pub fn pause(&mut self, gap: Gap) -> Result<(), BoxError> {
+        let (base, jitter) = self.profile.delay(gap);
+        let jitter = if jitter > 0.0 { rand::thread_rng().gen_range(-jitter..=jitter) } else { 0.0 };
+        self.wait_until(Instant::now() + Duration::from_secs_f64((base + jitter).max(0.0)))
+    }
+
+    // รอจนถึงเวลาที่กำหนด (ใช้กับเหตุการณ์ที่ตั้งเวลาไว้ล่วงหน้า เช่น จุดบนเส้นทางเมาส์ เพื่อไม่ให้เวลาคลาดสะสม)
+    pub fn wait_until(&mut self, at: Instant) -> Result<(), BoxError> {
+        loop {
+            if self.cancelled.load(Ordering::Relaxed) {
+                return Err("input command was cancelled".into());
+            }
+            let now = Instant::now();
+            if now >= at {
+                return Ok(());
+            }
+            // ตรวจการยกเลิกอย่างน้อยทุก 10ms
+            thread::sleep((at - now).min(Duration::from_millis(10)));
+        }
+    }
+}
+
+// ตั้งค่ายกเลิกเมื่อ future ที่รอผลถูก drop ก่อนคำสั่งเสร็จ
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+type Job = Box<dyn FnOnce(&dyn InputBackend, &mut Pacer) + Send>;
+
+struct Command {
+    // เวลาที่คำสั่งเข้าคิว
+    queued_at: Instant,
+    cancelled: Arc<AtomicBool>,
+    job: Job,
+}
+
+// worker thread เดียวที่ส่ง input ทั้งหมดผ่าน backend ตามลำดับที่สั่ง
+// clone ได้ทุกสำเนาใช้ worker เดียวกัน worker จะจบเมื่อสำเนาสุดท้ายถูก drop
+#[derive(Clone)]
+pub struct InputActor {
+    tx: mpsc::Sender<Command>,
+    backend: Arc<dyn InputBackend>,
+    profile: Arc<Mutex<TimingProfile>>,
+}
+
+impl InputActor {
+    pub fn spawn(backend: Arc<dyn InputBackend>, profile: TimingProfile) -> Self {
+        let (tx, mut rx) = mpsc::channel::<Command>(QUEUE_CAPACITY);
+        let profile = Arc::new(Mutex::new(profile));
+
+        let worker_backend = backend.clone();
+        let worker_profile = profile.clone();
+        thread::Builder::new()
+            .name(format!("input-{}", backend.name()))
+            .spawn(move || {
+                while let Some(command) = rx.blocking_recv() {
+                    // ผู้สั่งยกเลิกไปแล้วระหว่างรอในคิว
+                    if command.cancelled.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let waited = command.queued_at.elapsed();
+                    if waited > Duration::from_secs(1) {
+                        println!("Input command waited {:.1}s in the queue", waited.as_secs_f64());
+                    }
+                    let mut pacer = Pacer {
+                        profile: *worker_profile.lock().unwrap(),
+                        cancelled: command.cancelled,
+                    };
+                    // job ที่ panic ไม่ทำให้ worker หยุด (ผู้สั่งจะได้ error เพราะไม่มีผลตอบกลับ)
+                    let job = command.job;
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job(worker_backend.as_ref(), &mut pacer)));
+                    if result.is_err() {
+                        println!("Input command panicked");
+                    }
+                }
+            })
+            .expect("failed to start input thread");
+
+        Self { tx, backend, profile }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    // backend ที่ worker ใช้ (สำหรับคำถามที่ไม่ส่ง input เช่น can_type)
+    pub fn backend(&self) -> &Arc<dyn InputBackend> {
+        &self.backend
+    }
+
+    pub fn profile(&self) -> TimingProfile {
+        *self.profile.lock().unwrap()
+    }
+
+    // มีผลกับคำสั่งที่ worker เริ่มทำหลังจากนี้
+    pub fn set_profile(&self, profile: TimingProfile) {
+        *self.profile.lock().unwrap() = profile;
+    }
+
+    // ส่งคำสั่งเข้าคิวแล้วรอผล คำสั่งทำตามลำดับที่เข้าคิวบน worker thread
+    pub async fn run<T, F>(&self, job: F) -> Result<T, BoxError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn InputBackend, &mut Pacer) -> Result<T, BoxError> + Send + 'static,
+    {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(cancelled.clone());
+        let command = Command {
+            queued_at: Instant::now(),
+            cancelled,
+            job: Box::new(move |input, pacer| {
+                let _ = reply_tx.send(job(input, pacer));
+            }),
+        };
+        self.tx.send(command).await.map_err(|_| "input thread has stopped")?;
+        reply_rx.await.map_err(|_| "input command failed")?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_backend::{InputAction, RecordingInputBackend};
+
+    #[tokio::test]
+    async fn commands_run_in_the_order_they_were_queued() {
+        let recorder = Arc::new(RecordingInputBackend::new());
+        let actor = InputActor::spawn(recorder.clone(), TimingProfile::Fast);
+
+        let first = actor.clone();
+        let slow = tokio::spawn(async move {
+            first.run(|input, pacer| {
+                input.move_to(1, 1)?;
+                pacer.pause(Gap::Settle)?;
+                input.move_to(2, 2)
+            }).await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        actor.run(|input, _| input.move_to(3, 3)).await.unwrap();
+        slow.await.unwrap().unwrap();
+
+        let moves: Vec<InputAction> = [(1, 1), (2, 2), (3, 3)].iter().map(|&(x, y)| InputAction::MoveTo { x, y }).collect();
+        assert_eq!(recorder.actions(), moves);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_caller_cancels_the_command() {
+        let recorder = Arc::new(RecordingInputBackend::new());
+        let actor = InputActor::spawn(recorder.clone(), TimingProfile::LegacySafe);
+
+        let run = actor.run(|input, pacer| {
+            input.key_down("shift")?;
+            pacer.wait_until(Instant::now() + Duration::from_secs(5))?;
+            input.key_up("shift")
+        });
+        assert!(tokio::time::timeout(Duration::from_millis(50), run).await.is_err());
+
+        // คำสั่งถัดไปได้ทำทันทีโดยไม่ต้องรอคำสั่งที่ถูกยกเลิกครบ 5 วินาที
+        let started = Instant::now();
+        actor.run(|input, _| input.key_up("shift")).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(recorder.actions(), vec![
+            InputAction::KeyDown { key: "shift".to_string() },
+            InputAction::KeyUp { key: "shift".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn parses_profile_names() {
+        assert_eq!("fast".parse::<TimingProfile>(), Ok(TimingProfile::Fast));
+        assert_eq!("Legacy_Safe".parse::<TimingProfile>(), Ok(TimingProfile::LegacySafe));
+        assert!("slow".parse::<TimingProfile>().is_err());
+    }
+}
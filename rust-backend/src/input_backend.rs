@@ -0,0 +1,226 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::models::MouseButton;
+
+pub type InputResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+// ตัวส่ง input จำลอง (เมาส์/คีย์บอร์ด) ทุกฟังก์ชันเป็นแบบ blocking ให้เรียกผ่าน InputActor
+// ชื่อคีย์คืออักขระเดียว หรือชื่อคีย์พิเศษ เช่น enter, shift, control, f5
+pub trait InputBackend: Send + Sync {
+    // ชื่อของ backend สำหรับ log และการแจ้งสถานะ
+    fn name(&self) -> &'static str;
+
+    fn move_to(&self, x: i32, y: i32) -> InputResult;
+    // ตำแหน่งเมาส์ปัจจุบัน
+    fn cursor_position(&self) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>>;
+    fn button_down(&self, button: MouseButton) -> InputResult;
+    fn button_up(&self, button: MouseButton) -> InputResult;
+
+    fn click(&self, button: MouseButton) -> InputResult {
+        self.button_down(button)?;
+        self.button_up(button)
+    }
+
+    fn key_down(&self, key: &str) -> InputResult;
+    fn key_up(&self, key: &str) -> InputResult;
+
+    fn key_click(&self, key: &str) -> InputResult {
+        self.key_down(key)?;
+        self.key_up(key)
+    }
+
+    // พิมพ์ข้อความตามที่เขียน (ไม่ขึ้นกับ layout ของคีย์บอร์ด)
+    fn type_text(&self, text: &str) -> InputResult;
+
+    // backend พิมพ์อักขระนี้ด้วย type_text ได้โดยตรงหรือไม่ ถ้าไม่ได้จะวางผ่านคลิปบอร์ดแทน
+    fn can_type(&self, _c: char) -> bool {
+        true
+    }
+
+    // เลื่อน wheel ค่าบวกคือเลื่อนลง/ขวา
+    fn scroll(&self, dx: i32, dy: i32) -> InputResult;
+}
+
+// เลือก backend ตาม environment variable AUTOMATION_INPUT_BACKEND (enigo, uinput หรือ recording)
+// ใช้ uinput บน Wayland ที่ enigo ส่ง input ไม่ได้ และใช้ recording เพื่อรันบนเครื่องที่ไม่มีหน้าจอ เช่น CI
+pub fn from_env() -> Arc<dyn InputBackend> {
+    let backend: Arc<dyn InputBackend> = match std::env::var("AUTOMATION_INPUT_BACKEND").as_deref() {
+        Ok("recording") => Arc::new(RecordingInputBackend::new()),
+        Ok("uinput") => uinput_backend(),
+        Ok("enigo") | Err(_) => Arc::new(crate::mouse_keyboard::EnigoBackend),
+        Ok(other) => {
+            println!("Unknown input backend `{}`, falling back to enigo", other);
+            Arc::new(crate::mouse_keyboard::EnigoBackend)
+        },
+    };
+    println!("Using {} input backend", backend.name());
+    backend
+}
+
+#[cfg(target_os = "linux")]
+fn uinput_backend() -> Arc<dyn InputBackend> {
+    match crate::uinput::UinputBackend::open() {
+        Ok(backend) => Arc::new(backend),
+        Err(e) => {
+            println!("Cannot create uinput device ({}), falling back to enigo", e);
+            Arc::new(crate::mouse_keyboard::EnigoBackend)
+        },
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn uinput_backend() -> Arc<dyn InputBackend> {
+    println!("uinput is only available on Linux, falling back to enigo");
+    Arc::new(crate::mouse_keyboard::EnigoBackend)
+}
+
+// input ที่ RecordingInputBackend บันทึกไว้
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputAction {
+    MoveTo { x: i32, y: i32 },
+    Click { button: MouseButton },
+    ButtonDown { button: MouseButton },
+    ButtonUp { button: MouseButton },
+    KeyDown { key: String },
+    KeyUp { key: String },
+    KeyClick { key: String },
+    TypeText { text: String },
+    Scroll { dx: i32, dy: i32 },
+}
+
+impl fmt::Display for InputAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputAction::MoveTo { x, y } => write!(f, "move to {},{}", x, y),
+            InputAction::Click { button } => write!(f, "click {}", button.as_str()),
+            InputAction::ButtonDown { button } => write!(f, "{} button down", button.as_str()),
+            InputAction::ButtonUp { button } => write!(f, "{} button up", button.as_str()),
+            InputAction::KeyDown { key } => write!(f, "key down {}", key),
+            InputAction::KeyUp { key } => write!(f, "key up {}", key),
+            InputAction::KeyClick { key } => write!(f, "press {}", key),
+            InputAction::TypeText { text } => write!(f, "type \"{}\"", text),
+            InputAction::Scroll { dx, dy } => write!(f, "scroll {},{}", dx, dy),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedInput {
+    // เวลาที่ผ่านไปตั้งแต่สร้าง backend
+    pub elapsed: Duration,
+    pub action: InputAction,
+}
+
+// backend ที่ไม่ส่ง input จริง แต่บันทึกทุก action พร้อมเวลา ใช้ตรวจสอบว่า macro ส่ง input อะไรออกไปบ้าง
+pub struct RecordingInputBackend {
+    started: Instant,
+    log: Mutex<Vec<RecordedInput>>,
+    // ตำแหน่งเมาส์จำลอง (ตำแหน่งล่าสุดที่ move_to)
+    position: Mutex<(i32, i32)>,
+    // ข้อผิดพลาดที่จะคืนให้ action ถัดไป (ใช้จำลองกรณีส่ง input ไม่สำเร็จ)
+    next_failure: Mutex<Option<String>>,
+}
+
+#[allow(dead_code)]
+impl RecordingInputBackend {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            log: Mutex::new(Vec::new()),
+            position: Mutex::new((0, 0)),
+            next_failure: Mutex::new(None),
+        }
+    }
+
+    // action ทั้งหมดที่บันทึกไว้พร้อมเวลา
+    pub fn log(&self) -> Vec<RecordedInput> {
+        self.log.lock().unwrap().clone()
+    }
+
+    // action ทั้งหมดที่บันทึกไว้ (ไม่รวมเวลา)
+    pub fn actions(&self) -> Vec<InputAction> {
+        self.log.lock().unwrap().iter().map(|entry| entry.action.clone()).collect()
+    }
+
+    pub fn clear(&self) {
+        self.log.lock().unwrap().clear();
+    }
+
+    // ดึง action ที่บันทึกไว้ออกทั้งหมด (log จะว่างหลังเรียก)
+    pub fn take_actions(&self) -> Vec<InputAction> {
+        self.log.lock().unwrap().drain(..).map(|entry| entry.action).collect()
+    }
+
+    // ทำให้ action ถัดไปล้มเหลวด้วยข้อความที่กำหนด (action นั้นจะไม่ถูกบันทึก)
+    pub fn fail_next(&self, reason: impl Into<String>) {
+        *self.next_failure.lock().unwrap() = Some(reason.into());
+    }
+
+    fn record(&self, action: InputAction) -> InputResult {
+        if let Some(reason) = self.next_failure.lock().unwrap().take() {
+            return Err(reason.into());
+        }
+        self.log.lock().unwrap().push(RecordedInput {
+            elapsed: self.started.elapsed(),
+            action,
+        });
+        Ok(())
+    }
+}
+
+impl Default for RecordingInputBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputBackend for RecordingInputBackend {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> InputResult {
+        self.record(InputAction::MoveTo { x, y })?;
+        *self.position.lock().unwrap() = (x, y);
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(*self.position.lock().unwrap())
+    }
+
+    fn button_down(&self, button: MouseButton) -> InputResult {
+        self.record(InputAction::ButtonDown { button })
+    }
+
+    fn button_up(&self, button: MouseButton) -> InputResult {
+        self.record(InputAction::ButtonUp { button })
+    }
+
+    fn click(&self, button: MouseButton) -> InputResult {
+        self.record(InputAction::Click { button })
+    }
+
+    fn key_down(&self, key: &str) -> InputResult {
+        self.record(InputAction::KeyDown { key: key.to_string() })
+    }
+
+    fn key_up(&self, key: &str) -> InputResult {
+        self.record(InputAction::KeyUp { key: key.to_string() })
+    }
+
+    fn key_click(&self, key: &str) -> InputResult {
+        self.record(InputAction::KeyClick { key: key.to_string() })
+    }
+
+    fn type_text(&self, text: &str) -> InputResult {
+        self.record(InputAction::TypeText { text: text.to_string() })
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> InputResult {
+        self.record(InputAction::Scroll { dx, dy })
+    }
+}
@@ -0,0 +1,114 @@
+use device_query::{DeviceQuery, DeviceState, Keycode, MouseState};
+use tokio::sync::broadcast;
+
+use crate::key_chord::Key;
+use crate::wheel::WheelEvent;
+
+// ตัวอ่านสถานะเมาส์/คีย์บอร์ดของระบบที่ recorder ใช้
+pub trait InputMonitor: DeviceQuery {
+    // ชื่อของ monitor สำหรับ log
+    fn name(&self) -> &'static str;
+
+    // ตำแหน่งจาก get_mouse ตรงกับ cursor จริงหรือไม่ ถ้าไม่ recorder จะไม่บันทึกตำแหน่งเมาส์
+    fn knows_cursor_position(&self) -> bool;
+
+    // คีย์ที่กดอยู่ตามลำดับที่กด (recorder ใช้แทน get_keys เพราะ Keycode ไม่มีบางคีย์)
+    fn pressed_keys(&self) -> Vec<Key>;
+
+    fn subscribe_wheel_events(&self) -> broadcast::Receiver<WheelEvent>;
+}
+
+// อ่านผ่าน device_query (Windows, macOS และ X11)
+pub struct DeviceStateMonitor(DeviceState);
+
+impl DeviceStateMonitor {
+    pub fn new() -> Self {
+        Self(DeviceState::new())
+    }
+}
+
+impl Default for DeviceStateMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceQuery for DeviceStateMonitor {
+    fn get_mouse(&self) -> MouseState {
+        self.0.get_mouse()
+    }
+
+    fn get_keys(&self) -> Vec<Keycode> {
+        self.0.get_keys()
+    }
+}
+
+impl InputMonitor for DeviceStateMonitor {
+    fn name(&self) -> &'static str {
+        "device_query"
+    }
+
+    fn knows_cursor_position(&self) -> bool {
+        true
+    }
+
+    fn pressed_keys(&self) -> Vec<Key> {
+        self.0.get_keys().into_iter().map(crate::key_chord::key_for_keycode).collect()
+    }
+
+    fn subscribe_wheel_events(&self) -> broadcast::Receiver<WheelEvent> {
+        crate::wheel::subscribe_wheel_events()
+    }
+}
+
+impl InputMonitor for crate::evdev::EvdevMonitor {
+    fn name(&self) -> &'static str {
+        "evdev"
+    }
+
+    fn knows_cursor_position(&self) -> bool {
+        crate::evdev::EvdevMonitor::knows_cursor_position(self)
+    }
+
+    fn pressed_keys(&self) -> Vec<Key> {
+        crate::evdev::EvdevMonitor::pressed_keys(self)
+    }
+
+    fn subscribe_wheel_events(&self) -> broadcast::Receiver<WheelEvent> {
+        crate::evdev::EvdevMonitor::subscribe_wheel_events(self)
+    }
+}
+
+// เลือก monitor ตาม environment variable AUTOMATION_INPUT_MONITOR (device_query หรือ evdev)
+// ถ้าไม่ระบุจะใช้ evdev เมื่อ AUTOMATION_INPUT_BACKEND เป็น uinput (เช่น บน Wayland)
+pub fn from_env() -> Box<dyn InputMonitor> {
+    let use_evdev = match std::env::var("AUTOMATION_INPUT_MONITOR").as_deref() {
+        Ok("evdev") => true,
+        Ok("device_query") => false,
+        Ok(other) => {
+            println!("Unknown input monitor `{}`, falling back to device_query", other);
+            false
+        },
+        Err(_) => std::env::var("AUTOMATION_INPUT_BACKEND").as_deref() == Ok("uinput"),
+    };
+    let monitor: Box<dyn InputMonitor> = if use_evdev { evdev_monitor() } else { Box::new(DeviceStateMonitor::new()) };
+    println!("Using {} input monitor", monitor.name());
+    monitor
+}
+
+#[cfg(target_os = "linux")]
+fn evdev_monitor() -> Box<dyn InputMonitor> {
+    match crate::evdev::EvdevMonitor::open(crate::evdev::screen_size_from_env()) {
+        Ok(monitor) => Box::new(monitor),
+        Err(e) => {
+            println!("Cannot read evdev devices ({}), falling back to device_query", e);
+            Box::new(DeviceStateMonitor::new())
+        },
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn evdev_monitor() -> Box<dyn InputMonitor> {
+    println!("evdev is only available on Linux, falling back to device_query");
+    Box::new(DeviceStateMonitor::new())
+}
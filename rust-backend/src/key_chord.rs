@@ -0,0 +1,563 @@
+use std::fmt;
+use std::str::FromStr;
+
+use device_query::Keycode;
+
+// modifier ที่กดค้างไว้ระหว่างกดคีย์หลักของ chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    Control,
+    Shift,
+    Alt,
+    // ปุ่ม Windows / Super / Command
+    Meta,
+}
+
+impl Modifier {
+    pub fn from_name(name: &str) -> Option<Modifier> {
+        Modifier::from_key(Key::from_name(name)?)
+    }
+
+    pub fn from_key(key: Key) -> Option<Modifier> {
+        match key {
+            Key::Control => Some(Modifier::Control),
+            Key::Shift => Some(Modifier::Shift),
+            Key::Alt => Some(Modifier::Alt),
+            Key::Meta => Some(Modifier::Meta),
+            _ => None,
+        }
+    }
+
+    // คีย์ที่ต้องกดค้างสำหรับ modifier นี้
+    pub fn key(self) -> Key {
+        match self {
+            Modifier::Control => Key::Control,
+            Modifier::Shift => Key::Shift,
+            Modifier::Alt => Key::Alt,
+            Modifier::Meta => Key::Meta,
+        }
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.key().fmt(f)
+    }
+}
+
+// คีย์หนึ่งปุ่ม อักขระที่พิมพ์ได้เก็บเป็น Char ส่วนคีย์อื่นมีชื่อของตัวเอง
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Tab,
+    Enter,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+    Left,
+    Right,
+    Space,
+    Escape,
+    CapsLock,
+    NumLock,
+    ScrollLock,
+    PrintScreen,
+    Pause,
+    // ปุ่ม context menu (Apps)
+    Menu,
+    Shift,
+    Control,
+    Alt,
+    Meta,
+    // F1 - F24
+    F(u8),
+    // ปุ่มตัวเลขบน numpad 0 - 9
+    Numpad(u8),
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+}
+
+impl Key {
+    // แปลงชื่อคีย์ (ไม่สนตัวพิมพ์เล็ก/ใหญ่) หรืออักขระเดี่ยวเป็น Key
+    pub fn from_name(name: &str) -> Option<Key> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(if c == ' ' { Key::Space } else { Key::Char(c) });
+        }
+
+        let lower = name.to_lowercase();
+        let key = match lower.as_str() {
+            "tab" => Key::Tab,
+            "enter" | "return" => Key::Enter,
+            "backspace" => Key::Backspace,
+            "delete" | "del" => Key::Delete,
+            "insert" | "ins" => Key::Insert,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" | "pgup" => Key::PageUp,
+            "pagedown" | "pgdn" => Key::PageDown,
+            "up" | "arrowup" => Key::Up,
+            "down" | "arrowdown" => Key::Down,
+            "left" | "arrowleft" => Key::Left,
+            "right" | "arrowright" => Key::Right,
+            "space" => Key::Space,
+            "escape" | "esc" => Key::Escape,
+            "capslock" => Key::CapsLock,
+            "numlock" => Key::NumLock,
+            "scrolllock" => Key::ScrollLock,
+            "printscreen" | "prtsc" => Key::PrintScreen,
+            "pause" => Key::Pause,
+            "menu" | "apps" => Key::Menu,
+            "shift" => Key::Shift,
+            "ctrl" | "control" => Key::Control,
+            "alt" | "option" => Key::Alt,
+            "meta" | "win" | "windows" | "super" | "cmd" | "command" => Key::Meta,
+            "numpadadd" | "numpadplus" => Key::NumpadAdd,
+            "numpadsubtract" | "numpadminus" => Key::NumpadSubtract,
+            "numpadmultiply" => Key::NumpadMultiply,
+            "numpaddivide" => Key::NumpadDivide,
+            "numpaddecimal" | "numpaddot" => Key::NumpadDecimal,
+            "numpadenter" => Key::NumpadEnter,
+            // ชื่อของเครื่องหมายวรรคตอน (ใช้แทนอักขระที่ชนกับไวยากรณ์ เช่น plus)
+            "plus" => Key::Char('+'),
+            "minus" | "dash" => Key::Char('-'),
+            "equal" | "equals" => Key::Char('='),
+            "comma" => Key::Char(','),
+            "period" | "dot" => Key::Char('.'),
+            "slash" => Key::Char('/'),
+            "backslash" => Key::Char('\\'),
+            "semicolon" => Key::Char(';'),
+            "quote" | "apostrophe" => Key::Char('\''),
+            "backquote" | "grave" => Key::Char('`'),
+            "bracketleft" | "leftbracket" => Key::Char('['),
+            "bracketright" | "rightbracket" => Key::Char(']'),
+            // ชื่อแบบ Keycode ที่ recorder รุ่นเก่าบันทึกไว้ เช่น LControl, Key1
+            _ => return Self::numbered(&lower).or_else(|| name.parse::<Keycode>().ok().map(key_for_keycode)),
+        };
+        Some(key)
+    }
+
+    // ชื่อที่มีตัวเลขต่อท้าย เช่น f13, numpad7
+    fn numbered(lower: &str) -> Option<Key> {
+        if let Some(n) = lower.strip_prefix("numpad").and_then(|n| n.parse::<u8>().ok()) {
+            return (n <= 9).then_some(Key::Numpad(n));
+        }
+        let n = lower.strip_prefix('f')?.parse::<u8>().ok()?;
+        (1..=24).contains(&n).then_some(Key::F(n))
+    }
+}
+
+impl fmt::Display for Key {
+    // ชื่อมาตรฐานของคีย์ แปลงกลับด้วย Key::from_name ได้เสมอ
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Key::Char('+') => "plus",
+            Key::Char(c) => return write!(f, "{}", c),
+            Key::F(n) => return write!(f, "f{}", n),
+            Key::Numpad(n) => return write!(f, "numpad{}", n),
+            Key::Tab => "tab",
+            Key::Enter => "enter",
+            Key::Backspace => "backspace",
+            Key::Delete => "delete",
+            Key::Insert => "insert",
+            Key::Home => "home",
+            Key::End => "end",
+            Key::PageUp => "pageup",
+            Key::PageDown => "pagedown",
+            Key::Up => "up",
+            Key::Down => "down",
+            Key::Left => "left",
+            Key::Right => "right",
+            Key::Space => "space",
+            Key::Escape => "escape",
+            Key::CapsLock => "capslock",
+            Key::NumLock => "numlock",
+            Key::ScrollLock => "scrolllock",
+            Key::PrintScreen => "printscreen",
+            Key::Pause => "pause",
+            Key::Menu => "menu",
+            Key::Shift => "shift",
+            Key::Control => "ctrl",
+            Key::Alt => "alt",
+            Key::Meta => "meta",
+            Key::NumpadAdd => "numpadadd",
+            Key::NumpadSubtract => "numpadsubtract",
+            Key::NumpadMultiply => "numpadmultiply",
+            Key::NumpadDivide => "numpaddivide",
+            Key::NumpadDecimal => "numpaddecimal",
+            Key::NumpadEnter => "numpadenter",
+        };
+        f.write_str(name)
+    }
+}
+
+// ข้อผิดพลาดจากการแปลง chord ระบุ token ที่มีปัญหา
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChordError {
+    pub token: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for KeyChordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.token.is_empty() {
+            write!(f, "Invalid key chord: {}", self.reason)
+        } else {
+            write!(f, "Invalid key `{}`: {}", self.token, self.reason)
+        }
+    }
+}
+
+impl std::error::Error for KeyChordError {}
+
+// คีย์ผสม เช่น ctrl+shift+t ประกอบด้วย modifier (ตามลำดับที่เขียน) และคีย์หลักหนึ่งปุ่ม
+//
+// ไวยากรณ์: chord = { modifier "+" } key
+// ชื่อคีย์ไม่สนตัวพิมพ์เล็ก/ใหญ่ ใช้ "plus" หรือเขียน "ctrl++" สำหรับปุ่ม +
+// ตัวอักษรพิมพ์ใหญ่ที่ไม่มี modifier (เช่น "A") หมายถึง shift+a
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    pub modifiers: Vec<Modifier>,
+    pub key: Key,
+}
+
+impl FromStr for KeyChord {
+    type Err = KeyChordError;
+
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let chord = chord.trim();
+        if chord.is_empty() {
+            return Err(KeyChordError { token: String::new(), reason: "empty key" });
+        }
+
+        // ปุ่ม + เขียนท้าย chord ได้โดยตรง เช่น "+" หรือ "ctrl++"
+        let (prefix, last) = if chord == "+" {
+            (None, "+")
+        } else if let Some(prefix) = chord.strip_suffix("++") {
+            (Some(prefix), "+")
+        } else {
+            match chord.rsplit_once('+') {
+                Some((prefix, last)) => (Some(prefix), last.trim()),
+                None => (None, chord),
+            }
+        };
+
+        let mut modifiers = Vec::new();
+        if let Some(prefix) = prefix {
+            for token in prefix.split('+').map(str::trim) {
+                if token.is_empty() {
+                    return Err(KeyChordError { token: String::new(), reason: "empty key name between `+`" });
+                }
+                let modifier = match Modifier::from_name(token) {
+                    Some(modifier) => modifier,
+                    None if Key::from_name(token).is_some() => {
+                        return Err(KeyChordError { token: token.to_string(), reason: "only the last key of a chord may be a non-modifier" });
+                    },
+                    None => return Err(KeyChordError { token: token.to_string(), reason: "unknown key name" }),
+                };
+                if modifiers.contains(&modifier) {
+                    return Err(KeyChordError { token: token.to_string(), reason: "modifier is repeated" });
+                }
+                modifiers.push(modifier);
+            }
+        }
+
+        if last.is_empty() {
+            return Err(KeyChordError { token: String::new(), reason: "missing key after `+`" });
+        }
+        let mut key = Key::from_name(last)
+            .ok_or_else(|| KeyChordError { token: last.to_string(), reason: "unknown key name" })?;
+
+        // ตัวอักษรพิมพ์ใหญ่: ถ้าไม่มี modifier ให้กด shift ด้วย ถ้ามีอยู่แล้วถือว่าเป็นตัวเล็ก (ctrl+C เท่ากับ ctrl+c)
+        if let Key::Char(c) = key {
+            if c.is_uppercase() {
+                if modifiers.is_empty() {
+                    modifiers.push(Modifier::Shift);
+                }
+                key = Key::Char(c.to_lowercase().next().unwrap_or(c));
+            }
+        }
+        if modifiers.iter().any(|m| m.key() == key) {
+            return Err(KeyChordError { token: last.to_string(), reason: "modifier is repeated" });
+        }
+
+        Ok(KeyChord { modifiers, key })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier)?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+// ตารางชื่อคีย์มาตรฐาน: แปลง Keycode ที่ recorder จับได้เป็นคีย์ของ chord
+// match ครอบคลุมทุก Keycode เพื่อให้ compile ไม่ผ่านถ้า device_query เพิ่มคีย์ใหม่
+pub fn key_for_keycode(keycode: Keycode) -> Key {
+    match keycode {
+        Keycode::Key0 => Key::Char('0'),
+        Keycode::Key1 => Key::Char('1'),
+        Keycode::Key2 => Key::Char('2'),
+        Keycode::Key3 => Key::Char('3'),
+        Keycode::Key4 => Key::Char('4'),
+        Keycode::Key5 => Key::Char('5'),
+        Keycode::Key6 => Key::Char('6'),
+        Keycode::Key7 => Key::Char('7'),
+        Keycode::Key8 => Key::Char('8'),
+        Keycode::Key9 => Key::Char('9'),
+        Keycode::A => Key::Char('a'),
+        Keycode::B => Key::Char('b'),
+        Keycode::C => Key::Char('c'),
+        Keycode::D => Key::Char('d'),
+        Keycode::E => Key::Char('e'),
+        Keycode::F => Key::Char('f'),
+        Keycode::G => Key::Char('g'),
+        Keycode::H => Key::Char('h'),
+        Keycode::I => Key::Char('i'),
+        Keycode::J => Key::Char('j'),
+        Keycode::K => Key::Char('k'),
+        Keycode::L => Key::Char('l'),
+        Keycode::M => Key::Char('m'),
+        Keycode::N => Key::Char('n'),
+        Keycode::O => Key::Char('o'),
+        Keycode::P => Key::Char('p'),
+        Keycode::Q => Key::Char('q'),
+        Keycode::R => Key::Char('r'),
+        Keycode::S => Key::Char('s'),
+        Keycode::T => Key::Char('t'),
+        Keycode::U => Key::Char('u'),
+        Keycode::V => Key::Char('v'),
+        Keycode::W => Key::Char('w'),
+        Keycode::X => Key::Char('x'),
+        Keycode::Y => Key::Char('y'),
+        Keycode::Z => Key::Char('z'),
+        Keycode::F1 => Key::F(1),
+        Keycode::F2 => Key::F(2),
+        Keycode::F3 => Key::F(3),
+        Keycode::F4 => Key::F(4),
+        Keycode::F5 => Key::F(5),
+        Keycode::F6 => Key::F(6),
+        Keycode::F7 => Key::F(7),
+        Keycode::F8 => Key::F(8),
+        Keycode::F9 => Key::F(9),
+        Keycode::F10 => Key::F(10),
+        Keycode::F11 => Key::F(11),
+        Keycode::F12 => Key::F(12),
+        Keycode::Escape => Key::Escape,
+        Keycode::Space => Key::Space,
+        Keycode::LControl | Keycode::RControl => Key::Control,
+        Keycode::LShift | Keycode::RShift => Key::Shift,
+        Keycode::LAlt | Keycode::RAlt => Key::Alt,
+        Keycode::Meta => Key::Meta,
+        Keycode::Enter => Key::Enter,
+        Keycode::Up => Key::Up,
+        Keycode::Down => Key::Down,
+        Keycode::Left => Key::Left,
+        Keycode::Right => Key::Right,
+        Keycode::Backspace => Key::Backspace,
+        Keycode::CapsLock => Key::CapsLock,
+        Keycode::Tab => Key::Tab,
+        Keycode::Home => Key::Home,
+        Keycode::End => Key::End,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::Insert => Key::Insert,
+        Keycode::Delete => Key::Delete,
+        Keycode::Numpad0 => Key::Numpad(0),
+        Keycode::Numpad1 => Key::Numpad(1),
+        Keycode::Numpad2 => Key::Numpad(2),
+        Keycode::Numpad3 => Key::Numpad(3),
+        Keycode::Numpad4 => Key::Numpad(4),
+        Keycode::Numpad5 => Key::Numpad(5),
+        Keycode::Numpad6 => Key::Numpad(6),
+        Keycode::Numpad7 => Key::Numpad(7),
+        Keycode::Numpad8 => Key::Numpad(8),
+        Keycode::Numpad9 => Key::Numpad(9),
+        Keycode::NumpadSubtract => Key::NumpadSubtract,
+        Keycode::NumpadAdd => Key::NumpadAdd,
+        Keycode::NumpadDivide => Key::NumpadDivide,
+        Keycode::NumpadMultiply => Key::NumpadMultiply,
+        // คีย์เครื่องหมายตามตำแหน่งบนคีย์บอร์ด US
+        Keycode::Grave => Key::Char('`'),
+        Keycode::Minus => Key::Char('-'),
+        Keycode::Equal => Key::Char('='),
+        Keycode::LeftBracket => Key::Char('['),
+        Keycode::RightBracket => Key::Char(']'),
+        Keycode::BackSlash => Key::Char('\\'),
+        Keycode::Semicolon => Key::Char(';'),
+        Keycode::Apostrophe => Key::Char('\''),
+        Keycode::Comma => Key::Char(','),
+        Keycode::Dot => Key::Char('.'),
+        Keycode::Slash => Key::Char('/'),
+    }
+}
+
+// แปลงกลุ่มปุ่มที่ถูกกดพร้อมกัน (ตามลำดับที่กด) เป็น chord
+// ปุ่ม modifier ทั้งหมดเป็น modifier ของทุก chord ส่วนปุ่มอื่นแต่ละปุ่มเป็น chord แยกกัน
+// ถ้ากดแต่ modifier (เช่น ctrl+shift) modifier ตัวสุดท้ายจะเป็นคีย์หลัก
+pub fn chords_for_keys(pressed: &[Key]) -> Vec<KeyChord> {
+    let mut modifiers: Vec<Modifier> = Vec::new();
+    let mut keys: Vec<Key> = Vec::new();
+    for &key in pressed {
+        match Modifier::from_key(key) {
+            Some(modifier) if !modifiers.contains(&modifier) => modifiers.push(modifier),
+            Some(_) => {},
+            None if !keys.contains(&key) => keys.push(key),
+            None => {},
+        }
+    }
+
+    if keys.is_empty() {
+        return match modifiers.pop() {
+            Some(last) => vec![KeyChord { modifiers, key: last.key() }],
+            None => Vec::new(),
+        };
+    }
+    keys.into_iter()
+        .map(|key| KeyChord { modifiers: modifiers.clone(), key })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keycode ทั้งหมดของ device_query (ต้องเพิ่มที่นี่ด้วยเมื่ออัปเดต device_query)
+    const ALL_KEYCODES: [Keycode; 96] = [
+        Keycode::Key0, Keycode::Key1, Keycode::Key2, Keycode::Key3, Keycode::Key4,
+        Keycode::Key5, Keycode::Key6, Keycode::Key7, Keycode::Key8, Keycode::Key9,
+        Keycode::A, Keycode::B, Keycode::C, Keycode::D, Keycode::E, Keycode::F, Keycode::G,
+        Keycode::H, Keycode::I, Keycode::J, Keycode::K, Keycode::L, Keycode::M, Keycode::N,
+        Keycode::O, Keycode::P, Keycode::Q, Keycode::R, Keycode::S, Keycode::T, Keycode::U,
+        Keycode::V, Keycode::W, Keycode::X, Keycode::Y, Keycode::Z,
+        Keycode::F1, Keycode::F2, Keycode::F3, Keycode::F4, Keycode::F5, Keycode::F6,
+        Keycode::F7, Keycode::F8, Keycode::F9, Keycode::F10, Keycode::F11, Keycode::F12,
+        Keycode::Escape, Keycode::Space,
+        Keycode::LControl, Keycode::RControl, Keycode::LShift, Keycode::RShift,
+        Keycode::LAlt, Keycode::RAlt, Keycode::Meta,
+        Keycode::Enter, Keycode::Up, Keycode::Down, Keycode::Left, Keycode::Right,
+        Keycode::Backspace, Keycode::CapsLock, Keycode::Tab, Keycode::Home, Keycode::End,
+        Keycode::PageUp, Keycode::PageDown, Keycode::Insert, Keycode::Delete,
+        Keycode::Numpad0, Keycode::Numpad1, Keycode::Numpad2, Keycode::Numpad3, Keycode::Numpad4,
+        Keycode::Numpad5, Keycode::Numpad6, Keycode::Numpad7, Keycode::Numpad8, Keycode::Numpad9,
+        Keycode::NumpadSubtract, Keycode::NumpadAdd, Keycode::NumpadDivide, Keycode::NumpadMultiply,
+        Keycode::Grave, Keycode::Minus, Keycode::Equal, Keycode::LeftBracket, Keycode::RightBracket,
+        Keycode::BackSlash, Keycode::Semicolon, Keycode::Apostrophe, Keycode::Comma, Keycode::Dot,
+        Keycode::Slash,
+    ];
+
+    fn chords_for_keycodes(keycodes: &[Keycode]) -> Vec<KeyChord> {
+        let keys: Vec<Key> = keycodes.iter().map(|&keycode| key_for_keycode(keycode)).collect();
+        chords_for_keys(&keys)
+    }
+
+    #[test]
+    fn keycode_list_has_no_duplicates() {
+        let unique: std::collections::HashSet<_> = ALL_KEYCODES.iter().collect();
+        assert_eq!(unique.len(), ALL_KEYCODES.len());
+    }
+
+    #[test]
+    fn every_keycode_round_trips_to_a_playable_key() {
+        for keycode in ALL_KEYCODES {
+            let key = key_for_keycode(keycode);
+            let chords = chords_for_keycodes(&[keycode]);
+            assert_eq!(chords.len(), 1, "{:?}", keycode);
+
+            let text = chords[0].to_string();
+            let parsed: KeyChord = text
+                .parse()
+                .unwrap_or_else(|err| panic!("{:?} recorded as `{}` does not parse: {}", keycode, text, err));
+            assert_eq!(parsed, KeyChord { modifiers: Vec::new(), key }, "{:?} recorded as `{}`", keycode, text);
+            assert!(
+                crate::mouse_keyboard::enigo_key(parsed.key).is_some(),
+                "{:?} recorded as `{}` has no enigo key",
+                keycode,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn recorded_combinations_become_chords() {
+        let chord = |keys: &[Keycode]| {
+            chords_for_keycodes(keys).iter().map(|c| c.to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(chord(&[Keycode::LControl, Keycode::C]), ["ctrl+c"]);
+        assert_eq!(chord(&[Keycode::LShift, Keycode::A]), ["shift+a"]);
+        assert_eq!(chord(&[Keycode::RControl, Keycode::LShift, Keycode::T]), ["ctrl+shift+t"]);
+        assert_eq!(chord(&[Keycode::LControl, Keycode::RControl, Keycode::Equal]), ["ctrl+="]);
+        assert_eq!(chord(&[Keycode::LAlt, Keycode::Key1, Keycode::Key2]), ["alt+1", "alt+2"]);
+        assert_eq!(chord(&[Keycode::LControl, Keycode::LShift]), ["ctrl+shift"]);
+        assert_eq!(chord(&[Keycode::Meta, Keycode::Numpad5]), ["meta+numpad5"]);
+
+        // คีย์ที่ device_query ไม่มี (จาก evdev)
+        let keys = chords_for_keys(&[Key::Shift, Key::F(13), Key::PrintScreen, Key::NumpadDecimal]);
+        let keys: Vec<String> = keys.iter().map(|c| c.to_string()).collect();
+        assert_eq!(keys, ["shift+f13", "shift+printscreen", "shift+numpaddecimal"]);
+    }
+
+    #[test]
+    fn legacy_recorded_names_still_parse() {
+        for keycode in ALL_KEYCODES {
+            let legacy = format!("{:?}", keycode);
+            let parsed: KeyChord = legacy.parse().unwrap_or_else(|err| panic!("`{}`: {}", legacy, err));
+            let expected = key_for_keycode(keycode);
+            // ตัวอักษรเดี่ยวพิมพ์ใหญ่เช่น "A" หมายถึง shift+a
+            assert_eq!(parsed.key, expected, "`{}`", legacy);
+        }
+        assert_eq!("LControl+C".parse::<KeyChord>().unwrap().to_string(), "ctrl+c");
+        assert_eq!("LShift+Key1".parse::<KeyChord>().unwrap().to_string(), "shift+1");
+    }
+
+    fn chord(text: &str) -> String {
+        text.parse::<KeyChord>().unwrap_or_else(|err| panic!("`{}`: {}", text, err)).to_string()
+    }
+
+    fn chord_error(text: &str) -> KeyChordError {
+        text.parse::<KeyChord>().expect_err("chord should be rejected")
+    }
+
+    #[test]
+    fn parses_chords_into_canonical_names() {
+        assert_eq!(chord("ctrl+shift+t"), "ctrl+shift+t");
+        assert_eq!(chord(" Control + Shift + T "), "ctrl+shift+t");
+        assert_eq!(chord("alt+F4"), "alt+f4");
+        assert_eq!(chord("meta+numpad5"), "meta+numpad5");
+        assert_eq!(chord("Return"), "enter");
+        // modifier คงลำดับตามที่เขียน
+        assert_eq!(chord("shift+ctrl+a"), "shift+ctrl+a");
+    }
+
+    #[test]
+    fn handles_plus_and_uppercase_letters() {
+        assert_eq!(chord("+"), "plus");
+        assert_eq!(chord("ctrl++"), "ctrl+plus");
+        assert_eq!(chord("A"), "shift+a");
+        assert_eq!(chord("ctrl+C"), "ctrl+c");
+    }
+
+    #[test]
+    fn rejects_malformed_chords() {
+        assert_eq!(chord_error("").reason, "empty key");
+        assert_eq!(chord_error("ctrl+nosuchkey").token, "nosuchkey");
+        assert_eq!(chord_error("a+ctrl").reason, "only the last key of a chord may be a non-modifier");
+        assert_eq!(chord_error("ctrl+control+c").reason, "modifier is repeated");
+        assert_eq!(chord_error("shift+shift").reason, "modifier is repeated");
+        assert_eq!(chord_error("ctrl+").reason, "missing key after `+`");
+        assert_eq!(chord_error("ctrl++shift+a").reason, "empty key name between `+`");
+    }
+}
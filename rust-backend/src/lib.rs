@@ -3,10 +3,31 @@ mod mouse_keyboard;
 mod websocket;
 mod models;
 mod handlers;
+mod validation;
+mod executor;
+mod input_backend;
+mod key_chord;
+mod movement;
+mod clipboard;
+mod evdev;
+mod uinput;
+mod input_monitor;
+mod input_actor;
+mod display;
+mod wheel;
 
 // Re-export สิ่งที่จำเป็นสำหรับผู้ใช้งาน library
 pub use automation::AutomationController;
-pub use models::{MacroStep, ClickType, StatusUpdate, StepsUpdate, MousePosition, StepExecuting, RandomTimingConfig};
+pub use validation::StepDiagnostic;
+pub use input_backend::{InputBackend, InputResult, InputAction, RecordedInput, RecordingInputBackend};
+pub use evdev::{EventSink, EventSource, EvdevMonitor, FakeEvdev, InputEvent};
+pub use uinput::UinputBackend;
+pub use input_monitor::{DeviceStateMonitor, InputMonitor};
+pub use input_actor::{Gap, InputActor, Pacer, TimingProfile};
+pub use display::{DisplayLayout, DisplayProvider, DisplayResult, FakeDisplays, FixedDisplay, Monitor, XrandrDisplays};
+pub use clipboard::{ClipboardProvider, ClipboardResult, ClipboardFormat, ClipboardContent, CommandClipboard, MemoryClipboard};
+pub use key_chord::{Key, KeyChord, KeyChordError, Modifier};
+pub use models::{CoordinateSpace, Position, Rect, MacroStep, RawMacroStep, Step, StepTiming, StepError, ErrorPolicy, MouseButton, MouseMoveStep, MouseClickStep, MonitorAnchor, MonitorFallback, MovementKind, MovementProfile, CoordinateJitter, JitterDistribution, DragStep, Point, KeyPressStep, KeyHoldStep, TypeTextStep, ScrollStep, GroupStep, ClickType, StatusUpdate, StepsUpdate, MousePosition, StepExecuting, RandomTimingConfig};
 
 use tokio::runtime::Runtime;
 use warp::Filter;
@@ -15,9 +36,8 @@ use warp::Filter;
 pub fn create_server_instance() -> std::sync::Arc<tokio::sync::Mutex<AutomationController>> {
     // สร้าง shared state สำหรับ automation controller
     let automation = automation::AutomationController::new();
-    let automation = std::sync::Arc::new(tokio::sync::Mutex::new(automation));
     
-    automation
+    std::sync::Arc::new(tokio::sync::Mutex::new(automation))
 }
 
 /// ฟังก์ชันสำหรับสร้าง AutomationController
@@ -3,6 +3,18 @@ mod mouse_keyboard;
 mod websocket;
 mod models;
 mod handlers;
+mod validation;
+mod executor;
+mod input_backend;
+mod key_chord;
+mod movement;
+mod clipboard;
+mod evdev;
+mod uinput;
+mod input_monitor;
+mod input_actor;
+mod display;
+mod wheel;
 
 #[allow(unused_imports)]
 use warp::Filter;
@@ -1,16 +1,1205 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::str::FromStr;
 
 // ข้อมูลขั้นตอนการทำงาน
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// รับ/ส่งในรูปแบบเดิม {id, type, data} ที่ frontend ใช้อยู่ แต่ภายในเก็บเป็นชนิดข้อมูลที่ตรวจสอบแล้ว
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawMacroStep", into = "RawMacroStep")]
 pub struct MacroStep {
+    pub id: String,
+    pub action: Step,
+    pub timing: StepTiming,
+    // จุดหยุดสำหรับการรันแบบ debug (รันปกติจะไม่สนใจค่านี้)
+    pub breakpoint: bool,
+    // วิธีจัดการเมื่อขั้นตอนล้มเหลว ถ้าไม่ระบุจะใช้ค่าของ group ที่อยู่ หรือค่าเริ่มต้นของรอบการทำงาน
+    pub on_error: Option<ErrorPolicy>,
+    // ฟิลด์อื่นๆ ใน data ที่ backend ไม่ได้ใช้ (เช่น step_type, collapsed) เก็บไว้เพื่อส่งกลับให้ frontend
+    pub extra: Map<String, Value>,
+}
+
+// รูปแบบ JSON ดั้งเดิมของขั้นตอน
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawMacroStep {
     pub id: String,
     #[serde(rename = "type")]
     pub type_: String,
+    #[serde(default)]
     pub data: Value,
 }
 
+// ประเภทของขั้นตอนพร้อมข้อมูลเฉพาะของแต่ละประเภท
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    MouseMove(MouseMoveStep),
+    MouseClick(MouseClickStep),
+    MouseDoubleClick(MouseClickStep),
+    // กดปุ่มเมาส์ค้างไว้จนกว่าจะมีขั้นตอน mouse_up หรือรอบการทำงานจบ
+    MouseDown(MouseClickStep),
+    MouseUp(MouseClickStep),
+    Drag(DragStep),
+    KeyPress(KeyPressStep),
+    // กดคีย์ค้างไว้จนกว่าจะมีขั้นตอน key_up หรือรอบการทำงานจบ
+    KeyDown(KeyPressStep),
+    KeyUp(KeyPressStep),
+    KeyHold(KeyHoldStep),
+    TypeText(TypeTextStep),
+    Scroll(ScrollStep),
+    Wait,
+    Group(GroupStep),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseMoveStep {
+    pub position: Position,
+    // ถ้าไม่ระบุจะย้ายเมาส์ไปที่ตำแหน่งทันที
+    pub movement: Option<MovementProfile>,
+    // การสุ่มตำแหน่งเฉพาะขั้นตอนนี้ ถ้าไม่ระบุจะใช้ค่าของรอบการทำงาน
+    pub jitter: Option<CoordinateJitter>,
+    // จอที่ x,y อยู่ตอนบันทึก ถ้าระบุจะคำนวณตำแหน่งใหม่ตามการจัดวางจอปัจจุบัน
+    pub monitor: Option<MonitorAnchor>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseClickStep {
+    pub button: MouseButton,
+    // ตำแหน่งที่คลิก ใช้เฉพาะเมื่อระบุ movement หรือมีการสุ่มตำแหน่ง (ถ้าไม่ระบุจะคลิกที่ตำแหน่งปัจจุบันเหมือนเดิม)
+    pub target: Option<Position>,
+    // เคลื่อนเมาส์ไปที่ target ก่อนคลิก
+    pub movement: Option<MovementProfile>,
+    // การสุ่มตำแหน่งเฉพาะขั้นตอนนี้ ถ้าไม่ระบุจะใช้ค่าของรอบการทำงาน
+    pub jitter: Option<CoordinateJitter>,
+    // จอที่ target อยู่ตอนบันทึก
+    pub monitor: Option<MonitorAnchor>,
+}
+
+// ตำแหน่งของขั้นตอนเทียบกับจอที่บันทึก
+// รูปแบบ JSON: {"id": "HDMI-1", "x": 200, "y": 120, "scale": 1.5}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorAnchor {
+    // ชื่อจอตามที่ DisplayProvider รายงาน
+    pub id: String,
+    // ตำแหน่ง (pixel) นับจากมุมซ้ายบนของจอ
+    pub x: i32,
+    pub y: i32,
+    // อัตราขยายของจอตอนบันทึก (1.5 คือ 150%)
+    pub scale: f64,
+}
+
+// วิธีเล่นขั้นตอนที่บันทึกไว้บนจอซึ่งไม่ได้เชื่อมต่ออยู่
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorFallback {
+    // ใช้ตำแหน่งเดียวกันเทียบกับจอหลัก
+    #[default]
+    Primary,
+    // ใช้ x,y เดิมของขั้นตอนตามที่บันทึก
+    Absolute,
+    // ให้ขั้นตอนล้มเหลว
+    Fail,
+}
+
+impl MonitorFallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MonitorFallback::Primary => "primary",
+            MonitorFallback::Absolute => "absolute",
+            MonitorFallback::Fail => "fail",
+        }
+    }
+}
+
+impl FromStr for MonitorFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "primary" => Ok(MonitorFallback::Primary),
+            "absolute" => Ok(MonitorFallback::Absolute),
+            "fail" => Ok(MonitorFallback::Fail),
+            _ => Err(format!("unknown monitor fallback `{}` (expected primary, absolute or fail)", s)),
+        }
+    }
+}
+
+// การกระจายของตำแหน่งที่สุ่ม
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JitterDistribution {
+    // สุ่มเท่ากันทุกจุดภายในวงกลม
+    #[default]
+    Uniform,
+    // ส่วนใหญ่จะใกล้จุดกึ่งกลาง (ส่วนเบี่ยงเบนมาตรฐานครึ่งหนึ่งของรัศมี และไม่เกินรัศมี)
+    Gaussian,
+}
+
+impl JitterDistribution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JitterDistribution::Uniform => "uniform",
+            JitterDistribution::Gaussian => "gaussian",
+        }
+    }
+}
+
+impl FromStr for JitterDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "uniform" => Ok(JitterDistribution::Uniform),
+            "gaussian" | "normal" => Ok(JitterDistribution::Gaussian),
+            _ => Err(format!("unknown jitter distribution `{}` (expected uniform or gaussian)", s)),
+        }
+    }
+}
+
+// การสุ่มตำแหน่งเมาส์รอบเป้าหมายของ mouse_move และคลิก
+// ใช้ชื่อฟิลด์ jitter_radius / jitter_distribution ทั้งในการตั้งค่ารวมและใน data ของขั้นตอน
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct CoordinateJitter {
+    // รัศมี (pixel) 0 คือไม่สุ่ม
+    #[serde(rename = "jitter_radius", default)]
+    pub radius: f64,
+    #[serde(rename = "jitter_distribution", default)]
+    pub distribution: JitterDistribution,
+}
+
+impl CoordinateJitter {
+    pub fn is_enabled(&self) -> bool {
+        self.radius > 0.0
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.radius.is_finite() || self.radius < 0.0 {
+            return Err(format!("jitter_radius must be a non-negative number of pixels, got {}", self.radius));
+        }
+        Ok(())
+    }
+}
+
+// รูปแบบเส้นทางการเคลื่อนเมาส์
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementKind {
+    // เส้นตรงด้วยความเร็วคงที่
+    Linear,
+    // เส้นตรงที่เร่งตอนออกตัวและชะลอตอนใกล้ถึง
+    Eased,
+    // เส้นโค้ง Bezier แบบสุ่มที่เลยเป้าหมายไปเล็กน้อยแล้วกลับมา
+    Bezier,
+}
+
+impl MovementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementKind::Linear => "linear",
+            MovementKind::Eased => "eased",
+            MovementKind::Bezier => "bezier",
+        }
+    }
+}
+
+impl FromStr for MovementKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(MovementKind::Linear),
+            "eased" | "ease" => Ok(MovementKind::Eased),
+            "bezier" => Ok(MovementKind::Bezier),
+            _ => Err(format!("unknown movement profile `{}` (expected linear, eased or bezier)", s)),
+        }
+    }
+}
+
+// การเคลื่อนเมาส์ไปยังเป้าหมายแบบค่อยๆ เลื่อนแทนการย้ายไปทันที
+// รูปแบบ JSON: {"profile": "bezier", "duration": 0.5, "seed": 42}
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovementProfile {
+    pub kind: MovementKind,
+    // เวลาที่ใช้เคลื่อน (วินาที)
+    pub duration: f64,
+    // seed ของเส้นทางสุ่ม ถ้าไม่ระบุจะสุ่มเส้นทางใหม่ทุกครั้ง
+    pub seed: Option<u64>,
+}
+
+impl MovementProfile {
+    // เวลาเริ่มต้นเมื่อไม่ระบุ duration
+    pub const DEFAULT_DURATION: f64 = 0.5;
+
+    fn to_value(&self) -> Value {
+        let mut value = json!({ "profile": self.kind.as_str(), "duration": self.duration });
+        if let Some(seed) = self.seed {
+            value["seed"] = json!(seed);
+        }
+        value
+    }
+}
+
+impl fmt::Display for MovementProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}s", self.kind.as_str(), self.duration)
+    }
+}
+
+// ตำแหน่งบนหน้าจอ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+// สี่เหลี่ยมบนหน้าจอ (pixel) รูปแบบ JSON: {"x": 0, "y": 0, "width": 1920, "height": 1080}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    // จุดที่สัดส่วน (fx, fy) ของสี่เหลี่ยม โดย 1.0 คือ pixel สุดท้าย
+    pub fn point_at(&self, fx: f64, fy: f64) -> Point {
+        let x = ((fx * self.width as f64).round() as i32).clamp(0, (self.width - 1).max(0));
+        let y = ((fy * self.height as f64).round() as i32).clamp(0, (self.height - 1).max(0));
+        Point { x: self.x + x, y: self.y + y }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x && point.x < self.x + self.width && point.y >= self.y && point.y < self.y + self.height
+    }
+
+    // สัดส่วนของจุดในสี่เหลี่ยม คืน None ถ้าจุดอยู่นอกสี่เหลี่ยม
+    pub fn fraction_of(&self, point: Point) -> Option<(f64, f64)> {
+        if !self.contains(point) {
+            return None;
+        }
+        let fx = (point.x - self.x) as f64 / self.width as f64;
+        let fy = (point.y - self.y) as f64 / self.height as f64;
+        Some((fx, fy))
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+// หน่วยของตำแหน่งในขั้นตอน ("coordinates" ใน data)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordinateSpace {
+    // pixel บนหน้าจอ
+    #[default]
+    Absolute,
+    // สัดส่วน 0..1 ของจอใน monitor ของขั้นตอน (ไม่ระบุคือจอหลัก) หรือของ reference
+    Normalized,
+}
+
+impl CoordinateSpace {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoordinateSpace::Absolute => "absolute",
+            CoordinateSpace::Normalized => "normalized",
+        }
+    }
+}
+
+impl FromStr for CoordinateSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "absolute" => Ok(CoordinateSpace::Absolute),
+            "normalized" => Ok(CoordinateSpace::Normalized),
+            _ => Err(format!("unknown coordinates `{}` (expected absolute or normalized)", s)),
+        }
+    }
+}
+
+// ตำแหน่งเป้าหมายของเมาส์
+// รูปแบบ JSON: x,y เป็น pixel ตามเดิม หรือ {"coordinates": "normalized", "x": 0.5, "y": 0.25, "reference": {..}}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Absolute(Point),
+    // สัดส่วนของจอใน monitor ของขั้นตอน (ไม่ระบุคือจอหลัก) ตอนเล่น หรือของ reference ถ้าระบุ
+    Normalized { x: f64, y: f64, reference: Option<Rect> },
+}
+
+impl Position {
+    // ตำแหน่งแบบ pixel บนหน้าจอขนาด screen
+    pub fn to_absolute(self, screen: Rect) -> Point {
+        match self {
+            Position::Absolute(point) => point,
+            Position::Normalized { x, y, reference } => reference.unwrap_or(screen).point_at(x, y),
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Absolute(point) => write!(f, "{},{}", point.x, point.y),
+            Position::Normalized { x, y, reference: None } => write!(f, "{:.1}%,{:.1}%", x * 100.0, y * 100.0),
+            Position::Normalized { x, y, reference: Some(reference) } => {
+                write!(f, "{:.1}%,{:.1}% of {}", x * 100.0, y * 100.0, reference)
+            },
+        }
+    }
+}
+
+// กดปุ่มเมาส์ค้างที่ from แล้วลากไปยัง to ภายในเวลา duration
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragStep {
+    // ถ้าไม่ระบุจะเริ่มลากจากตำแหน่งเมาส์ปัจจุบัน
+    pub from: Option<Point>,
+    pub to: Point,
+    pub button: MouseButton,
+    // เวลาที่ใช้ลาก (วินาที)
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyPressStep {
+    pub key: String,
+}
+
+// กดคีย์ค้างไว้ตามเวลาที่กำหนดแล้วปล่อย
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyHoldStep {
+    pub key: String,
+    // เวลาที่กดค้าง (วินาที)
+    pub duration: f64,
+}
+
+// พิมพ์ข้อความ Unicode ทีละตัวอักษร
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeTextStep {
+    pub text: String,
+    // เวลารอหลังพิมพ์แต่ละตัวอักษร (วินาที)
+    pub char_delay: f64,
+    // สุ่มเวลารอเพิ่ม/ลดได้ไม่เกินค่านี้ในแต่ละตัวอักษร (วินาที)
+    pub char_jitter: f64,
+}
+
+// เลื่อน wheel ของเมาส์ ค่าบวกคือเลื่อนลง/ขวา (หน่วยเป็นจำนวนรอย wheel)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollStep {
+    pub dx: i32,
+    pub dy: i32,
+    // จำนวนครั้งที่เลื่อน
+    pub repeat: u32,
+    // เวลารอระหว่างการเลื่อนแต่ละครั้ง (วินาที)
+    pub interval: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStep {
+    pub name: Option<String>,
+    pub loop_count: u32,
+    pub steps: Vec<MacroStep>,
+}
+
+// การตั้งค่าเวลาที่ทุกขั้นตอนใช้ร่วมกัน
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepTiming {
+    // เวลารอหลังทำขั้นตอนเสร็จ (วินาที)
+    pub wait_time: f64,
+    pub randomize: bool,
+}
+
+impl Default for StepTiming {
+    fn default() -> Self {
+        Self {
+            wait_time: 1.0,
+            randomize: false,
+        }
+    }
+}
+
+// ปุ่มเมาส์
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    // ปุ่มข้าง (ย้อนกลับ / ไปข้างหน้า)
+    X1,
+    X2,
+}
+
+impl MouseButton {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+            MouseButton::X1 => "x1",
+            MouseButton::X2 => "x2",
+        }
+    }
+}
+
+impl FromStr for MouseButton {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "left" => Ok(MouseButton::Left),
+            "right" => Ok(MouseButton::Right),
+            "middle" => Ok(MouseButton::Middle),
+            "x1" | "back" => Ok(MouseButton::X1),
+            "x2" | "forward" => Ok(MouseButton::X2),
+            _ => Err(format!("unknown mouse button `{}`", s)),
+        }
+    }
+}
+
+// วิธีจัดการเมื่อขั้นตอนทำงานล้มเหลว
+// รูปแบบข้อความ: "abort", "continue" หรือ "retry(n, backoff)"
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ErrorPolicy {
+    // หยุดรอบการทำงานทันที
+    Abort,
+    // แจ้งข้อผิดพลาดแล้วทำขั้นตอนถัดไป (พฤติกรรมเดิม)
+    #[default]
+    Continue,
+    // ลองใหม่สูงสุด attempts ครั้ง รอ backoff วินาทีก่อนลองครั้งแรกและเพิ่มเป็นสองเท่าทุกครั้ง
+    // ถ้ายังล้มเหลวจะหยุดรอบการทำงาน
+    Retry { attempts: u32, backoff: f64 },
+}
+
+impl fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorPolicy::Abort => write!(f, "abort"),
+            ErrorPolicy::Continue => write!(f, "continue"),
+            ErrorPolicy::Retry { attempts, backoff } => write!(f, "retry({}, {})", attempts, backoff),
+        }
+    }
+}
+
+impl FromStr for ErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let policy = s.trim().to_lowercase();
+        match policy.as_str() {
+            "abort" => return Ok(ErrorPolicy::Abort),
+            "continue" => return Ok(ErrorPolicy::Continue),
+            _ => {},
+        }
+
+        let args = policy
+            .strip_prefix("retry")
+            .map(str::trim)
+            .and_then(|rest| rest.strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| format!("unknown error policy `{}` (expected abort, continue or retry(n, backoff))", s))?;
+
+        let mut parts = args.split(',').map(str::trim);
+        let attempts = parts
+            .next()
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("retry count in `{}` must be a positive integer", s))?;
+        let backoff = match parts.next() {
+            None | Some("") => 0.0,
+            Some(b) => b
+                .parse::<f64>()
+                .ok()
+                .filter(|b| b.is_finite() && *b >= 0.0)
+                .ok_or_else(|| format!("retry backoff in `{}` must be a non-negative number of seconds", s))?,
+        };
+        if parts.next().is_some() {
+            return Err(format!("too many arguments in `{}`", s));
+        }
+
+        Ok(ErrorPolicy::Retry { attempts, backoff })
+    }
+}
+
+impl Serialize for ErrorPolicy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+// ข้อผิดพลาดเมื่อข้อมูลขั้นตอนไม่ถูกต้อง
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepError {
+    // ชื่อฟิลด์ที่มีปัญหา (ถ้าระบุได้)
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+impl StepError {
+    pub(crate) fn new(field: &str, reason: impl Into<String>) -> Self {
+        Self {
+            field: Some(field.to_string()),
+            reason: reason.into(),
+        }
+    }
+
+    // เติม prefix ให้ชื่อฟิลด์ ใช้กับขั้นตอนที่อยู่ใน group
+    pub(crate) fn nested(self, prefix: &str) -> Self {
+        Self {
+            field: Some(match self.field {
+                Some(field) => format!("{}.{}", prefix, field),
+                None => prefix.to_string(),
+            }),
+            reason: self.reason,
+        }
+    }
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}", field, self.reason),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+impl MouseClickStep {
+    // ส่วนท้ายของคำอธิบายเมื่อเคลื่อนเมาส์ก่อนคลิก เช่น " at 640,380 (eased 0.5s)"
+    fn describe_movement(&self) -> String {
+        match (self.target, &self.movement) {
+            (Some(target), Some(movement)) => format!(" at {} ({})", target, movement),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Step {
+    // ชื่อประเภทขั้นตอนตามที่ใช้ในฟิลด์ "type"
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Step::MouseMove(_) => "mouse_move",
+            Step::MouseClick(_) => "mouse_click",
+            Step::MouseDoubleClick(_) => "mouse_double_click",
+            Step::MouseDown(_) => "mouse_down",
+            Step::MouseUp(_) => "mouse_up",
+            Step::Drag(_) => "drag",
+            Step::KeyPress(_) => "key_press",
+            Step::KeyDown(_) => "key_down",
+            Step::KeyUp(_) => "key_up",
+            Step::KeyHold(_) => "key_hold",
+            Step::TypeText(_) => "type_text",
+            Step::Scroll(_) => "scroll",
+            Step::Wait => "wait",
+            Step::Group(_) => "group",
+        }
+    }
+
+    // คำอธิบายสั้นๆ ว่าขั้นตอนนี้จะทำอะไร เช่น "move to 640,380" หรือ "press ctrl+c"
+    pub fn describe(&self) -> String {
+        match self {
+            Step::MouseMove(target) => match &target.movement {
+                Some(movement) => format!("move to {} ({})", target.position, movement),
+                None => format!("move to {}", target.position),
+            },
+            Step::MouseClick(click) => format!("click {}{}", click.button.as_str(), click.describe_movement()),
+            Step::MouseDoubleClick(click) => format!("double click {}{}", click.button.as_str(), click.describe_movement()),
+            Step::MouseDown(click) => format!("{} button down{}", click.button.as_str(), click.describe_movement()),
+            Step::MouseUp(click) => format!("{} button up{}", click.button.as_str(), click.describe_movement()),
+            Step::Drag(drag) => match drag.from {
+                Some(from) => format!("drag {} from {},{} to {},{}", drag.button.as_str(), from.x, from.y, drag.to.x, drag.to.y),
+                None => format!("drag {} to {},{}", drag.button.as_str(), drag.to.x, drag.to.y),
+            },
+            Step::KeyPress(press) => format!("press {}", press.key),
+            Step::KeyDown(press) => format!("key down {}", press.key),
+            Step::KeyUp(press) => format!("key up {}", press.key),
+            Step::KeyHold(hold) => format!("hold {} for {}s", hold.key, hold.duration),
+            Step::TypeText(typing) => format!("type \"{}\"", typing.text),
+            Step::Scroll(scroll) if scroll.repeat > 1 => format!("scroll {},{} x {}", scroll.dx, scroll.dy, scroll.repeat),
+            Step::Scroll(scroll) => format!("scroll {},{}", scroll.dx, scroll.dy),
+            Step::Wait => "wait".to_string(),
+            Step::Group(group) => format!(
+                "group {} ({} steps x {})",
+                group.name.as_deref().unwrap_or("(unnamed)"),
+                group.steps.len(),
+                group.loop_count
+            ),
+        }
+    }
+
+    // ใช้ convert กับตำแหน่งและ monitor ของ mouse_move และคลิก (รวมถึงขั้นตอนใน group) เช่น DisplayLayout::convert
+    // คืนปัญหาของตำแหน่งที่แปลงไม่ได้ ขั้นตอนนั้นจะไม่ถูกเปลี่ยน
+    pub fn convert_coordinates<F>(&mut self, convert: &mut F) -> Vec<StepError>
+    where
+        F: FnMut(Position, &mut Option<MonitorAnchor>) -> Result<Position, String>,
+    {
+        let mut problems = Vec::new();
+        match self {
+            Step::MouseMove(step) => {
+                let mut monitor = step.monitor.clone();
+                match convert(step.position, &mut monitor) {
+                    Ok(position) => {
+                        step.position = position;
+                        step.monitor = monitor;
+                    },
+                    Err(reason) => problems.push(StepError::new("x", reason)),
+                }
+            },
+            Step::MouseClick(step) | Step::MouseDoubleClick(step) | Step::MouseDown(step) | Step::MouseUp(step) => {
+                if let Some(target) = step.target {
+                    let mut monitor = step.monitor.clone();
+                    match convert(target, &mut monitor) {
+                        Ok(position) => {
+                            step.target = Some(position);
+                            step.monitor = monitor;
+                        },
+                        Err(reason) => problems.push(StepError::new("x", reason)),
+                    }
+                }
+            },
+            Step::Group(group) => {
+                for (i, inner) in group.steps.iter_mut().enumerate() {
+                    let prefix = format!("groupSteps[{}]", i);
+                    problems.extend(inner.action.convert_coordinates(convert).into_iter().map(|err| err.nested(&prefix)));
+                }
+            },
+            _ => {},
+        }
+        problems
+    }
+
+    // ชื่อฟิลด์ใน data ที่ประเภทนี้ใช้ (ฟิลด์อื่นจะเก็บไว้ใน extra)
+    fn data_fields(type_: &str) -> &'static [&'static str] {
+        match type_ {
+            "mouse_move" => &["x", "y", "coordinates", "reference", "movement", "jitter_radius", "jitter_distribution", "monitor"],
+            "mouse_click" | "mouse_double_click" | "mouse_down" | "mouse_up" => {
+                &["button", "x", "y", "coordinates", "reference", "movement", "jitter_radius", "jitter_distribution", "monitor"]
+            },
+            "drag" => &["from", "to", "button", "duration"],
+            "key_press" | "key_down" | "key_up" => &["key"],
+            "key_hold" => &["key", "duration"],
+            "type_text" => &["text", "char_delay", "char_jitter"],
+            "scroll" => &["dx", "dy", "repeat", "interval"],
+            "group" => &["isGroup", "groupName", "groupLoopCount", "groupSteps"],
+            _ => &[],
+        }
+    }
+
+    fn from_data(type_: &str, data: &Map<String, Value>) -> Result<Self, StepError> {
+        match type_ {
+            "mouse_move" => {
+                let position = match optional_position(data)? {
+                    Some(position) => position,
+                    None => return Err(StepError::new("x", "is required")),
+                };
+                let monitor = optional_monitor(data)?;
+                check_monitor(position, &monitor)?;
+                Ok(Step::MouseMove(MouseMoveStep {
+                    position,
+                    movement: optional_movement(data)?,
+                    jitter: optional_jitter(data)?,
+                    monitor,
+                }))
+            },
+            "mouse_click" => Ok(Step::MouseClick(mouse_click_data(data)?)),
+            "mouse_double_click" => Ok(Step::MouseDoubleClick(mouse_click_data(data)?)),
+            "mouse_down" => Ok(Step::MouseDown(mouse_click_data(data)?)),
+            "mouse_up" => Ok(Step::MouseUp(mouse_click_data(data)?)),
+            "drag" => Ok(Step::Drag(DragStep {
+                from: match data.get("from") {
+                    None | Some(Value::Null) => None,
+                    Some(_) => Some(required_point(data, "from")?),
+                },
+                to: required_point(data, "to")?,
+                button: optional_button(data)?,
+                duration: optional_seconds(data, "duration")?,
+            })),
+            "key_press" => Ok(Step::KeyPress(key_press_data(data)?)),
+            "key_down" => Ok(Step::KeyDown(key_press_data(data)?)),
+            "key_up" => Ok(Step::KeyUp(key_press_data(data)?)),
+            "key_hold" => {
+                let KeyPressStep { key } = key_press_data(data)?;
+                if data.get("duration").is_none_or(Value::is_null) {
+                    return Err(StepError::new("duration", "is required"));
+                }
+                Ok(Step::KeyHold(KeyHoldStep { key, duration: optional_seconds(data, "duration")? }))
+            },
+            "type_text" => {
+                let text = required_str(data, "text")?;
+                if text.is_empty() {
+                    return Err(StepError::new("text", "must not be empty"));
+                }
+                Ok(Step::TypeText(TypeTextStep {
+                    text: text.to_string(),
+                    char_delay: optional_seconds(data, "char_delay")?,
+                    char_jitter: optional_seconds(data, "char_jitter")?,
+                }))
+            },
+            "scroll" => {
+                let dx = optional_i32(data, "dx")?;
+                let dy = optional_i32(data, "dy")?;
+                if dx == 0 && dy == 0 {
+                    return Err(StepError::new("dy", "dx and dy must not both be 0"));
+                }
+                let repeat = match data.get("repeat") {
+                    None | Some(Value::Null) => 1,
+                    Some(value) => value
+                        .as_u64()
+                        .and_then(|n| u32::try_from(n).ok())
+                        .filter(|n| *n >= 1)
+                        .ok_or_else(|| StepError::new("repeat", format!("must be a positive integer, got {}", value)))?,
+                };
+                Ok(Step::Scroll(ScrollStep { dx, dy, repeat, interval: optional_seconds(data, "interval")? }))
+            },
+            "wait" => Ok(Step::Wait),
+            "group" => {
+                let name = match data.get("groupName") {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(name)) => Some(name.clone()),
+                    Some(_) => return Err(StepError::new("groupName", "expected a string")),
+                };
+                let loop_count = match data.get("groupLoopCount") {
+                    None | Some(Value::Null) => 1,
+                    Some(value) => value
+                        .as_u64()
+                        .and_then(|n| u32::try_from(n).ok())
+                        .ok_or_else(|| StepError::new("groupLoopCount", "expected a non-negative integer"))?,
+                };
+                let steps = match data.get("groupSteps") {
+                    None | Some(Value::Null) => Vec::new(),
+                    Some(Value::Array(values)) => values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| {
+                            MacroStep::deserialize_value(value).map_err(|e| e.nested(&format!("groupSteps[{}]", i)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Some(_) => return Err(StepError::new("groupSteps", "expected an array of steps")),
+                };
+                Ok(Step::Group(GroupStep { name, loop_count, steps }))
+            },
+            _ => Err(StepError {
+                field: Some("type".to_string()),
+                reason: format!("unsupported step type `{}`", type_),
+            }),
+        }
+    }
+
+    fn write_data(&self, data: &mut Map<String, Value>) {
+        match self {
+            Step::MouseMove(step) => {
+                write_position(data, step.position);
+                if let Some(movement) = &step.movement {
+                    data.insert("movement".to_string(), movement.to_value());
+                }
+                write_jitter(data, step.jitter);
+                write_monitor(data, &step.monitor);
+            },
+            Step::MouseClick(step) | Step::MouseDoubleClick(step) | Step::MouseDown(step) | Step::MouseUp(step) => {
+                data.insert("button".to_string(), json!(step.button.as_str()));
+                if let Some(target) = step.target {
+                    write_position(data, target);
+                }
+                if let Some(movement) = &step.movement {
+                    data.insert("movement".to_string(), movement.to_value());
+                }
+                write_jitter(data, step.jitter);
+                write_monitor(data, &step.monitor);
+            },
+            Step::Drag(step) => {
+                if let Some(from) = step.from {
+                    data.insert("from".to_string(), json!({ "x": from.x, "y": from.y }));
+                }
+                data.insert("to".to_string(), json!({ "x": step.to.x, "y": step.to.y }));
+                data.insert("button".to_string(), json!(step.button.as_str()));
+                data.insert("duration".to_string(), json!(step.duration));
+            },
+            Step::KeyPress(step) | Step::KeyDown(step) | Step::KeyUp(step) => {
+                data.insert("key".to_string(), json!(step.key));
+            },
+            Step::KeyHold(step) => {
+                data.insert("key".to_string(), json!(step.key));
+                data.insert("duration".to_string(), json!(step.duration));
+            },
+            Step::TypeText(step) => {
+                data.insert("text".to_string(), json!(step.text));
+                data.insert("char_delay".to_string(), json!(step.char_delay));
+                data.insert("char_jitter".to_string(), json!(step.char_jitter));
+            },
+            Step::Scroll(step) => {
+                data.insert("dx".to_string(), json!(step.dx));
+                data.insert("dy".to_string(), json!(step.dy));
+                data.insert("repeat".to_string(), json!(step.repeat));
+                data.insert("interval".to_string(), json!(step.interval));
+            },
+            Step::Wait => {},
+            Step::Group(group) => {
+                data.insert("isGroup".to_string(), json!(true));
+                if let Some(name) = &group.name {
+                    data.insert("groupName".to_string(), json!(name));
+                }
+                data.insert("groupLoopCount".to_string(), json!(group.loop_count));
+                data.insert("groupSteps".to_string(), json!(group.steps));
+            },
+        }
+    }
+}
+
+impl StepTiming {
+    fn from_data(data: &Map<String, Value>) -> Result<Self, StepError> {
+        let mut timing = StepTiming::default();
+        match data.get("wait_time") {
+            None | Some(Value::Null) => {},
+            Some(value) => {
+                let wait_time = value
+                    .as_f64()
+                    .ok_or_else(|| StepError::new("wait_time", "expected a number of seconds"))?;
+                if !wait_time.is_finite() || wait_time < 0.0 {
+                    return Err(StepError::new("wait_time", format!("must be a non-negative number of seconds, got {}", wait_time)));
+                }
+                timing.wait_time = wait_time;
+            },
+        }
+        match data.get("randomize") {
+            None | Some(Value::Null) => {},
+            Some(Value::Bool(randomize)) => timing.randomize = *randomize,
+            Some(_) => return Err(StepError::new("randomize", "expected true or false")),
+        }
+        Ok(timing)
+    }
+}
+
+impl MacroStep {
+    // สร้างขั้นตอนจากชื่อประเภทและ data ในรูปแบบเดิม
+    pub fn from_legacy(id: String, type_: &str, data: &Value) -> Result<Self, StepError> {
+        let data = data
+            .as_object()
+            .ok_or_else(|| StepError::new("data", "expected an object"))?;
+
+        let action = Step::from_data(type_, data)?;
+        let timing = StepTiming::from_data(data)?;
+        let breakpoint = match data.get("breakpoint") {
+            None | Some(Value::Null) => false,
+            Some(Value::Bool(breakpoint)) => *breakpoint,
+            Some(_) => return Err(StepError::new("breakpoint", "expected true or false")),
+        };
+        let on_error = match data.get("on_error") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(policy)) => Some(policy.parse().map_err(|e: String| StepError::new("on_error", e))?),
+            Some(other) => return Err(StepError::new("on_error", format!("expected a string, got {}", other))),
+        };
+
+        let used = Step::data_fields(type_);
+        let extra = data
+            .iter()
+            .filter(|(key, _)| !used.contains(&key.as_str()) && !["wait_time", "randomize", "breakpoint", "on_error"].contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        Ok(Self { id, action, timing, breakpoint, on_error, extra })
+    }
+
+    // แปลง JSON ของขั้นตอนเดียว รวมถึงกรณีที่ไม่มี id หรือ type
+    pub fn deserialize_value(value: &Value) -> Result<Self, StepError> {
+        let raw = RawMacroStep::deserialize(value).map_err(|e| StepError {
+            field: None,
+            reason: e.to_string(),
+        })?;
+        MacroStep::try_from(raw)
+    }
+
+    // แปลงกลับเป็น data ในรูปแบบเดิม
+    pub fn data(&self) -> Value {
+        let mut data = self.extra.clone();
+        self.action.write_data(&mut data);
+        data.insert("wait_time".to_string(), json!(self.timing.wait_time));
+        data.insert("randomize".to_string(), json!(self.timing.randomize));
+        // ส่ง breakpoint เฉพาะขั้นตอนที่ตั้งไว้ เพื่อให้ JSON ของขั้นตอนทั่วไปเหมือนเดิม
+        if self.breakpoint {
+            data.insert("breakpoint".to_string(), json!(true));
+        }
+        if let Some(policy) = &self.on_error {
+            data.insert("on_error".to_string(), json!(policy.to_string()));
+        }
+        Value::Object(data)
+    }
+}
+
+// ฟังก์ชันช่วยอ่านฟิลด์จาก data
+fn required_i32(data: &Map<String, Value>, field: &str) -> Result<i32, StepError> {
+    let value = data
+        .get(field)
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| StepError::new(field, "is required"))?;
+    value
+        .as_i64()
+        .and_then(|n| i32::try_from(n).ok())
+        .ok_or_else(|| StepError::new(field, format!("expected an integer, got {}", value)))
+}
+
+// จำนวนเต็มที่ไม่ระบุก็ได้ (ค่าเริ่มต้น 0)
+fn optional_i32(data: &Map<String, Value>, field: &str) -> Result<i32, StepError> {
+    match data.get(field) {
+        None | Some(Value::Null) => Ok(0),
+        Some(_) => required_i32(data, field),
+    }
+}
+
+fn required_str<'a>(data: &'a Map<String, Value>, field: &str) -> Result<&'a str, StepError> {
+    let value = data
+        .get(field)
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| StepError::new(field, "is required"))?;
+    value
+        .as_str()
+        .ok_or_else(|| StepError::new(field, format!("expected a string, got {}", value)))
+}
+
+// ตำแหน่งในรูปแบบ {"x": .., "y": ..}
+fn required_point(data: &Map<String, Value>, field: &str) -> Result<Point, StepError> {
+    let point = data
+        .get(field)
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| StepError::new(field, "is required"))?
+        .as_object()
+        .ok_or_else(|| StepError::new(field, "expected an object with x and y"))?;
+    Ok(Point {
+        x: required_i32(point, "x").map_err(|e| e.nested(field))?,
+        y: required_i32(point, "y").map_err(|e| e.nested(field))?,
+    })
+}
+
+fn mouse_click_data(data: &Map<String, Value>) -> Result<MouseClickStep, StepError> {
+    let target = optional_position(data)?;
+    let movement = optional_movement(data)?;
+    if movement.is_some() && target.is_none() {
+        return Err(StepError::new("movement", "requires x and y to move to"));
+    }
+    let monitor = optional_monitor(data)?;
+    match target {
+        Some(target) => check_monitor(target, &monitor)?,
+        None if monitor.is_some() => return Err(StepError::new("monitor", "requires x and y")),
+        None => {},
+    }
+    Ok(MouseClickStep { button: optional_button(data)?, target, movement, jitter: optional_jitter(data)?, monitor })
+}
+
+// ตำแหน่ง x,y ของขั้นตอน (None ถ้าไม่ระบุทั้ง x และ y) ตามหน่วยใน "coordinates"
+fn optional_position(data: &Map<String, Value>) -> Result<Option<Position>, StepError> {
+    let space = match data.get("coordinates") {
+        None | Some(Value::Null) => CoordinateSpace::Absolute,
+        Some(Value::String(space)) => space.parse().map_err(|e: String| StepError::new("coordinates", e))?,
+        Some(other) => return Err(StepError::new("coordinates", format!("expected a string, got {}", other))),
+    };
+    let reference = match data.get("reference") {
+        None | Some(Value::Null) => None,
+        Some(_) if space == CoordinateSpace::Absolute => {
+            return Err(StepError::new("reference", "requires coordinates to be normalized"));
+        },
+        Some(value) => {
+            let reference: Rect = serde_json::from_value(value.clone())
+                .map_err(|_| StepError::new("reference", "expected an object with x, y, width and height"))?;
+            if reference.width <= 0 || reference.height <= 0 {
+                return Err(StepError::new("reference", "width and height must be positive"));
+            }
+            Some(reference)
+        },
+    };
+
+    let has = |field: &str| data.get(field).is_some_and(|v| !v.is_null());
+    if !has("x") && !has("y") {
+        return match space {
+            CoordinateSpace::Absolute => Ok(None),
+            CoordinateSpace::Normalized => Err(StepError::new("x", "is required")),
+        };
+    }
+    Ok(Some(match space {
+        CoordinateSpace::Absolute => Position::Absolute(Point { x: required_i32(data, "x")?, y: required_i32(data, "y")? }),
+        CoordinateSpace::Normalized => Position::Normalized {
+            x: required_fraction(data, "x")?,
+            y: required_fraction(data, "y")?,
+            reference,
+        },
+    }))
+}
+
+// สัดส่วนระหว่าง 0 ถึง 1
+fn required_fraction(data: &Map<String, Value>, field: &str) -> Result<f64, StepError> {
+    let value = data
+        .get(field)
+        .filter(|v| !v.is_null())
+        .ok_or_else(|| StepError::new(field, "is required"))?;
+    value
+        .as_f64()
+        .filter(|fraction| (0.0..=1.0).contains(fraction))
+        .ok_or_else(|| StepError::new(field, format!("must be a fraction between 0 and 1, got {}", value)))
+}
+
+fn write_position(data: &mut Map<String, Value>, position: Position) {
+    match position {
+        Position::Absolute(point) => {
+            data.insert("x".to_string(), json!(point.x));
+            data.insert("y".to_string(), json!(point.y));
+        },
+        Position::Normalized { x, y, reference } => {
+            data.insert("coordinates".to_string(), json!(CoordinateSpace::Normalized.as_str()));
+            data.insert("x".to_string(), json!(x));
+            data.insert("y".to_string(), json!(y));
+            if let Some(reference) = reference {
+                data.insert("reference".to_string(), json!(reference));
+            }
+        },
+    }
+}
+
+// ตำแหน่งแบบสัดส่วนของ reference ไม่ได้เทียบกับจอใด จึงใช้ร่วมกับ monitor ไม่ได้
+fn check_monitor(position: Position, monitor: &Option<MonitorAnchor>) -> Result<(), StepError> {
+    if monitor.is_some() && matches!(position, Position::Normalized { reference: Some(_), .. }) {
+        return Err(StepError::new("monitor", "cannot be used with a reference rectangle"));
+    }
+    Ok(())
+}
+
+// จอที่บันทึกตำแหน่งไว้ {"id": .., "x": .., "y": .., "scale": ..} (scale ไม่ระบุคือ 1)
+fn optional_monitor(data: &Map<String, Value>) -> Result<Option<MonitorAnchor>, StepError> {
+    let monitor = match data.get("monitor") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Object(monitor)) => monitor,
+        Some(other) => return Err(StepError::new("monitor", format!("expected an object, got {}", other))),
+    };
+    let id = required_str(monitor, "id").map_err(|e| e.nested("monitor"))?;
+    if id.is_empty() {
+        return Err(StepError::new("id", "must not be empty").nested("monitor"));
+    }
+    let scale = match monitor.get("scale") {
+        None | Some(Value::Null) => 1.0,
+        Some(value) => value
+            .as_f64()
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .ok_or_else(|| StepError::new("scale", format!("must be a positive number, got {}", value)).nested("monitor"))?,
+    };
+    Ok(Some(MonitorAnchor {
+        id: id.to_string(),
+        x: required_i32(monitor, "x").map_err(|e| e.nested("monitor"))?,
+        y: required_i32(monitor, "y").map_err(|e| e.nested("monitor"))?,
+        scale,
+    }))
+}
+
+fn write_monitor(data: &mut Map<String, Value>, monitor: &Option<MonitorAnchor>) {
+    if let Some(monitor) = monitor {
+        data.insert("monitor".to_string(), json!(monitor));
+    }
+}
+
+// รูปแบบการเคลื่อนเมาส์ {"profile": .., "duration": .., "seed": ..}
+fn optional_movement(data: &Map<String, Value>) -> Result<Option<MovementProfile>, StepError> {
+    let movement = match data.get("movement") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Object(movement)) => movement,
+        Some(other) => return Err(StepError::new("movement", format!("expected an object, got {}", other))),
+    };
+    let kind = required_str(movement, "profile")
+        .map_err(|e| e.nested("movement"))?
+        .parse()
+        .map_err(|e: String| StepError::new("profile", e).nested("movement"))?;
+    let duration = match movement.get("duration") {
+        None | Some(Value::Null) => MovementProfile::DEFAULT_DURATION,
+        Some(_) => optional_seconds(movement, "duration").map_err(|e| e.nested("movement"))?,
+    };
+    let seed = match movement.get("seed") {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(
+            value
+                .as_u64()
+                .ok_or_else(|| StepError::new("seed", format!("expected a non-negative integer, got {}", value)).nested("movement"))?,
+        ),
+    };
+    Ok(Some(MovementProfile { kind, duration, seed }))
+}
+
+// การสุ่มตำแหน่งเฉพาะขั้นตอน (jitter_radius และ jitter_distribution ซึ่งไม่ระบุก็ได้)
+fn optional_jitter(data: &Map<String, Value>) -> Result<Option<CoordinateJitter>, StepError> {
+    let radius = match data.get("jitter_radius") {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(
+            value
+                .as_f64()
+                .filter(|radius| radius.is_finite() && *radius >= 0.0)
+                .ok_or_else(|| StepError::new("jitter_radius", format!("must be a non-negative number of pixels, got {}", value)))?,
+        ),
+    };
+    let distribution = match data.get("jitter_distribution") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(distribution)) => {
+            Some(distribution.parse().map_err(|e: String| StepError::new("jitter_distribution", e))?)
+        },
+        Some(other) => return Err(StepError::new("jitter_distribution", format!("expected a string, got {}", other))),
+    };
+    match (radius, distribution) {
+        (None, None) => Ok(None),
+        (None, Some(_)) => Err(StepError::new("jitter_distribution", "requires jitter_radius")),
+        (Some(radius), distribution) => Ok(Some(CoordinateJitter { radius, distribution: distribution.unwrap_or_default() })),
+    }
+}
+
+fn write_jitter(data: &mut Map<String, Value>, jitter: Option<CoordinateJitter>) {
+    if let Some(jitter) = jitter {
+        data.insert("jitter_radius".to_string(), json!(jitter.radius));
+        data.insert("jitter_distribution".to_string(), json!(jitter.distribution.as_str()));
+    }
+}
+
+fn key_press_data(data: &Map<String, Value>) -> Result<KeyPressStep, StepError> {
+    let key = required_str(data, "key")?;
+    if key.trim().is_empty() {
+        return Err(StepError::new("key", "must not be empty"));
+    }
+    Ok(KeyPressStep { key: key.to_string() })
+}
+
+// เวลาเป็นวินาทีที่ไม่ระบุก็ได้ (ค่าเริ่มต้น 0)
+fn optional_seconds(data: &Map<String, Value>, field: &str) -> Result<f64, StepError> {
+    match data.get(field) {
+        None | Some(Value::Null) => Ok(0.0),
+        Some(value) => value
+            .as_f64()
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+            .ok_or_else(|| StepError::new(field, format!("must be a non-negative number of seconds, got {}", value))),
+    }
+}
+
+fn optional_button(data: &Map<String, Value>) -> Result<MouseButton, StepError> {
+    match data.get("button") {
+        None | Some(Value::Null) => Ok(MouseButton::Left),
+        Some(Value::String(button)) => button.parse().map_err(|e: String| StepError::new("button", e)),
+        Some(other) => Err(StepError::new("button", format!("expected a string, got {}", other))),
+    }
+}
+
+impl TryFrom<RawMacroStep> for MacroStep {
+    type Error = StepError;
+
+    fn try_from(raw: RawMacroStep) -> Result<Self, Self::Error> {
+        MacroStep::from_legacy(raw.id, &raw.type_, &raw.data)
+    }
+}
+
+impl From<MacroStep> for RawMacroStep {
+    fn from(step: MacroStep) -> Self {
+        RawMacroStep {
+            data: step.data(),
+            type_: step.action.type_name().to_string(),
+            id: step.id,
+        }
+    }
+}
+
 // ประเภทการคลิก
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ClickType {
     Left,
@@ -20,6 +1209,7 @@ pub enum ClickType {
 }
 
 // ข้อมูลสถานะการทำงาน
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusUpdate {
     pub status: String,
@@ -27,12 +1217,14 @@ pub struct StatusUpdate {
 }
 
 // ข้อมูลการอัปเดตขั้นตอน
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepsUpdate {
     pub steps: Vec<MacroStep>
 }
 
 // ข้อมูลตำแหน่งเมาส์
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MousePosition {
     pub x: i32,
@@ -40,6 +1232,7 @@ pub struct MousePosition {
 }
 
 // ข้อมูลขั้นตอนที่กำลังทำงาน
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepExecuting {
     pub index: i32,
@@ -47,26 +1240,31 @@ pub struct StepExecuting {
     pub total_loops: i32
 }
 
-// การตั้งค่าการสุ่มเวลา
+// การตั้งค่าการสุ่มเวลา และการสุ่มตำแหน่งเมาส์ (jitter_radius / jitter_distribution)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RandomTimingConfig {
     pub enabled: bool,
     pub min_factor: f32,
-    pub max_factor: f32
+    pub max_factor: f32,
+    #[serde(flatten, default)]
+    pub jitter: CoordinateJitter,
 }
 
 // ข้อมูลการทำงานอัตโนมัติ
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunAutomationData {
     #[serde(default = "default_loop_count")]
     pub loop_count: i32
 }
 
+#[allow(dead_code)]
 fn default_loop_count() -> i32 {
     1
 }
 
 // ข้อมูลการทำงานเสร็จสิ้น
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutomationCompleted {
     pub total_loops: i32,
@@ -74,6 +1272,7 @@ pub struct AutomationCompleted {
 }
 
 // ข้อความ WebSocket
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum WebSocketMessage {
@@ -100,4 +1299,111 @@ pub enum WebSocketMessage {
     
     #[serde(rename = "random_timing_updated")]
     RandomTimingUpdated(RandomTimingConfig)
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: &str, type_: &str, data: Value) -> Value {
+        json!({ "id": id, "type": type_, "data": data })
+    }
+
+    // อ่าน JSON แล้วเขียนกลับต้องได้ค่าเดิมทุกฟิลด์
+    fn assert_round_trip(value: Value) {
+        let parsed = MacroStep::deserialize_value(&value).unwrap_or_else(|e| panic!("{}: {}", value, e));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+        assert_eq!(MacroStep::deserialize_value(&serde_json::to_value(&parsed).unwrap()).unwrap(), parsed);
+    }
+
+    fn parse_error(value: Value) -> StepError {
+        MacroStep::deserialize_value(&value).expect_err("step should be rejected")
+    }
+
+    #[test]
+    fn round_trips_every_step_type() {
+        let steps = vec![
+            step("move", "mouse_move", json!({ "x": 10, "y": 20, "wait_time": 0.5, "randomize": false })),
+            step("move-bezier", "mouse_move", json!({
+                "x": 40, "y": 60,
+                "movement": { "profile": "bezier", "duration": 0.4, "seed": 7 },
+                "jitter_radius": 3.0, "jitter_distribution": "gaussian",
+                "monitor": { "id": "HDMI-1", "x": 200, "y": 120, "scale": 1.5 },
+                "wait_time": 1.0, "randomize": true,
+            })),
+            step("move-normalized", "mouse_move", json!({
+                "coordinates": "normalized", "x": 0.25, "y": 0.75,
+                "reference": { "x": 0, "y": 0, "width": 1920, "height": 1080 },
+                "wait_time": 0.0, "randomize": false,
+            })),
+            step("click", "mouse_click", json!({ "button": "right", "x": 10, "y": 20, "wait_time": 0.0, "randomize": false, "on_error": "abort" })),
+            step("double", "mouse_double_click", json!({ "button": "left", "wait_time": 0.0, "randomize": false, "breakpoint": true })),
+            step("down-x1", "mouse_down", json!({ "button": "x1", "wait_time": 0.0, "randomize": false })),
+            step("up-middle", "mouse_up", json!({ "button": "middle", "wait_time": 0.0, "randomize": false })),
+            step("drag", "drag", json!({
+                "from": { "x": 1, "y": 2 }, "to": { "x": 30, "y": 40 }, "button": "left", "duration": 0.5,
+                "wait_time": 0.0, "randomize": false,
+            })),
+            step("press", "key_press", json!({ "key": "ctrl+c", "wait_time": 0.0, "randomize": false })),
+            step("down", "key_down", json!({ "key": "shift", "wait_time": 0.0, "randomize": false })),
+            step("up", "key_up", json!({ "key": "shift", "wait_time": 0.0, "randomize": false })),
+            step("hold", "key_hold", json!({ "key": "a", "duration": 1.5, "wait_time": 0.0, "randomize": false })),
+            step("type", "type_text", json!({ "text": "สวัสดี", "char_delay": 0.05, "char_jitter": 0.01, "wait_time": 0.0, "randomize": false })),
+            step("scroll", "scroll", json!({ "dx": 0, "dy": -3, "repeat": 2, "interval": 0.1, "wait_time": 0.0, "randomize": false })),
+            step("wait", "wait", json!({ "wait_time": 2.5, "randomize": true })),
+            step("group", "group", json!({
+                "isGroup": true, "groupName": "login", "groupLoopCount": 3,
+                "groupSteps": [step("group-1", "key_press", json!({ "key": "enter", "wait_time": 0.0, "randomize": false }))],
+                "wait_time": 0.0, "randomize": false, "on_error": "retry(2, 0.5)",
+            })),
+        ];
+        for value in steps {
+            assert_round_trip(value);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_data_fields() {
+        let value = step("1", "mouse_move", json!({
+            "x": 10, "y": 20, "wait_time": 0.0, "randomize": false,
+            "step_type": "mouse_move", "collapsed": true,
+        }));
+        let parsed = MacroStep::deserialize_value(&value).unwrap();
+        assert_eq!(parsed.extra.get("step_type"), Some(&json!("mouse_move")));
+        assert_eq!(parsed.extra.get("collapsed"), Some(&json!(true)));
+        assert!(!parsed.extra.contains_key("x"));
+        assert!(!parsed.extra.contains_key("wait_time"));
+        assert_round_trip(value);
+    }
+
+    #[test]
+    fn rejects_malformed_steps() {
+        let error = parse_error(step("1", "mouse_move", json!({ "y": 20 })));
+        assert_eq!(error.field.as_deref(), Some("x"));
+        assert_eq!(error.reason, "is required");
+
+        let error = parse_error(step("2", "mouse_click", json!({ "button": "sideways" })));
+        assert_eq!(error.field.as_deref(), Some("button"));
+        assert!(error.reason.contains("sideways"), "{}", error.reason);
+
+        let error = parse_error(step("3", "wait", json!({ "wait_time": -1.0 })));
+        assert_eq!(error.field.as_deref(), Some("wait_time"));
+        assert!(error.reason.starts_with("must be a non-negative number of seconds"), "{}", error.reason);
+
+        let error = parse_error(step("4", "teleport", json!({})));
+        assert_eq!(error.field.as_deref(), Some("type"));
+    }
+
+    #[test]
+    fn parses_error_policies() {
+        assert_eq!("Abort".parse(), Ok(ErrorPolicy::Abort));
+        assert_eq!("continue".parse(), Ok(ErrorPolicy::Continue));
+        assert_eq!("retry(3)".parse(), Ok(ErrorPolicy::Retry { attempts: 3, backoff: 0.0 }));
+        assert_eq!(" retry (2, 0.5) ".parse(), Ok(ErrorPolicy::Retry { attempts: 2, backoff: 0.5 }));
+        assert_eq!(ErrorPolicy::Retry { attempts: 2, backoff: 0.5 }.to_string(), "retry(2, 0.5)");
+
+        for bad in ["skip", "retry", "retry(0)", "retry(2, -1)", "retry(1, 2, 3)"] {
+            assert!(bad.parse::<ErrorPolicy>().is_err(), "{} should be rejected", bad);
+        }
+    }
+}
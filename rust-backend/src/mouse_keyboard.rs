@@ -3,8 +3,15 @@ use device_query::{DeviceQuery, DeviceState, Keycode};
 use tokio::sync::mpsc::Sender;
 use std::thread;
 use tokio::task;
-use clipboard_win::{formats, get_clipboard, set_clipboard};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::sync::Arc;
+use rand::Rng;
+
+use crate::clipboard::{ClipboardContent, ClipboardFormat, ClipboardProvider, ClipboardResult};
+use crate::input_actor::{Gap, InputActor, Pacer};
+use crate::input_backend::{InputBackend, InputResult};
+use crate::key_chord::{Key, KeyChord, Modifier};
+use crate::models::MovementProfile;
 
 // นิยามประเภทของเหตุการณ์เมาส์
 #[allow(dead_code)]
@@ -17,14 +24,8 @@ pub enum MouseEvent {
     },
 }
 
-// นิยามประเภทของปุ่มเมาส์
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub enum MouseButton {
-    Left,
-    Right,
-    Middle,
-}
+// ประเภทของปุ่มเมาส์ใช้ร่วมกับ models
+pub use crate::models::MouseButton;
 
 // นิยามประเภทของเหตุการณ์คีย์บอร์ด
 #[allow(dead_code)]
@@ -40,7 +41,7 @@ pub enum KeyboardEvent {
 pub fn get_cursor_position() -> (i32, i32) {
     let device_state = DeviceState::new();
     let mouse_state = device_state.get_mouse();
-    (mouse_state.coords.0 as i32, mouse_state.coords.1 as i32)
+    (mouse_state.coords.0, mouse_state.coords.1)
 }
 
 // ฟังก์ชันสำหรับดึงตำแหน่งเมาส์ปัจจุบัน (async version)
@@ -49,323 +50,690 @@ pub async fn get_mouse_position() -> (i32, i32) {
     task::spawn_blocking(move || {
         let device_state = DeviceState::new();
         let mouse_state = device_state.get_mouse();
-        (mouse_state.coords.0 as i32, mouse_state.coords.1 as i32)
+        (mouse_state.coords.0, mouse_state.coords.1)
     }).await.unwrap_or((0, 0))
 }
 
+// backend ที่ส่ง input จริงผ่าน enigo
+pub struct EnigoBackend;
+
+thread_local! {
+    // Enigo หนึ่งตัวต่อ thread (input ทั้งหมดส่งจาก InputActor thread เดียว จึงสร้างเพียงครั้งเดียว)
+    static ENIGO: std::cell::RefCell<Enigo> = std::cell::RefCell::new(Enigo::new());
+}
+
+fn with_enigo<T>(f: impl FnOnce(&mut Enigo) -> T) -> T {
+    ENIGO.with(|enigo| f(&mut enigo.borrow_mut()))
+}
+
+impl EnigoBackend {
+    fn key(name: &str) -> Result<enigo::Key, Box<dyn std::error::Error + Send + Sync>> {
+        let key = Key::from_name(name).ok_or_else(|| format!("Unsupported key: {}", name))?;
+        enigo_key(key).ok_or_else(|| format!("Key `{}` is not supported on this platform", key).into())
+    }
+
+    fn button(button: MouseButton) -> Result<EnigoMouseButton, Box<dyn std::error::Error + Send + Sync>> {
+        let button = match button {
+            MouseButton::Left => EnigoMouseButton::Left,
+            MouseButton::Right => EnigoMouseButton::Right,
+            MouseButton::Middle => EnigoMouseButton::Middle,
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            MouseButton::X1 => EnigoMouseButton::Back,
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            MouseButton::X2 => EnigoMouseButton::Forward,
+            #[allow(unreachable_patterns)]
+            other => return Err(format!("Mouse button `{}` is not supported on this platform", other.as_str()).into()),
+        };
+        Ok(button)
+    }
+}
+
+impl InputBackend for EnigoBackend {
+    fn name(&self) -> &'static str {
+        "enigo"
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> InputResult {
+        with_enigo(|enigo| enigo.mouse_move_to(x, y));
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(with_enigo(|enigo| enigo.mouse_location()))
+    }
+
+    fn button_down(&self, button: MouseButton) -> InputResult {
+        let button = Self::button(button)?;
+        with_enigo(|enigo| enigo.mouse_down(button));
+        Ok(())
+    }
+
+    fn button_up(&self, button: MouseButton) -> InputResult {
+        let button = Self::button(button)?;
+        with_enigo(|enigo| enigo.mouse_up(button));
+        Ok(())
+    }
+
+    fn click(&self, button: MouseButton) -> InputResult {
+        let button = Self::button(button)?;
+        with_enigo(|enigo| enigo.mouse_click(button));
+        Ok(())
+    }
+
+    fn key_down(&self, key: &str) -> InputResult {
+        let key = Self::key(key)?;
+        with_enigo(|enigo| enigo.key_down(key));
+        Ok(())
+    }
+
+    fn key_up(&self, key: &str) -> InputResult {
+        let key = Self::key(key)?;
+        with_enigo(|enigo| enigo.key_up(key));
+        Ok(())
+    }
+
+    fn key_click(&self, key: &str) -> InputResult {
+        let key = Self::key(key)?;
+        with_enigo(|enigo| enigo.key_click(key));
+        Ok(())
+    }
+
+    fn type_text(&self, text: &str) -> InputResult {
+        with_enigo(|enigo| enigo.key_sequence(text));
+        Ok(())
+    }
+
+    fn can_type(&self, c: char) -> bool {
+        // enigo บน Windows ส่งอักขระนอก BMP (เช่น emoji) ได้ไม่ครบ จึงต้องวางผ่านคลิปบอร์ด
+        cfg!(not(target_os = "windows")) || c.len_utf16() == 1
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> InputResult {
+        with_enigo(|enigo| {
+            if dx != 0 {
+                enigo.mouse_scroll_x(dx);
+            }
+            if dy != 0 {
+                enigo.mouse_scroll_y(dy);
+            }
+        });
+        Ok(())
+    }
+}
+
 // เลื่อนเมาส์ไปยังตำแหน่งที่กำหนด
 #[allow(dead_code)]
-pub async fn mouse_move(x: i32, y: i32) {
-    task::spawn_blocking(move || {
-        let mut enigo = Enigo::new();
-        enigo.mouse_move_to(x, y);
-    }).await.unwrap_or(());
+pub async fn mouse_move(input: &InputActor, x: i32, y: i32) -> InputResult {
+    input.run(move |input, _| input.move_to(x, y)).await
+}
+
+// เคลื่อนเมาส์จากตำแหน่งปัจจุบันไปยังตำแหน่งที่กำหนดตามรูปแบบการเคลื่อน
+// ใช้เวลาตาม duration ของ profile (คูณด้วย time_scale)
+pub async fn mouse_move_along(
+    input: &InputActor,
+    x: i32,
+    y: i32,
+    profile: &MovementProfile,
+    time_scale: f64,
+) -> InputResult {
+    let profile = profile.clone();
+    input.run(move |input, pacer| {
+        let from = input.cursor_position()?;
+        let path = crate::movement::movement_path(from, (x, y), &profile);
+        let interval = Duration::from_secs_f64((profile.duration * time_scale).max(0.0) / path.len() as f64);
+        // ตั้งเวลาของแต่ละจุดจากเวลาเริ่ม เวลารวมจึงไม่คลาดตามจำนวนจุด
+        let start = Instant::now();
+        for (i, (px, py)) in path.into_iter().enumerate() {
+            pacer.wait_until(start + interval * i as u32)?;
+            input.move_to(px, py)?;
+        }
+        Ok(())
+    }).await
 }
 
 // คลิกเมาส์
 #[allow(dead_code)]
-pub async fn mouse_click(button: MouseButton) {
-    task::spawn_blocking(move || {
-        let mut enigo = Enigo::new();
-        match button {
-            MouseButton::Left => enigo.mouse_click(EnigoMouseButton::Left),
-            MouseButton::Right => enigo.mouse_click(EnigoMouseButton::Right),
-            MouseButton::Middle => enigo.mouse_click(EnigoMouseButton::Middle),
-        }
-    }).await.unwrap_or(());
+pub async fn mouse_click(input: &InputActor, button: MouseButton) -> InputResult {
+    input.run(move |input, _| input.click(button)).await
 }
 
 // ดับเบิลคลิกเมาส์
 #[allow(dead_code)]
-pub async fn mouse_double_click(button: MouseButton) {
-    task::spawn_blocking(move || {
-        let mut enigo = Enigo::new();
-        match button {
-            MouseButton::Left => {
-                enigo.mouse_click(EnigoMouseButton::Left);
-                // หน่วงเวลาเล็กน้อยระหว่างคลิก
-                thread::sleep(std::time::Duration::from_millis(50));
-                enigo.mouse_click(EnigoMouseButton::Left);
-            },
-            MouseButton::Right => {
-                enigo.mouse_click(EnigoMouseButton::Right);
-                thread::sleep(std::time::Duration::from_millis(50));
-                enigo.mouse_click(EnigoMouseButton::Right);
-            },
-            MouseButton::Middle => {
-                enigo.mouse_click(EnigoMouseButton::Middle);
-                thread::sleep(std::time::Duration::from_millis(50));
-                enigo.mouse_click(EnigoMouseButton::Middle);
+pub async fn mouse_double_click(input: &InputActor, button: MouseButton) -> InputResult {
+    input.run(move |input, pacer| {
+        input.click(button)?;
+        // หน่วงเวลาเล็กน้อยระหว่างคลิก
+        pacer.pause(Gap::DoubleClick)?;
+        input.click(button)
+    }).await
+}
+
+// กดปุ่มเมาส์ค้างไว้
+pub async fn mouse_down(input: &InputActor, button: MouseButton, held: &HeldInputs) -> InputResult {
+    let held = held.clone();
+    input.run(move |input, _| {
+        input.button_down(button)?;
+        held.press_button(button);
+        Ok(())
+    }).await
+}
+
+// ปล่อยปุ่มเมาส์
+pub async fn mouse_up(input: &InputActor, button: MouseButton, held: &HeldInputs) -> InputResult {
+    let held = held.clone();
+    input.run(move |input, _| {
+        input.button_up(button)?;
+        held.release_button(button);
+        Ok(())
+    }).await
+}
+
+// ลากเมาส์: กดปุ่มค้างที่ from แล้วเลื่อนเป็นเส้นตรงไปยัง to ภายใน duration วินาที (คูณด้วย time_scale) แล้วปล่อย
+// ถ้าไม่ระบุ from จะเริ่มจากตำแหน่งเมาส์ปัจจุบัน
+pub async fn mouse_drag(
+    input: &InputActor,
+    from: Option<(i32, i32)>,
+    to: (i32, i32),
+    button: MouseButton,
+    duration: f64,
+    time_scale: f64,
+    held: &HeldInputs,
+) -> InputResult {
+    let held = held.clone();
+    input.run(move |input, pacer| {
+        let from = match from {
+            Some(from) => {
+                input.move_to(from.0, from.1)?;
+                from
             },
+            None => input.cursor_position()?,
+        };
+
+        input.button_down(button)?;
+        held.press_button(button);
+        // หน่วงเล็กน้อยให้โปรแกรมปลายทางรับรู้ว่าเริ่มลาก
+        // (ถ้าถูกยกเลิกระหว่างลาก ปุ่มยังอยู่ใน held และจะถูกปล่อยตอนจบรอบ)
+        pacer.pause(Gap::DragStart)?;
+
+        // เลื่อนประมาณ 60 ครั้งต่อวินาที และอย่างน้อย 10 ครั้งเพื่อให้โปรแกรมเห็นการลาก
+        let duration = (duration * time_scale).max(0.0);
+        let moves = ((duration * 60.0).round() as usize).max(10);
+        let interval = Duration::from_secs_f64(duration / moves as f64);
+        let start = Instant::now();
+        for i in 1..=moves {
+            let t = i as f64 / moves as f64;
+            let x = from.0 + ((to.0 - from.0) as f64 * t).round() as i32;
+            let y = from.1 + ((to.1 - from.1) as f64 * t).round() as i32;
+            input.move_to(x, y)?;
+            pacer.wait_until(start + interval * i as u32)?;
         }
-    }).await.unwrap_or(());
+
+        input.button_up(button)?;
+        held.release_button(button);
+        Ok(())
+    }).await
+}
+
+// เลื่อน wheel repeat ครั้ง รอ interval วินาที (คูณด้วย time_scale) ระหว่างแต่ละครั้ง
+pub async fn mouse_scroll(
+    input: &InputActor,
+    dx: i32,
+    dy: i32,
+    repeat: u32,
+    interval: f64,
+    time_scale: f64,
+) -> InputResult {
+    let interval = Duration::from_secs_f64((interval * time_scale).max(0.0));
+    input.run(move |input, pacer| {
+        let start = Instant::now();
+        for i in 0..repeat {
+            pacer.wait_until(start + interval * i)?;
+            input.scroll(dx, dy)?;
+        }
+        Ok(())
+    }).await
+}
+
+// กด Ctrl ค้างไว้แล้วกดคีย์ที่กำหนด (ใช้กับ copy/paste/select all)
+fn control_shortcut(input: &dyn InputBackend, pacer: &mut Pacer, key: char) -> InputResult {
+    press_chord(input, pacer, &KeyChord { modifiers: vec![Modifier::Control], key: Key::Char(key) })
+}
+
+// ส่ง Ctrl+key แล้วรอให้โปรแกรมปลายทางทำงานเสร็จ
+async fn run_shortcut(input: &InputActor, key: char, name: &str) {
+    println!("Executing {} (Ctrl+{}) command", name, key.to_ascii_uppercase());
+    let result = input.run(move |input, pacer| {
+        control_shortcut(input, pacer, key)?;
+        pacer.pause(Gap::Settle)
+    }).await;
+    if let Err(err) = result {
+        println!("{} failed: {}", name, err);
+    }
 }
 
 // ฟังก์ชันสำหรับ Copy (Ctrl+C)
 #[allow(dead_code)]
-pub async fn perform_copy() {
-    task::spawn_blocking(move || {
-        println!("Executing Copy (Ctrl+C) command");
-        
-        let mut enigo = Enigo::new();
-        enigo.key_down(enigo::Key::Control);
-        thread::sleep(std::time::Duration::from_millis(100));
-        enigo.key_click(enigo::Key::Layout('c'));
-        thread::sleep(std::time::Duration::from_millis(100));
-        enigo.key_up(enigo::Key::Control);
-        
-        // รอให้การคัดลอกเสร็จสมบูรณ์
-        thread::sleep(std::time::Duration::from_millis(300));
-    }).await.unwrap_or(());
+pub async fn perform_copy(input: &InputActor) {
+    run_shortcut(input, 'c', "Copy").await
 }
 
 // ฟังก์ชันสำหรับ Paste (Ctrl+V)
 #[allow(dead_code)]
-pub async fn perform_paste() {
-    task::spawn_blocking(move || {
-        println!("Executing Paste (Ctrl+V) command");
-        
-        let mut enigo = Enigo::new();
-        enigo.key_down(enigo::Key::Control);
-        thread::sleep(std::time::Duration::from_millis(100));
-        enigo.key_click(enigo::Key::Layout('v'));
-        thread::sleep(std::time::Duration::from_millis(100));
-        enigo.key_up(enigo::Key::Control);
-        
-        // รอให้การวางเสร็จสมบูรณ์
-        thread::sleep(std::time::Duration::from_millis(300));
-    }).await.unwrap_or(());
+pub async fn perform_paste(input: &InputActor) {
+    run_shortcut(input, 'v', "Paste").await
 }
 
 // ฟังก์ชันสำหรับ Select All (Ctrl+A)
 #[allow(dead_code)]
-pub async fn perform_select_all() {
-    task::spawn_blocking(move || {
-        println!("Executing Select All (Ctrl+A) command");
-        
-        let mut enigo = Enigo::new();
-        enigo.key_down(enigo::Key::Control);
-        thread::sleep(std::time::Duration::from_millis(100)); // เพิ่มเวลารอเป็น 100ms
-        enigo.key_click(enigo::Key::Layout('a'));
-        thread::sleep(std::time::Duration::from_millis(100)); // เพิ่มเวลารอเป็น 100ms
-        enigo.key_up(enigo::Key::Control);
-        
-        // รอให้การเลือกเสร็จสมบูรณ์
-        thread::sleep(std::time::Duration::from_millis(300)); // เพิ่มเวลารอเป็น 300ms
-    }).await.unwrap_or(());
-}
-
-// ฟังก์ชันสำหรับการกดคีย์แบบทั่วไป
-pub async fn keyboard_press_key(key: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("Pressing key: {}", key);
-    
-    let key = key.to_string();
-    task::spawn_blocking(move || -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut enigo = Enigo::new();
-        let mut has_modifiers = false;
-        
-        // หลังจากการแก้ไข จะตรวจสอบทั้ง ctrl และ Control
-        if key.to_lowercase().contains("ctrl+") || key.to_lowercase().contains("control+") {
-            has_modifiers = true;
-            
-            // ตรวจสอบคีย์ย่อย
-            let sub_key = if key.to_lowercase().contains("ctrl+") {
-                key.split("ctrl+").collect::<Vec<&str>>()[1].to_lowercase()
-            } else {
-                key.split("control+").collect::<Vec<&str>>()[1].to_lowercase()
-            };
-            
-            println!("Pressing Control + {}", sub_key);
-            
-            // พิเศษสำหรับ Ctrl+A, Ctrl+C, Ctrl+V, Ctrl+X
-            match sub_key.as_str() {
-                "a" => {
-                    // Select All
-                    println!("Special command: Select All");
-                    enigo.key_down(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_click(enigo::Key::Layout('a'));
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_up(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(300));
-                    return Ok(());
-                },
-                "c" => {
-                    // Copy
-                    println!("Special command: Copy");
-                    enigo.key_down(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_click(enigo::Key::Layout('c'));
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_up(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(300));
-                    return Ok(());
-                },
-                "v" => {
-                    // Paste
-                    println!("Special command: Paste");
-                    enigo.key_down(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_click(enigo::Key::Layout('v'));
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_up(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(300));
-                    return Ok(());
-                },
-                "x" => {
-                    // Cut
-                    println!("Special command: Cut");
-                    // ทำ Select All ก่อน
-                    enigo.key_down(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_click(enigo::Key::Layout('a'));
-                    thread::sleep(Duration::from_millis(300));
-                    // ทำ Cut
-                    enigo.key_click(enigo::Key::Layout('x'));
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_up(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(300));
-                    return Ok(());
-                },
-                _ => {
-                    // กรณีอื่นๆ ใช้ enigo
-                    enigo.key_down(enigo::Key::Control);
-                    thread::sleep(Duration::from_millis(100));
-                    
-                    if sub_key.len() == 1 {
-                        let c = sub_key.chars().next().unwrap();
-                        enigo.key_click(enigo::Key::Layout(c));
-                    } else {
-                        // พยายามแปลงเป็นคีย์พิเศษ
-                        match sub_key.as_str() {
-                            "tab" => enigo.key_click(enigo::Key::Tab),
-                            "enter" => enigo.key_click(enigo::Key::Return),
-                            "backspace" => enigo.key_click(enigo::Key::Backspace),
-                            "delete" => enigo.key_click(enigo::Key::Delete),
-                            "home" => enigo.key_click(enigo::Key::Home),
-                            "end" => enigo.key_click(enigo::Key::End),
-                            "pageup" => enigo.key_click(enigo::Key::PageUp),
-                            "pagedown" => enigo.key_click(enigo::Key::PageDown),
-                            "up" => enigo.key_click(enigo::Key::UpArrow),
-                            "down" => enigo.key_click(enigo::Key::DownArrow),
-                            "left" => enigo.key_click(enigo::Key::LeftArrow),
-                            "right" => enigo.key_click(enigo::Key::RightArrow),
-                            "space" => enigo.key_click(enigo::Key::Space),
-                            "escape" => enigo.key_click(enigo::Key::Escape),
-                            "f1" => enigo.key_click(enigo::Key::F1),
-                            "f2" => enigo.key_click(enigo::Key::F2),
-                            "f3" => enigo.key_click(enigo::Key::F3),
-                            "f4" => enigo.key_click(enigo::Key::F4),
-                            "f5" => enigo.key_click(enigo::Key::F5),
-                            "f6" => enigo.key_click(enigo::Key::F6),
-                            "f7" => enigo.key_click(enigo::Key::F7),
-                            "f8" => enigo.key_click(enigo::Key::F8),
-                            "f9" => enigo.key_click(enigo::Key::F9),
-                            "f10" => enigo.key_click(enigo::Key::F10),
-                            "f11" => enigo.key_click(enigo::Key::F11),
-                            "f12" => enigo.key_click(enigo::Key::F12),
-                            _ => {
-                                println!("Unsupported key: {}", sub_key);
-                                return Err(Box::new(std::io::Error::new(
-                                    std::io::ErrorKind::InvalidInput,
-                                    format!("Unsupported key: {}", sub_key),
-                                )));
-                            }
-                        }
-                    }
-                    
-                    thread::sleep(Duration::from_millis(100));
-                    enigo.key_up(enigo::Key::Control);
-                }
-            }
+pub async fn perform_select_all(input: &InputActor) {
+    run_shortcut(input, 'a', "Select all").await
+}
+
+// แปลงคีย์เป็นคีย์ของ enigo (None ถ้า enigo ไม่รองรับคีย์นี้บนแพลตฟอร์มปัจจุบัน)
+pub(crate) fn enigo_key(key: Key) -> Option<enigo::Key> {
+    let key = match key {
+        Key::Char(c) => enigo::Key::Layout(c),
+        Key::Tab => enigo::Key::Tab,
+        Key::Enter => enigo::Key::Return,
+        Key::Backspace => enigo::Key::Backspace,
+        Key::Delete => enigo::Key::Delete,
+        Key::Home => enigo::Key::Home,
+        Key::End => enigo::Key::End,
+        Key::PageUp => enigo::Key::PageUp,
+        Key::PageDown => enigo::Key::PageDown,
+        Key::Up => enigo::Key::UpArrow,
+        Key::Down => enigo::Key::DownArrow,
+        Key::Left => enigo::Key::LeftArrow,
+        Key::Right => enigo::Key::RightArrow,
+        Key::Space => enigo::Key::Space,
+        Key::Escape => enigo::Key::Escape,
+        Key::CapsLock => enigo::Key::CapsLock,
+        Key::Shift => enigo::Key::Shift,
+        Key::Control => enigo::Key::Control,
+        Key::Alt => enigo::Key::Alt,
+        Key::Meta => enigo::Key::Meta,
+        // enigo ไม่มีปุ่ม enter ของ numpad แยก
+        Key::NumpadEnter => enigo::Key::Return,
+        Key::F(n) => function_key(n)?,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        Key::Insert => enigo::Key::Insert,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        Key::NumLock => enigo::Key::Numlock,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        Key::Pause => enigo::Key::Pause,
+        #[cfg(target_os = "windows")]
+        Key::PrintScreen => enigo::Key::Snapshot,
+        #[cfg(target_os = "linux")]
+        Key::PrintScreen => enigo::Key::Print,
+        #[cfg(target_os = "windows")]
+        Key::ScrollLock => enigo::Key::Scroll,
+        #[cfg(target_os = "linux")]
+        Key::ScrollLock => enigo::Key::ScrollLock,
+        #[cfg(target_os = "windows")]
+        Key::Menu => enigo::Key::Apps,
+        #[cfg(target_os = "linux")]
+        Key::Menu => enigo::Key::LMenu,
+        #[cfg(target_os = "windows")]
+        Key::Numpad(n) => numpad_key(n)?,
+        #[cfg(target_os = "windows")]
+        Key::NumpadAdd => enigo::Key::Add,
+        #[cfg(target_os = "windows")]
+        Key::NumpadSubtract => enigo::Key::Subtract,
+        #[cfg(target_os = "windows")]
+        Key::NumpadMultiply => enigo::Key::Multiply,
+        #[cfg(target_os = "windows")]
+        Key::NumpadDivide => enigo::Key::Divide,
+        #[cfg(target_os = "windows")]
+        Key::NumpadDecimal => enigo::Key::Decimal,
+        // แพลตฟอร์มอื่น enigo ไม่มีคีย์ numpad จึงส่งอักขระเดียวกันแทน
+        #[cfg(not(target_os = "windows"))]
+        Key::Numpad(n) => enigo::Key::Layout(char::from_digit(u32::from(n), 10)?),
+        #[cfg(not(target_os = "windows"))]
+        Key::NumpadAdd => enigo::Key::Layout('+'),
+        #[cfg(not(target_os = "windows"))]
+        Key::NumpadSubtract => enigo::Key::Layout('-'),
+        #[cfg(not(target_os = "windows"))]
+        Key::NumpadMultiply => enigo::Key::Layout('*'),
+        #[cfg(not(target_os = "windows"))]
+        Key::NumpadDivide => enigo::Key::Layout('/'),
+        #[cfg(not(target_os = "windows"))]
+        Key::NumpadDecimal => enigo::Key::Layout('.'),
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn function_key(n: u8) -> Option<enigo::Key> {
+    let key = match n {
+        1 => enigo::Key::F1,
+        2 => enigo::Key::F2,
+        3 => enigo::Key::F3,
+        4 => enigo::Key::F4,
+        5 => enigo::Key::F5,
+        6 => enigo::Key::F6,
+        7 => enigo::Key::F7,
+        8 => enigo::Key::F8,
+        9 => enigo::Key::F9,
+        10 => enigo::Key::F10,
+        11 => enigo::Key::F11,
+        12 => enigo::Key::F12,
+        13 => enigo::Key::F13,
+        14 => enigo::Key::F14,
+        15 => enigo::Key::F15,
+        16 => enigo::Key::F16,
+        17 => enigo::Key::F17,
+        18 => enigo::Key::F18,
+        19 => enigo::Key::F19,
+        20 => enigo::Key::F20,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        21 => enigo::Key::F21,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        22 => enigo::Key::F22,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        23 => enigo::Key::F23,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        24 => enigo::Key::F24,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(target_os = "windows")]
+fn numpad_key(n: u8) -> Option<enigo::Key> {
+    let key = match n {
+        0 => enigo::Key::Numpad0,
+        1 => enigo::Key::Numpad1,
+        2 => enigo::Key::Numpad2,
+        3 => enigo::Key::Numpad3,
+        4 => enigo::Key::Numpad4,
+        5 => enigo::Key::Numpad5,
+        6 => enigo::Key::Numpad6,
+        7 => enigo::Key::Numpad7,
+        8 => enigo::Key::Numpad8,
+        9 => enigo::Key::Numpad9,
+        _ => return None,
+    };
+    Some(key)
+}
+
+// ตรวจสอบว่า keyboard_press_key รองรับคีย์นี้หรือไม่ โดยไม่กดคีย์จริง
+pub fn validate_key(key: &str) -> Result<(), String> {
+    key.parse::<KeyChord>().map(|_| ()).map_err(|err| err.to_string())
+}
+
+// กด chord: กด modifier ตามลำดับ, กดคีย์หลัก แล้วปล่อย modifier ย้อนกลับ
+// modifier ที่กดไปแล้วจะถูกปล่อยเสมอแม้ขั้นตอนใดล้มเหลว เพื่อไม่ให้คีย์ค้าง
+fn press_chord(input: &dyn InputBackend, pacer: &mut Pacer, chord: &KeyChord) -> InputResult {
+    let mut held: Vec<String> = Vec::with_capacity(chord.modifiers.len());
+    let mut result = Ok(());
+
+    for modifier in &chord.modifiers {
+        let name = modifier.to_string();
+        if let Err(err) = input.key_down(&name) {
+            result = Err(err);
+            break;
         }
-        
-        // ปรับปรุงการจัดการกับคีย์อื่นๆ (ไม่มี modifier)
-        if !has_modifiers {
-            // สำหรับอักขระเดี่ยว
-            if key.len() == 1 {
-                let c = key.chars().next().unwrap();
-                // แทนที่การใช้ key_click ด้วยการใช้ key_down และ key_up
-                println!("Typing single character: {}", c);
-                
-                // จัดการกับอักขระทั้งตัวใหญ่และตัวเล็ก
-                if c.is_uppercase() {
-                    println!("Handling uppercase letter: {}", c);
-                    // ตัวอักษรตัวใหญ่ - ต้องกด Shift ด้วย
-                    enigo.key_down(enigo::Key::Shift);
-                    thread::sleep(Duration::from_millis(50));
-                    let lowercase_c = c.to_lowercase().next().unwrap();
-                    enigo.key_click(enigo::Key::Layout(lowercase_c));
-                    thread::sleep(Duration::from_millis(50));
-                    enigo.key_up(enigo::Key::Shift);
-                } else {
-                    // ตัวอักษรทั่วไป
-                    enigo.key_click(enigo::Key::Layout(c));
+        held.push(name);
+        if let Err(err) = pacer.pause(Gap::Key) {
+            result = Err(err);
+            break;
+        }
+    }
+
+    if result.is_ok() {
+        result = input.key_click(&chord.key.to_string());
+        if result.is_ok() && !held.is_empty() {
+            result = pacer.pause(Gap::Key);
+        }
+    }
+
+    for name in held.iter().rev() {
+        let released = input.key_up(name);
+        if result.is_ok() {
+            result = released;
+        }
+    }
+    result
+}
+
+// ฟังก์ชันสำหรับการกดคีย์แบบทั่วไป รองรับ chord เช่น ctrl+shift+t, alt+f4, numpad5
+pub async fn keyboard_press_key(input: &InputActor, key: &str) -> InputResult {
+    let chord: KeyChord = key.parse()?;
+    println!("Pressing key: {}", chord);
+
+    input.run(move |input, pacer| {
+        press_chord(input, pacer, &chord)?;
+        pacer.pause(Gap::Key)
+    }).await
+}
+
+// คีย์และปุ่มเมาส์ที่รอบการทำงานกดค้างไว้ (ตามลำดับที่กด) ใช้ปล่อยทั้งหมดเมื่อรอบการทำงานจบ
+#[derive(Debug, Clone, Default)]
+pub struct HeldInputs(Arc<std::sync::Mutex<HeldState>>);
+
+#[derive(Debug, Default)]
+struct HeldState {
+    keys: Vec<Key>,
+    buttons: Vec<MouseButton>,
+}
+
+impl HeldInputs {
+    fn press(&self, key: Key) {
+        let mut held = self.0.lock().unwrap();
+        if !held.keys.contains(&key) {
+            held.keys.push(key);
+        }
+    }
+
+    fn release(&self, key: Key) {
+        self.0.lock().unwrap().keys.retain(|held| *held != key);
+    }
+
+    fn press_button(&self, button: MouseButton) {
+        let mut held = self.0.lock().unwrap();
+        if !held.buttons.contains(&button) {
+            held.buttons.push(button);
+        }
+    }
+
+    fn release_button(&self, button: MouseButton) {
+        self.0.lock().unwrap().buttons.retain(|held| *held != button);
+    }
+
+    fn take_all(&self) -> (Vec<Key>, Vec<MouseButton>) {
+        let mut held = self.0.lock().unwrap();
+        (std::mem::take(&mut held.keys), std::mem::take(&mut held.buttons))
+    }
+}
+
+// กดคีย์ทั้งหมดใน chord ค้างไว้ (modifier ก่อน แล้วจึงคีย์หลัก)
+pub async fn key_down(input: &InputActor, key: &str, held: &HeldInputs) -> InputResult {
+    let chord: KeyChord = key.parse()?;
+    let held = held.clone();
+    input.run(move |input, _| {
+        for key in chord.modifiers.iter().map(|m| m.key()).chain(std::iter::once(chord.key)) {
+            input.key_down(&key.to_string())?;
+            held.press(key);
+        }
+        Ok(())
+    }).await
+}
+
+// ปล่อยคีย์ทั้งหมดใน chord (ลำดับย้อนกลับกับ key_down)
+pub async fn key_up(input: &InputActor, key: &str, held: &HeldInputs) -> InputResult {
+    let chord: KeyChord = key.parse()?;
+    let held = held.clone();
+    input.run(move |input, _| {
+        for key in std::iter::once(chord.key).chain(chord.modifiers.iter().rev().map(|m| m.key())) {
+            input.key_up(&key.to_string())?;
+            held.release(key);
+        }
+        Ok(())
+    }).await
+}
+
+// กดคีย์ค้างไว้ duration วินาที (คูณด้วย time_scale) แล้วปล่อย
+// ถ้ารอบการทำงานถูกหยุดระหว่างรอ คีย์จะถูกปล่อยโดย release_held_inputs
+pub async fn key_hold(input: &InputActor, key: &str, duration: f64, time_scale: f64, held: &HeldInputs) -> InputResult {
+    key_down(input, key, held).await?;
+    tokio::time::sleep(Duration::from_secs_f64((duration * time_scale).max(0.0))).await;
+    key_up(input, key, held).await
+}
+
+// ปล่อยปุ่มเมาส์และคีย์ที่ยังกดค้างอยู่ทั้งหมด คืนชื่อคีย์และปุ่มเมาส์ที่ปล่อย
+pub async fn release_held_inputs(input: &InputActor, held: &HeldInputs) -> (Vec<String>, Vec<String>) {
+    let (keys, buttons) = held.take_all();
+    if keys.is_empty() && buttons.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let released = input.run(move |input, _| {
+        let buttons = buttons
+            .iter()
+            .rev()
+            .map(|button| {
+                if let Err(err) = input.button_up(*button) {
+                    println!("Failed to release held {} button: {}", button.as_str(), err);
                 }
-                
-                thread::sleep(Duration::from_millis(50));
-            } else {
-                // สำหรับคีย์พิเศษ
-                match key.to_lowercase().as_str() {
-                    "tab" => enigo.key_click(enigo::Key::Tab),
-                    "enter" => enigo.key_click(enigo::Key::Return),
-                    "backspace" => enigo.key_click(enigo::Key::Backspace),
-                    "delete" => enigo.key_click(enigo::Key::Delete),
-                    "home" => enigo.key_click(enigo::Key::Home),
-                    "end" => enigo.key_click(enigo::Key::End),
-                    "pageup" => enigo.key_click(enigo::Key::PageUp),
-                    "pagedown" => enigo.key_click(enigo::Key::PageDown),
-                    "up" => enigo.key_click(enigo::Key::UpArrow),
-                    "down" => enigo.key_click(enigo::Key::DownArrow),
-                    "left" => enigo.key_click(enigo::Key::LeftArrow),
-                    "right" => enigo.key_click(enigo::Key::RightArrow),
-                    "space" => enigo.key_click(enigo::Key::Space),
-                    "escape" => enigo.key_click(enigo::Key::Escape),
-                    "f1" => enigo.key_click(enigo::Key::F1),
-                    "f2" => enigo.key_click(enigo::Key::F2),
-                    "f3" => enigo.key_click(enigo::Key::F3),
-                    "f4" => enigo.key_click(enigo::Key::F4),
-                    "f5" => enigo.key_click(enigo::Key::F5),
-                    "f6" => enigo.key_click(enigo::Key::F6),
-                    "f7" => enigo.key_click(enigo::Key::F7),
-                    "f8" => enigo.key_click(enigo::Key::F8),
-                    "f9" => enigo.key_click(enigo::Key::F9),
-                    "f10" => enigo.key_click(enigo::Key::F10),
-                    "f11" => enigo.key_click(enigo::Key::F11),
-                    "f12" => enigo.key_click(enigo::Key::F12),
-                    _ => {
-                        println!("Unsupported key: {}", key);
-                        return Err(Box::new(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            format!("Unsupported key: {}", key),
-                        )));
-                    }
+                button.as_str().to_string()
+            })
+            .collect();
+        let keys = keys
+            .iter()
+            .rev()
+            .map(|key| {
+                let name = key.to_string();
+                if let Err(err) = input.key_up(&name) {
+                    println!("Failed to release held key {}: {}", name, err);
                 }
-            }
+                name
+            })
+            .collect();
+        Ok((keys, buttons))
+    }).await;
+    released.unwrap_or_else(|err| {
+        println!("Failed to release held inputs: {}", err);
+        (Vec::new(), Vec::new())
+    })
+}
+
+// ส่วนของข้อความที่พิมพ์ด้วยวิธีเดียวกัน
+enum TextChunk {
+    Type(String),
+    Key(Key),
+    Paste(String),
+}
+
+// แบ่งข้อความเป็นส่วนที่พิมพ์ได้โดยตรง ส่วนที่ต้องกดคีย์ (ขึ้นบรรทัดใหม่, tab) และส่วนที่ต้องวางผ่านคลิปบอร์ด
+// ถ้า per_char เป็นจริง อักขระที่พิมพ์ได้จะแยกเป็นส่วนละตัวเพื่อรอระหว่างตัวอักษร
+fn split_text(input: &dyn InputBackend, text: &str, per_char: bool) -> Result<Vec<TextChunk>, String> {
+    let mut chunks: Vec<TextChunk> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // \r\n นับเป็นการขึ้นบรรทัดใหม่ครั้งเดียว
+            '\r' if chars.peek() == Some(&'\n') => continue,
+            '\r' | '\n' => chunks.push(TextChunk::Key(Key::Enter)),
+            '\t' => chunks.push(TextChunk::Key(Key::Tab)),
+            c if c.is_control() => return Err(format!("Cannot type control character U+{:04X}", c as u32)),
+            c if input.can_type(c) => match chunks.last_mut() {
+                Some(TextChunk::Type(typed)) if !per_char => typed.push(c),
+                _ => chunks.push(TextChunk::Type(c.to_string())),
+            },
+            c => match chunks.last_mut() {
+                Some(TextChunk::Paste(pasted)) => pasted.push(c),
+                _ => chunks.push(TextChunk::Paste(c.to_string())),
+            },
         }
-        
-        Ok(())
-    }).await?
+    }
+    Ok(chunks)
+}
+
+// วางข้อความผ่านคลิปบอร์ด (Ctrl+V) แล้วคืนค่าคลิปบอร์ดเดิม
+async fn paste_text(input: &InputActor, clipboard: &Arc<dyn ClipboardProvider>, text: &str) -> InputResult {
+    let previous = {
+        let clipboard = clipboard.clone();
+        task::spawn_blocking(move || clipboard.snapshot()).await?.ok().flatten()
+    };
+    set_clipboard_text(clipboard, text).await?;
+
+    let result = input.run(|input, pacer| {
+        control_shortcut(input, pacer, 'v')?;
+        // รอให้โปรแกรมปลายทางอ่านคลิปบอร์ดก่อนคืนค่าเดิม
+        pacer.pause(Gap::Settle)
+    }).await;
+
+    if let Some(previous) = previous {
+        if let Err(err) = set_clipboard(clipboard, previous).await {
+            println!("Failed to restore clipboard after paste: {}", err);
+        }
+    }
+    result
+}
+
+// พิมพ์ข้อความ Unicode รอ char_delay ± char_jitter วินาทีหลังแต่ละตัวอักษร (คูณด้วย time_scale)
+// อักขระที่ backend พิมพ์โดยตรงไม่ได้จะวางผ่านคลิปบอร์ดแทน
+pub async fn type_text(
+    input: &InputActor,
+    clipboard: &Arc<dyn ClipboardProvider>,
+    text: &str,
+    char_delay: f64,
+    char_jitter: f64,
+    time_scale: f64,
+) -> InputResult {
+    let per_char = char_delay > 0.0 || char_jitter > 0.0;
+    let chunks = split_text(input.backend().as_ref(), text, per_char)?;
+    println!("Typing {} characters in {} chunks", text.chars().count(), chunks.len());
+
+    for chunk in chunks {
+        match chunk {
+            TextChunk::Type(typed) => {
+                input.run(move |input, _| input.type_text(&typed)).await?;
+            },
+            TextChunk::Key(key) => {
+                input.run(move |input, _| input.key_click(&key.to_string())).await?;
+            },
+            TextChunk::Paste(pasted) => {
+                println!("Pasting {:?} through the clipboard", pasted);
+                paste_text(input, clipboard, &pasted).await?;
+            },
+        }
+
+        if per_char {
+            let jitter = if char_jitter > 0.0 {
+                rand::thread_rng().gen_range(-char_jitter..=char_jitter)
+            } else {
+                0.0
+            };
+            let delay = ((char_delay + jitter) * time_scale).max(0.0);
+            tokio::time::sleep(Duration::from_secs_f64(delay)).await;
+        }
+    }
+    Ok(())
 }
 
 // ฟังก์ชันใหม่สำหรับทำงานกับคลิปบอร์ดโดยตรง
-#[allow(dead_code)]
-pub async fn get_clipboard_text() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    task::spawn_blocking(move || {
-        get_clipboard(formats::Unicode).map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
-            Box::new(std::io::Error::new(std::io::ErrorKind::Other, format!("Clipboard error: {}", e)))
-        })
-    }).await?
+pub async fn get_clipboard_text(clipboard: &Arc<dyn ClipboardProvider>) -> ClipboardResult<String> {
+    let clipboard = clipboard.clone();
+    task::spawn_blocking(move || clipboard.get_text()).await?
 }
 
-#[allow(dead_code)]
-pub async fn set_clipboard_text(text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+pub async fn set_clipboard_text(clipboard: &Arc<dyn ClipboardProvider>, text: &str) -> ClipboardResult<()> {
+    let clipboard = clipboard.clone();
     let text = text.to_string();
-    task::spawn_blocking(move || {
-        set_clipboard(formats::Unicode, text).map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
-            Box::new(std::io::Error::new(std::io::ErrorKind::Other, format!("Clipboard error: {}", e)))
-        })
-    }).await?
+    task::spawn_blocking(move || clipboard.set_text(&text)).await?
+}
+
+// อ่านข้อมูลรูปแบบที่กำหนด (None ถ้าคลิปบอร์ดไม่มีข้อมูลรูปแบบนี้)
+pub async fn get_clipboard(clipboard: &Arc<dyn ClipboardProvider>, format: ClipboardFormat) -> ClipboardResult<Option<ClipboardContent>> {
+    let clipboard = clipboard.clone();
+    task::spawn_blocking(move || clipboard.get(format)).await?
+}
+
+pub async fn set_clipboard(clipboard: &Arc<dyn ClipboardProvider>, content: ClipboardContent) -> ClipboardResult<()> {
+    let clipboard = clipboard.clone();
+    task::spawn_blocking(move || clipboard.set(&content)).await?
 }
 
 // เริ่มติดตามเหตุการณ์เมาส์
@@ -397,8 +765,8 @@ pub async fn start_mouse_listener(tx: Sender<MouseEvent>) {
                     
                     let event = MouseEvent::Click {
                         button,
-                        x: mouse_state.coords.0 as i32,
-                        y: mouse_state.coords.1 as i32,
+                        x: mouse_state.coords.0,
+                        y: mouse_state.coords.1,
                     };
                     
                     // ส่งเหตุการณ์ไปยัง channel
@@ -452,4 +820,284 @@ pub async fn start_keyboard_listener(tx: Sender<KeyboardEvent>) {
             thread::sleep(std::time::Duration::from_millis(10));
         }
     });
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_actor::TimingProfile;
+    use crate::input_backend::{InputAction, RecordingInputBackend};
+
+    fn actor() -> (Arc<RecordingInputBackend>, InputActor) {
+        let recorder = Arc::new(RecordingInputBackend::new());
+        let actor = InputActor::spawn(recorder.clone(), TimingProfile::Fast);
+        (recorder, actor)
+    }
+
+    fn key_down(key: &str) -> InputAction {
+        InputAction::KeyDown { key: key.to_string() }
+    }
+
+    fn key_up(key: &str) -> InputAction {
+        InputAction::KeyUp { key: key.to_string() }
+    }
+
+    fn key_click(key: &str) -> InputAction {
+        InputAction::KeyClick { key: key.to_string() }
+    }
+
+    fn typed(text: &str) -> InputAction {
+        InputAction::TypeText { text: text.to_string() }
+    }
+
+    // backend ที่พิมพ์ได้เฉพาะ ASCII (เหมือน enigo บน Windows ที่พิมพ์ emoji ไม่ได้) ส่งต่อ input ให้ recorder
+    struct AsciiOnly(Arc<RecordingInputBackend>);
+
+    impl InputBackend for AsciiOnly {
+        fn name(&self) -> &'static str {
+            "ascii-only"
+        }
+
+        fn move_to(&self, x: i32, y: i32) -> InputResult {
+            self.0.move_to(x, y)
+        }
+
+        fn cursor_position(&self) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
+            self.0.cursor_position()
+        }
+
+        fn button_down(&self, button: MouseButton) -> InputResult {
+            self.0.button_down(button)
+        }
+
+        fn button_up(&self, button: MouseButton) -> InputResult {
+            self.0.button_up(button)
+        }
+
+        fn key_down(&self, key: &str) -> InputResult {
+            self.0.key_down(key)
+        }
+
+        fn key_up(&self, key: &str) -> InputResult {
+            self.0.key_up(key)
+        }
+
+        fn key_click(&self, key: &str) -> InputResult {
+            self.0.key_click(key)
+        }
+
+        fn type_text(&self, text: &str) -> InputResult {
+            self.0.type_text(text)
+        }
+
+        fn can_type(&self, c: char) -> bool {
+            c.is_ascii()
+        }
+
+        fn scroll(&self, dx: i32, dy: i32) -> InputResult {
+            self.0.scroll(dx, dy)
+        }
+    }
+
+    fn clipboard() -> Arc<dyn ClipboardProvider> {
+        Arc::new(crate::clipboard::MemoryClipboard::new())
+    }
+
+    #[tokio::test]
+    async fn releases_chord_modifiers_in_reverse_order() {
+        let (recorder, input) = actor();
+        keyboard_press_key(&input, "alt+f4").await.unwrap();
+        keyboard_press_key(&input, "Shift+Ctrl+T").await.unwrap();
+
+        assert_eq!(recorder.take_actions(), vec![
+            key_down("alt"),
+            key_click("f4"),
+            key_up("alt"),
+            key_down("shift"),
+            key_down("ctrl"),
+            key_click("t"),
+            key_up("ctrl"),
+            key_up("shift"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_keys_without_sending_input() {
+        let (recorder, input) = actor();
+        assert!(validate_key("ctrl+nosuchkey").is_err());
+        assert!(keyboard_press_key(&input, "ctrl+nosuchkey").await.is_err());
+        assert_eq!(recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn failed_modifier_is_not_released() {
+        let (recorder, input) = actor();
+        recorder.fail_next("device busy");
+        assert!(keyboard_press_key(&input, "ctrl+c").await.is_err());
+        // ctrl กดไม่สำเร็จจึงไม่มีคีย์ค้างให้ปล่อย
+        assert_eq!(recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn types_unicode_text_and_presses_keys_for_line_breaks() {
+        let (recorder, input) = actor();
+        type_text(&input, &clipboard(), "สวัสดี\r\nok\tdone", 0.0, 0.0, 1.0).await.unwrap();
+
+        assert_eq!(recorder.take_actions(), vec![
+            typed("สวัสดี"),
+            key_click(&Key::Enter.to_string()),
+            typed("ok"),
+            key_click(&Key::Tab.to_string()),
+            typed("done"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn types_one_character_at_a_time_with_a_delay() {
+        let (recorder, input) = actor();
+        type_text(&input, &clipboard(), "né", 0.001, 0.0, 1.0).await.unwrap();
+        assert_eq!(recorder.take_actions(), vec![typed("n"), typed("é")]);
+    }
+
+    #[test]
+    fn splits_out_characters_the_backend_cannot_type() {
+        let backend = AsciiOnly(Arc::new(RecordingInputBackend::new()));
+        let chunks = split_text(&backend, "hi 😀!", false).unwrap();
+        assert!(matches!(
+            &chunks[..],
+            [TextChunk::Type(a), TextChunk::Paste(b), TextChunk::Type(c)] if a == "hi " && b == "😀" && c == "!"
+        ));
+    }
+
+    #[tokio::test]
+    async fn pastes_characters_the_backend_cannot_type() {
+        let recorder = Arc::new(RecordingInputBackend::new());
+        let input = InputActor::spawn(Arc::new(AsciiOnly(recorder.clone())), TimingProfile::Fast);
+        let clipboard = clipboard();
+        set_clipboard_text(&clipboard, "previous").await.unwrap();
+
+        type_text(&input, &clipboard, "hi 😀!", 0.0, 0.0, 1.0).await.unwrap();
+        assert_eq!(recorder.take_actions(), vec![
+            typed("hi "),
+            key_down("ctrl"),
+            key_click("v"),
+            key_up("ctrl"),
+            typed("!"),
+        ]);
+        // คืนค่าคลิปบอร์ดเดิมหลังวาง
+        assert_eq!(get_clipboard_text(&clipboard).await.unwrap(), "previous");
+    }
+
+    #[tokio::test]
+    async fn refuses_control_characters() {
+        let (recorder, input) = actor();
+        assert!(type_text(&input, &clipboard(), "a\u{7}b", 0.0, 0.0, 1.0).await.is_err());
+        assert_eq!(recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn releases_held_inputs_in_reverse_order() {
+        let (recorder, input) = actor();
+        let held = HeldInputs::default();
+        super::key_down(&input, "ctrl+a", &held).await.unwrap();
+        mouse_down(&input, MouseButton::Right, &held).await.unwrap();
+        recorder.clear();
+
+        let released = release_held_inputs(&input, &held).await;
+        assert_eq!(released, (vec!["a".to_string(), "ctrl".to_string()], vec!["right".to_string()]));
+        assert_eq!(recorder.take_actions(), vec![
+            InputAction::ButtonUp { button: MouseButton::Right },
+            key_up("a"),
+            key_up("ctrl"),
+        ]);
+        // ปล่อยไปแล้วจึงไม่ปล่อยซ้ำ
+        assert_eq!(release_held_inputs(&input, &held).await, (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn released_keys_are_no_longer_held() {
+        let (recorder, input) = actor();
+        let held = HeldInputs::default();
+        key_hold(&input, "shift", 0.01, 1.0, &held).await.unwrap();
+        super::key_down(&input, "ctrl+a", &held).await.unwrap();
+        super::key_up(&input, "ctrl+a", &held).await.unwrap();
+
+        assert_eq!(recorder.take_actions(), vec![
+            key_down("shift"),
+            key_up("shift"),
+            key_down("ctrl"),
+            key_down("a"),
+            key_up("a"),
+            key_up("ctrl"),
+        ]);
+        assert_eq!(release_held_inputs(&input, &held).await, (vec![], vec![]));
+        assert_eq!(recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn drags_in_a_straight_line_with_the_button_held() {
+        let (recorder, input) = actor();
+        let held = HeldInputs::default();
+        mouse_drag(&input, Some((0, 0)), (100, 50), MouseButton::Left, 0.0, 1.0, &held).await.unwrap();
+
+        let actions = recorder.take_actions();
+        assert_eq!(actions[..2], [InputAction::MoveTo { x: 0, y: 0 }, InputAction::ButtonDown { button: MouseButton::Left }]);
+        let moves: Vec<InputAction> = (1..=10).map(|i| InputAction::MoveTo { x: i * 10, y: i * 5 }).collect();
+        assert_eq!(actions[2..12], moves[..]);
+        assert_eq!(actions[12..], [InputAction::ButtonUp { button: MouseButton::Left }]);
+        assert_eq!(release_held_inputs(&input, &held).await, (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn drags_from_the_cursor_when_no_start_is_given() {
+        let (recorder, input) = actor();
+        mouse_move(&input, 20, 30).await.unwrap();
+        recorder.clear();
+
+        mouse_drag(&input, None, (30, 30), MouseButton::Right, 0.0, 1.0, &HeldInputs::default()).await.unwrap();
+        let actions = recorder.take_actions();
+        assert_eq!(actions.first(), Some(&InputAction::ButtonDown { button: MouseButton::Right }));
+        assert_eq!(actions[1], InputAction::MoveTo { x: 21, y: 30 });
+        assert_eq!(actions.last(), Some(&InputAction::ButtonUp { button: MouseButton::Right }));
+    }
+
+    #[tokio::test]
+    async fn mouse_up_releases_a_held_button() {
+        let (recorder, input) = actor();
+        let held = HeldInputs::default();
+        mouse_down(&input, MouseButton::X1, &held).await.unwrap();
+        mouse_up(&input, MouseButton::X1, &held).await.unwrap();
+
+        assert_eq!(recorder.take_actions(), vec![
+            InputAction::ButtonDown { button: MouseButton::X1 },
+            InputAction::ButtonUp { button: MouseButton::X1 },
+        ]);
+        assert_eq!(release_held_inputs(&input, &held).await, (vec![], vec![]));
+    }
+
+    #[tokio::test]
+    async fn scrolls_repeatedly_with_the_interval_between() {
+        let (recorder, input) = actor();
+        mouse_scroll(&input, -1, 2, 3, 0.02, 1.0).await.unwrap();
+
+        let log = recorder.log();
+        assert!(log.iter().all(|entry| entry.action == InputAction::Scroll { dx: -1, dy: 2 }));
+        assert_eq!(log.len(), 3);
+        assert!(log[2].elapsed - log[0].elapsed >= Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn moves_along_the_profile_path_from_the_cursor() {
+        let (recorder, input) = actor();
+        mouse_move(&input, 100, 100).await.unwrap();
+        recorder.clear();
+
+        let profile = MovementProfile { kind: crate::models::MovementKind::Bezier, duration: 0.5, seed: Some(7) };
+        mouse_move_along(&input, 400, 250, &profile, 0.0).await.unwrap();
+        let expected: Vec<InputAction> = crate::movement::movement_path((100, 100), (400, 250), &profile)
+            .into_iter()
+            .map(|(x, y)| InputAction::MoveTo { x, y })
+            .collect();
+        assert_eq!(recorder.take_actions(), expected);
+    }
+}
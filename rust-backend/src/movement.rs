@@ -0,0 +1,191 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::models::{CoordinateJitter, JitterDistribution, MovementKind, MovementProfile};
+
+// จำนวนครั้งที่เลื่อนเมาส์ต่อวินาทีระหว่างเคลื่อน
+const MOVES_PER_SECOND: f64 = 60.0;
+// เลื่อนอย่างน้อยเท่านี้ครั้งเพื่อให้โปรแกรมปลายทางเห็นว่าเมาส์เคลื่อนผ่านจริง
+const MIN_MOVES: usize = 10;
+
+// จุดที่เมาส์จะผ่านระหว่างเคลื่อนจาก from ไป to ตามรูปแบบที่กำหนด (ไม่รวม from และจุดสุดท้ายคือ to เสมอ)
+pub fn movement_path(from: (i32, i32), to: (i32, i32), profile: &MovementProfile) -> Vec<(i32, i32)> {
+    let moves = ((profile.duration * MOVES_PER_SECOND).round() as usize).max(MIN_MOVES);
+    let start = (from.0 as f64, from.1 as f64);
+    let end = (to.0 as f64, to.1 as f64);
+
+    let points: Vec<(f64, f64)> = match profile.kind {
+        MovementKind::Linear => (1..=moves).map(|i| lerp(start, end, i as f64 / moves as f64)).collect(),
+        MovementKind::Eased => (1..=moves).map(|i| lerp(start, end, ease_in_out(i as f64 / moves as f64))).collect(),
+        MovementKind::Bezier => {
+            let mut rng = match profile.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            bezier_path(start, end, moves, &mut rng)
+        },
+    };
+
+    let mut path: Vec<(i32, i32)> = Vec::with_capacity(points.len() + 1);
+    for (x, y) in points {
+        let point = (x.round() as i32, y.round() as i32);
+        // ข้ามจุดที่ซ้ำกับจุดก่อนหน้า (รวมจุดแรกที่ปัดแล้วยังอยู่ที่ from)
+        if *path.last().unwrap_or(&from) != point {
+            path.push(point);
+        }
+    }
+    if path.last() != Some(&to) {
+        path.push(to);
+    }
+    path
+}
+
+// สุ่มตำแหน่งรอบ point ภายในรัศมีของ jitter
+pub fn jitter_point(point: (i32, i32), jitter: &CoordinateJitter) -> (i32, i32) {
+    if !jitter.is_enabled() {
+        return point;
+    }
+    let mut rng = rand::thread_rng();
+    let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+    let distance = match jitter.distribution {
+        // sqrt ทำให้จุดกระจายเท่ากันทั่วพื้นที่วงกลม (ไม่กระจุกที่กลาง)
+        JitterDistribution::Uniform => jitter.radius * rng.gen::<f64>().sqrt(),
+        // ระยะจากจุดกลางของการแจกแจงปกติสองมิติ (Rayleigh) ตัดไม่ให้เกินรัศมี
+        JitterDistribution::Gaussian => {
+            let sigma = jitter.radius / 2.0;
+            (sigma * (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt()).min(jitter.radius)
+        },
+    };
+    (
+        point.0 + (distance * angle.cos()).round() as i32,
+        point.1 + (distance * angle.sin()).round() as i32,
+    )
+}
+
+fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+// เร่งช่วงแรกและชะลอช่วงท้าย (cubic ease-in-out)
+fn ease_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+fn cubic_bezier(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), p3: (f64, f64), t: f64) -> (f64, f64) {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+    )
+}
+
+// โค้งไปยังจุดที่เลยเป้าหมายเล็กน้อย แล้วค่อยๆ กลับมาที่เป้าหมาย
+fn bezier_path(start: (f64, f64), end: (f64, f64), moves: usize, rng: &mut StdRng) -> Vec<(f64, f64)> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let distance = dx.hypot(dy);
+    if distance < 1.0 {
+        return vec![end];
+    }
+
+    // ทิศทางไปยังเป้าหมายและทิศตั้งฉาก
+    let (ux, uy) = (dx / distance, dy / distance);
+    let (nx, ny) = (-uy, ux);
+
+    let overshoot = (distance * rng.gen_range(0.03..0.08)).clamp(2.0, 40.0);
+    let drift = overshoot * rng.gen_range(-0.5..0.5);
+    let overshoot_point = (end.0 + ux * overshoot + nx * drift, end.1 + uy * overshoot + ny * drift);
+
+    // จุดควบคุมเบี่ยงออกจากเส้นตรงแบบสุ่มเพื่อให้เส้นทางโค้ง
+    let bend = distance * 0.25;
+    let (bend1, bend2) = (rng.gen_range(-bend..bend), rng.gen_range(-bend..bend));
+    let control1 = (start.0 + dx / 3.0 + nx * bend1, start.1 + dy / 3.0 + ny * bend1);
+    let control2 = (start.0 + dx * 2.0 / 3.0 + nx * bend2, start.1 + dy * 2.0 / 3.0 + ny * bend2);
+
+    let settle = (moves / 6).max(2);
+    let curve = moves - settle;
+    let mut points: Vec<(f64, f64)> = (1..=curve)
+        .map(|i| cubic_bezier(start, control1, control2, overshoot_point, ease_in_out(i as f64 / curve as f64)))
+        .collect();
+    points.extend((1..=settle).map(|i| lerp(overshoot_point, end, ease_in_out(i as f64 / settle as f64))));
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(kind: MovementKind, duration: f64, seed: Option<u64>) -> MovementProfile {
+        MovementProfile { kind, duration, seed }
+    }
+
+    fn step_length(a: (i32, i32), b: (i32, i32)) -> f64 {
+        ((b.0 - a.0) as f64).hypot((b.1 - a.1) as f64)
+    }
+
+    #[test]
+    fn linear_paths_move_in_even_steps_to_the_target() {
+        let path = movement_path((0, 0), (600, 300), &profile(MovementKind::Linear, 0.5, None));
+        assert_eq!(path.len(), 30);
+        assert_eq!(path[0], (20, 10));
+        assert_eq!(path.last(), Some(&(600, 300)));
+    }
+
+    #[test]
+    fn short_movements_still_pass_through_several_points() {
+        let path = movement_path((0, 0), (100, 0), &profile(MovementKind::Linear, 0.0, None));
+        assert_eq!(path.len(), MIN_MOVES);
+        // ระยะสั้นกว่าจำนวนจุดจะไม่มีจุดซ้ำกัน
+        let path = movement_path((0, 0), (3, 0), &profile(MovementKind::Linear, 0.0, None));
+        assert_eq!(path, vec![(1, 0), (2, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn eased_paths_start_and_end_slowly() {
+        let path = movement_path((0, 0), (1000, 0), &profile(MovementKind::Eased, 0.5, None));
+        let middle = path.len() / 2;
+        assert!(step_length((0, 0), path[0]) < step_length(path[middle - 1], path[middle]));
+        assert!(step_length(path[path.len() - 2], path[path.len() - 1]) < step_length(path[middle - 1], path[middle]));
+        assert_eq!(path.last(), Some(&(1000, 0)));
+    }
+
+    #[test]
+    fn bezier_paths_repeat_for_the_same_seed() {
+        let seeded = profile(MovementKind::Bezier, 0.5, Some(42));
+        let path = movement_path((10, 10), (800, 500), &seeded);
+        assert_eq!(path, movement_path((10, 10), (800, 500), &seeded));
+        assert_ne!(path, movement_path((10, 10), (800, 500), &profile(MovementKind::Bezier, 0.5, Some(43))));
+        assert_eq!(path.last(), Some(&(800, 500)));
+        // เส้นโค้งไม่ใช่เส้นตรง
+        assert_ne!(path, movement_path((10, 10), (800, 500), &profile(MovementKind::Linear, 0.5, None)));
+    }
+
+    #[test]
+    fn movements_to_the_current_position_stay_put() {
+        for kind in [MovementKind::Linear, MovementKind::Eased, MovementKind::Bezier] {
+            assert_eq!(movement_path((5, 5), (5, 5), &profile(kind, 0.5, Some(1))), vec![(5, 5)]);
+        }
+    }
+
+    #[test]
+    fn jitter_stays_within_the_radius() {
+        for distribution in [JitterDistribution::Uniform, JitterDistribution::Gaussian] {
+            let jitter = CoordinateJitter { radius: 8.0, distribution };
+            let points: Vec<(i32, i32)> = (0..2000).map(|_| jitter_point((500, 400), &jitter)).collect();
+            // ปัดเป็น pixel แล้วอาจเกินรัศมีได้ไม่เกินครึ่ง pixel ในแต่ละแกน
+            let limit = 8.0 + std::f64::consts::FRAC_1_SQRT_2;
+            assert!(points.iter().all(|&point| step_length((500, 400), point) <= limit), "{:?}", distribution);
+            assert!(points.iter().any(|&point| point != points[0]), "{:?}", distribution);
+        }
+    }
+
+    #[test]
+    fn disabled_jitter_keeps_the_point() {
+        let jitter = CoordinateJitter { radius: 0.0, distribution: JitterDistribution::Uniform };
+        assert_eq!(jitter_point((12, 34), &jitter), (12, 34));
+    }
+}
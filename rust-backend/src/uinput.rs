@@ -0,0 +1,222 @@
+use std::sync::Mutex;
+
+use crate::evdev::{self, EventSink, InputEvent, ABS_X, ABS_Y, EV_ABS, EV_KEY, EV_REL, REL_HWHEEL, REL_WHEEL};
+use crate::input_backend::{InputBackend, InputResult};
+use crate::key_chord::Key;
+use crate::models::MouseButton;
+
+// ชื่ออุปกรณ์ที่สร้างผ่าน /dev/uinput (EvdevDevices ใช้ชื่อนี้เพื่อไม่บันทึก input ของตัวเอง)
+pub const UINPUT_DEVICE_NAME: &str = "mouse-keyboard-automation";
+
+// backend ที่ส่ง input เป็นเหตุการณ์ evdev ผ่านอุปกรณ์เสมือน ใช้ได้ทั้ง X11 และ Wayland
+// เมาส์เป็นแบบพิกัดสัมบูรณ์ในช่วงขนาดหน้าจอ ส่วนคีย์ส่งเป็นรหัสคีย์ตาม layout US
+// (compositor จะแปลงรหัสตาม layout ที่ผู้ใช้ตั้งไว้ อักขระที่ไม่มีบนคีย์บอร์ด US จะวางผ่านคลิปบอร์ดแทน)
+pub struct UinputBackend {
+    sink: Box<dyn EventSink>,
+    screen: (i32, i32),
+    // uinput ถามตำแหน่ง cursor ไม่ได้ จึงจำตำแหน่งล่าสุดที่ move_to ไว้
+    position: Mutex<(i32, i32)>,
+}
+
+impl UinputBackend {
+    // สร้างอุปกรณ์เสมือนผ่าน /dev/uinput (ต้องมีสิทธิ์เขียน เช่น กฎ udev ให้กลุ่ม input)
+    #[cfg(target_os = "linux")]
+    pub fn open() -> std::io::Result<Self> {
+        let screen = evdev::screen_size_from_env();
+        Ok(Self::with_sink(UinputDevice::create(screen)?, screen))
+    }
+
+    // ส่งเหตุการณ์ไปยัง sink ที่กำหนด (เช่น FakeEvdev สำหรับทดสอบ)
+    pub fn with_sink(sink: impl EventSink + 'static, screen: (i32, i32)) -> Self {
+        Self {
+            sink: Box::new(sink),
+            screen,
+            position: Mutex::new((screen.0 / 2, screen.1 / 2)),
+        }
+    }
+
+    // ส่งเหตุการณ์พร้อมปิดกลุ่มด้วย SYN_REPORT
+    fn send(&self, events: &[InputEvent]) -> InputResult {
+        let mut events = events.to_vec();
+        events.push(InputEvent::sync());
+        self.sink.emit(&events)?;
+        Ok(())
+    }
+
+    fn key(&self, name: &str, value: i32) -> InputResult {
+        let key = Key::from_name(name).ok_or_else(|| format!("Unsupported key: {}", name))?;
+        let (code, shift) = evdev::key_code(key).ok_or_else(|| format!("Key `{}` is not supported by uinput", key))?;
+        self.send(&evdev::key_events(code, shift, value))
+    }
+
+    fn button(&self, button: MouseButton, value: i32) -> InputResult {
+        let code = match button {
+            MouseButton::Left => evdev::BTN_LEFT,
+            MouseButton::Right => evdev::BTN_RIGHT,
+            MouseButton::Middle => evdev::BTN_MIDDLE,
+            MouseButton::X1 => evdev::BTN_SIDE,
+            MouseButton::X2 => evdev::BTN_EXTRA,
+        };
+        self.send(&[InputEvent::new(EV_KEY, code, value)])
+    }
+}
+
+impl InputBackend for UinputBackend {
+    fn name(&self) -> &'static str {
+        "uinput"
+    }
+
+    fn move_to(&self, x: i32, y: i32) -> InputResult {
+        let (x, y) = (x.clamp(0, self.screen.0 - 1), y.clamp(0, self.screen.1 - 1));
+        self.send(&[InputEvent::new(EV_ABS, ABS_X, x), InputEvent::new(EV_ABS, ABS_Y, y)])?;
+        *self.position.lock().unwrap() = (x, y);
+        Ok(())
+    }
+
+    fn cursor_position(&self) -> Result<(i32, i32), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(*self.position.lock().unwrap())
+    }
+
+    fn button_down(&self, button: MouseButton) -> InputResult {
+        self.button(button, 1)
+    }
+
+    fn button_up(&self, button: MouseButton) -> InputResult {
+        self.button(button, 0)
+    }
+
+    fn key_down(&self, key: &str) -> InputResult {
+        self.key(key, 1)
+    }
+
+    fn key_up(&self, key: &str) -> InputResult {
+        self.key(key, 0)
+    }
+
+    fn type_text(&self, text: &str) -> InputResult {
+        for c in text.chars() {
+            let (code, shift) = evdev::key_code(Key::Char(c))
+                .ok_or_else(|| format!("Character {:?} cannot be typed through uinput", c))?;
+            self.send(&evdev::key_events(code, shift, 1))?;
+            self.send(&evdev::key_events(code, shift, 0))?;
+        }
+        Ok(())
+    }
+
+    fn can_type(&self, c: char) -> bool {
+        evdev::key_code(Key::Char(c)).is_some()
+    }
+
+    fn scroll(&self, dx: i32, dy: i32) -> InputResult {
+        let mut events = Vec::new();
+        if dx != 0 {
+            events.push(InputEvent::new(EV_REL, REL_HWHEEL, dx));
+        }
+        // evdev ใช้ค่าบวกสำหรับเลื่อนขึ้น
+        if dy != 0 {
+            events.push(InputEvent::new(EV_REL, REL_WHEEL, -dy));
+        }
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.send(&events)
+    }
+}
+
+// อุปกรณ์เสมือนที่สร้างผ่าน /dev/uinput และถูกลบเมื่อ drop
+#[cfg(target_os = "linux")]
+pub struct UinputDevice {
+    file: std::fs::File,
+}
+
+#[cfg(target_os = "linux")]
+impl UinputDevice {
+    pub fn create(screen: (i32, i32)) -> std::io::Result<Self> {
+        use evdev::ioc;
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::os::unix::io::AsRawFd;
+
+        const UI_DEV_CREATE: u32 = ioc(0, b'U', 1, 0);
+        const UI_DEV_SETUP: u32 = ioc(1, b'U', 3, std::mem::size_of::<libc::uinput_setup>());
+        const UI_ABS_SETUP: u32 = ioc(1, b'U', 4, std::mem::size_of::<libc::uinput_abs_setup>());
+        const UI_SET_EVBIT: u32 = ioc(1, b'U', 100, std::mem::size_of::<libc::c_int>());
+        const UI_SET_KEYBIT: u32 = ioc(1, b'U', 101, std::mem::size_of::<libc::c_int>());
+        const UI_SET_RELBIT: u32 = ioc(1, b'U', 102, std::mem::size_of::<libc::c_int>());
+        const UI_SET_ABSBIT: u32 = ioc(1, b'U', 103, std::mem::size_of::<libc::c_int>());
+        const BUS_VIRTUAL: u16 = 0x06;
+
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")
+            .map_err(|e| std::io::Error::new(e.kind(), format!("cannot open /dev/uinput: {}", e)))?;
+        let fd = file.as_raw_fd();
+        let check = |result: libc::c_int| if result < 0 { Err(std::io::Error::last_os_error()) } else { Ok(()) };
+
+        unsafe {
+            for kind in [evdev::EV_SYN, EV_KEY, EV_REL, EV_ABS] {
+                check(libc::ioctl(fd, UI_SET_EVBIT as _, libc::c_int::from(kind)))?;
+            }
+            // คีย์บอร์ดทุกปุ่มตามรหัสของ Linux และปุ่มเมาส์ที่รองรับ
+            for code in (1..=248).chain(evdev::BTN_LEFT..=evdev::BTN_EXTRA) {
+                check(libc::ioctl(fd, UI_SET_KEYBIT as _, libc::c_int::from(code)))?;
+            }
+            for code in [REL_WHEEL, REL_HWHEEL] {
+                check(libc::ioctl(fd, UI_SET_RELBIT as _, libc::c_int::from(code)))?;
+            }
+            for (code, size) in [(ABS_X, screen.0), (ABS_Y, screen.1)] {
+                check(libc::ioctl(fd, UI_SET_ABSBIT as _, libc::c_int::from(code)))?;
+                let mut abs: libc::uinput_abs_setup = std::mem::zeroed();
+                abs.code = code;
+                abs.absinfo.maximum = size - 1;
+                check(libc::ioctl(fd, UI_ABS_SETUP as _, &abs))?;
+            }
+
+            let mut setup: libc::uinput_setup = std::mem::zeroed();
+            setup.id.bustype = BUS_VIRTUAL;
+            setup.id.vendor = 0x1209;
+            setup.id.product = 0x0001;
+            for (dst, src) in setup.name.iter_mut().zip(UINPUT_DEVICE_NAME.bytes()) {
+                *dst = src as libc::c_char;
+            }
+            check(libc::ioctl(fd, UI_DEV_SETUP as _, &setup))?;
+            check(libc::ioctl(fd, UI_DEV_CREATE as _))?;
+        }
+
+        // รอให้ compositor เห็นอุปกรณ์ใหม่ก่อน ไม่เช่นนั้นเหตุการณ์แรกๆ จะหายไป
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        println!("Created uinput device {} ({}x{})", UINPUT_DEVICE_NAME, screen.0, screen.1);
+        Ok(Self { file })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl EventSink for UinputDevice {
+    fn emit(&self, events: &[InputEvent]) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let size = std::mem::size_of::<libc::input_event>();
+        let mut buffer = Vec::with_capacity(size * events.len());
+        for event in events {
+            let mut raw: libc::input_event = unsafe { std::mem::zeroed() };
+            raw.type_ = event.kind;
+            raw.code = event.code;
+            raw.value = event.value;
+            let bytes = unsafe { std::slice::from_raw_parts(&raw as *const libc::input_event as *const u8, size) };
+            buffer.extend_from_slice(bytes);
+        }
+        (&self.file).write_all(&buffer)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for UinputDevice {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+
+        const UI_DEV_DESTROY: u32 = evdev::ioc(0, b'U', 2, 0);
+        unsafe {
+            libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY as _);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{MacroStep, Step, StepError};
+
+// ปัญหาที่พบในขั้นตอน ใช้ส่งให้ UI ไฮไลต์ขั้นตอนที่ไม่ถูกต้อง
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepDiagnostic {
+    // ตำแหน่งของขั้นตอนในรายการ (เริ่มที่ 0)
+    pub index: usize,
+    pub id: Option<String>,
+    // ชื่อฟิลด์ที่มีปัญหา ขั้นตอนใน group จะมี prefix เช่น groupSteps[2].key
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+impl StepDiagnostic {
+    pub(crate) fn new(index: usize, id: Option<String>, error: StepError) -> Self {
+        Self {
+            index,
+            id,
+            field: error.field,
+            reason: error.reason,
+        }
+    }
+}
+
+// แปลง steps ในรูปแบบ JSON และตรวจสอบทุกขั้นตอน
+// ถ้ามีปัญหาจะคืนรายการปัญหาทั้งหมด ไม่ใช่แค่ปัญหาแรก
+pub fn parse_steps(values: &[Value]) -> Result<Vec<MacroStep>, Vec<StepDiagnostic>> {
+    parse_steps_at(values, 0)
+}
+
+// เหมือน parse_steps แต่เริ่มนับ index จากตำแหน่งที่กำหนด (ใช้ตอนเพิ่มขั้นตอนต่อท้าย)
+pub fn parse_steps_at(values: &[Value], first_index: usize) -> Result<Vec<MacroStep>, Vec<StepDiagnostic>> {
+    let mut steps = Vec::with_capacity(values.len());
+    let mut problems = Vec::new();
+
+    for (offset, value) in values.iter().enumerate() {
+        let index = first_index + offset;
+        let id = value.get("id").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+        let step = match MacroStep::deserialize_value(value) {
+            Ok(step) => step,
+            Err(err) => {
+                problems.push(StepDiagnostic::new(index, id, err));
+                continue;
+            }
+        };
+
+        problems.extend(
+            check_step(&step)
+                .into_iter()
+                .map(|err| StepDiagnostic::new(index, id.clone(), err)),
+        );
+        steps.push(step);
+    }
+
+    if problems.is_empty() {
+        Ok(steps)
+    } else {
+        Err(problems)
+    }
+}
+
+// ตรวจสอบขั้นตอนที่แปลงเป็นชนิดข้อมูลแล้ว (เช่น steps ที่เก็บไว้ใน controller)
+pub fn validate_steps(steps: &[MacroStep]) -> Vec<StepDiagnostic> {
+    steps
+        .iter()
+        .enumerate()
+        .flat_map(|(index, step)| {
+            check_step(step)
+                .into_iter()
+                .map(move |err| StepDiagnostic::new(index, Some(step.id.clone()), err))
+        })
+        .collect()
+}
+
+// สรุปปัญหาเป็นข้อความสั้นๆ สำหรับแสดงใน status
+pub fn summarize(problems: &[StepDiagnostic]) -> String {
+    match problems.first() {
+        None => "All steps are valid".to_string(),
+        Some(first) => {
+            let location = match &first.field {
+                Some(field) => format!("step #{} ({})", first.index + 1, field),
+                None => format!("step #{}", first.index + 1),
+            };
+            if problems.len() == 1 {
+                format!("Invalid {}: {}", location, first.reason)
+            } else {
+                format!("{} problems found, first at {}: {}", problems.len(), location, first.reason)
+            }
+        }
+    }
+}
+
+// ตรวจสอบเงื่อนไขที่ไม่สามารถตรวจได้ตอนแปลงข้อมูล
+fn check_step(step: &MacroStep) -> Vec<StepError> {
+    let mut problems = Vec::new();
+
+    match &step.action {
+        Step::KeyPress(press) | Step::KeyDown(press) | Step::KeyUp(press) => {
+            if let Err(reason) = crate::mouse_keyboard::validate_key(&press.key) {
+                problems.push(StepError::new("key", reason));
+            }
+        },
+        Step::KeyHold(hold) => {
+            if let Err(reason) = crate::mouse_keyboard::validate_key(&hold.key) {
+                problems.push(StepError::new("key", reason));
+            }
+        },
+        Step::Group(group) => {
+            if group.loop_count == 0 {
+                problems.push(StepError::new("groupLoopCount", "must be at least 1"));
+            }
+            for (i, inner) in group.steps.iter().enumerate() {
+                let prefix = format!("groupSteps[{}]", i);
+                problems.extend(check_step(inner).into_iter().map(|err| err.nested(&prefix)));
+            }
+        },
+        Step::MouseMove(_)
+        | Step::MouseClick(_)
+        | Step::MouseDoubleClick(_)
+        | Step::MouseDown(_)
+        | Step::MouseUp(_)
+        | Step::Drag(_)
+        | Step::TypeText(_)
+        | Step::Scroll(_)
+        | Step::Wait => {},
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key_reason(key: &str) -> String {
+        crate::mouse_keyboard::validate_key(key).expect_err("key should be invalid")
+    }
+
+    #[test]
+    fn reports_unknown_keys_with_step_location() {
+        let steps = vec![
+            json!({ "id": "a", "type": "key_press", "data": { "key": "ctrl+c" } }),
+            json!({ "id": "b", "type": "key_press", "data": { "key": "ctrl+nosuchkey" } }),
+        ];
+        assert_eq!(parse_steps(&steps).unwrap_err(), vec![StepDiagnostic {
+            index: 1,
+            id: Some("b".to_string()),
+            field: Some("key".to_string()),
+            reason: key_reason("ctrl+nosuchkey"),
+        }]);
+    }
+
+    #[test]
+    fn reports_nested_group_problems() {
+        let group = json!({ "id": "g", "type": "group", "data": {
+            "isGroup": true,
+            "groupLoopCount": 0,
+            "groupSteps": [
+                { "id": "g-1", "type": "wait", "data": {} },
+                { "id": "g-2", "type": "key_hold", "data": { "key": "nosuchkey", "duration": 1.0 } },
+            ],
+        } });
+        assert_eq!(parse_steps_at(&[group], 4).unwrap_err(), vec![
+            StepDiagnostic {
+                index: 4,
+                id: Some("g".to_string()),
+                field: Some("groupLoopCount".to_string()),
+                reason: "must be at least 1".to_string(),
+            },
+            StepDiagnostic {
+                index: 4,
+                id: Some("g".to_string()),
+                field: Some("groupSteps[1].key".to_string()),
+                reason: key_reason("nosuchkey"),
+            },
+        ]);
+    }
+
+    #[test]
+    fn collects_parse_and_check_problems_together() {
+        let steps = vec![
+            json!({ "id": "a", "type": "mouse_move", "data": { "y": 1 } }),
+            json!({ "id": "b", "type": "key_up", "data": { "key": "nosuchkey" } }),
+        ];
+        let problems = parse_steps(&steps).unwrap_err();
+        assert_eq!(problems.len(), 2);
+        assert_eq!((problems[0].index, problems[0].field.as_deref()), (0, Some("x")));
+        assert_eq!((problems[1].index, problems[1].field.as_deref()), (1, Some("key")));
+        assert_eq!(summarize(&problems), "2 problems found, first at step #1 (x): is required");
+    }
+}
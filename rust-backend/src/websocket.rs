@@ -7,10 +7,18 @@ use uuid::Uuid;
 use serde_json::{json, Value};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use std::error::Error as StdError;
-use tokio::time::Duration;
-use device_query::{DeviceState, DeviceQuery};
+use base64::Engine;
 
 use crate::automation::AutomationController;
+use crate::clipboard::{ClipboardContent, ClipboardFormat};
+use crate::executor::{self, DebugCommand, RunRequest, Simulation, StepRange};
+use crate::display::{DisplayLayout, Monitor};
+use crate::input_actor::TimingProfile;
+use crate::key_chord::Key;
+use crate::models::{
+    CoordinateJitter, CoordinateSpace, ErrorPolicy, MacroStep, MonitorAnchor, MonitorFallback, RandomTimingConfig, Rect,
+};
+use crate::validation::{self, StepDiagnostic};
 // use crate::models::WebSocketMessage;
 
 // จัดการการเชื่อมต่อ WebSocket และเพิ่มผู้ใช้ใหม่ไปยัง AutomationController
@@ -101,20 +109,22 @@ async fn handle_websocket_message(
                     println!("Performing copy (Ctrl+C)");
                     
                     // อ่านข้อความที่เลือกไว้ก่อน
+                    let input = controller.input.clone();
+                    let clipboard = controller.clipboard.clone();
                     drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
                     
                     // 1. เลือกข้อความด้วย Ctrl+A
-                    crate::mouse_keyboard::perform_select_all().await;
+                    crate::mouse_keyboard::perform_select_all(&input).await;
                     // รอสักครู่
                     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
                     
                     // 2. คัดลอกด้วย Ctrl+C
-                    crate::mouse_keyboard::perform_copy().await;
+                    crate::mouse_keyboard::perform_copy(&input).await;
                     // รอสักครู่
                     tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
                     
                     // 3. อ่านข้อความจากคลิปบอร์ด
-                    let clipboard_text = match crate::mouse_keyboard::get_clipboard_text().await {
+                    let clipboard_text = match crate::mouse_keyboard::get_clipboard_text(&clipboard).await {
                         Ok(text) => {
                             println!("Clipboard text: {}", text);
                             text
@@ -145,11 +155,13 @@ async fn handle_websocket_message(
                     // ตรวจสอบว่ามีข้อความที่ต้องการวางหรือไม่
                     let custom_text = json_data.get("text").and_then(|v| v.as_str()).map(|s| s.to_string());
                     
+                    let input = controller.input.clone();
+                    let clipboard = controller.clipboard.clone();
                     drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
                     
                     // ถ้ามีข้อความที่กำหนด ให้ตั้งค่าคลิปบอร์ดใหม่
                     if let Some(text) = custom_text {
-                        match crate::mouse_keyboard::set_clipboard_text(&text).await {
+                        match crate::mouse_keyboard::set_clipboard_text(&clipboard, &text).await {
                             Ok(_) => println!("Set clipboard text: {}", text),
                             Err(err) => println!("Failed to set clipboard text: {}", err)
                         }
@@ -159,7 +171,7 @@ async fn handle_websocket_message(
                     }
                     
                     // วางด้วย Ctrl+V
-                    crate::mouse_keyboard::perform_paste().await;
+                    crate::mouse_keyboard::perform_paste(&input).await;
                     
                     // ส่งข้อความยืนยันกลับไปยังไคลเอนต์
                     let response = create_message("action_completed", json!({
@@ -175,25 +187,43 @@ async fn handle_websocket_message(
                     return Ok(());
                 },
                 "get_clipboard" => {
-                    println!("Getting clipboard text");
-                    drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
-                    
-                    // อ่านข้อความจากคลิปบอร์ด
-                    let clipboard_text = match crate::mouse_keyboard::get_clipboard_text().await {
-                        Ok(text) => {
-                            println!("Clipboard text: {}", text);
-                            text
-                        },
+                    // format: text (ค่าเริ่มต้น), html หรือ image
+                    let format = match json_data.get("format").and_then(|v| v.as_str()).unwrap_or("text").parse::<ClipboardFormat>() {
+                        Ok(format) => format,
                         Err(err) => {
-                            println!("Failed to get clipboard text: {}", err);
-                            String::new()
+                            send_error(&controller.clients, client_id, err);
+                            return Ok(());
                         }
                     };
+                    println!("Getting clipboard {}", format);
+                    let clipboard = controller.clipboard.clone();
+                    drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
                     
-                    // ส่งข้อความกลับไปยังไคลเอนต์
-                    let response = create_message("clipboard_text", json!({
-                        "text": clipboard_text
-                    }));
+                    let response = if format == ClipboardFormat::Text {
+                        // อ่านข้อความจากคลิปบอร์ด
+                        let clipboard_text = match crate::mouse_keyboard::get_clipboard_text(&clipboard).await {
+                            Ok(text) => {
+                                println!("Clipboard text: {}", text);
+                                text
+                            },
+                            Err(err) => {
+                                println!("Failed to get clipboard text: {}", err);
+                                String::new()
+                            }
+                        };
+                        create_message("clipboard_text", json!({
+                            "text": clipboard_text
+                        }))
+                    } else {
+                        let content = match crate::mouse_keyboard::get_clipboard(&clipboard, format).await {
+                            Ok(content) => content,
+                            Err(err) => {
+                                println!("Failed to get clipboard {}: {}", format, err);
+                                None
+                            }
+                        };
+                        create_message("clipboard_content", clipboard_content_json(format, content.as_ref()))
+                    };
                     
                     // ล็อคใหม่เพื่อส่งข้อความกลับ
                     let controller = automation_controller.lock().await;
@@ -203,18 +233,29 @@ async fn handle_websocket_message(
                     return Ok(());
                 },
                 "set_clipboard" => {
-                    if let Some(text) = json_data.get("text").and_then(|v| v.as_str()) {
-                        println!("Setting clipboard text: {}", text);
+                    let content = match parse_clipboard_content(&json_data) {
+                        Ok(content) => content,
+                        Err(err) => {
+                            send_error(&controller.clients, client_id, err);
+                            return Ok(());
+                        }
+                    };
+                    if let Some(content) = content {
+                        match &content {
+                            ClipboardContent::Text(text) => println!("Setting clipboard text: {}", text),
+                            other => println!("Setting clipboard {}", other.format()),
+                        }
+                        let clipboard = controller.clipboard.clone();
                         drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
                         
-                        // ตั้งค่าข้อความในคลิปบอร์ด
-                        let status = match crate::mouse_keyboard::set_clipboard_text(text).await {
+                        // ตั้งค่าข้อมูลในคลิปบอร์ด
+                        let status = match crate::mouse_keyboard::set_clipboard(&clipboard, content).await {
                             Ok(_) => {
-                                println!("Set clipboard text successfully");
+                                println!("Set clipboard successfully");
                                 "success"
                             },
                             Err(err) => {
-                                println!("Failed to set clipboard text: {}", err);
+                                println!("Failed to set clipboard: {}", err);
                                 "error"
                             }
                         };
@@ -258,8 +299,9 @@ async fn handle_websocket_message(
                         return Ok(());
                     }
                     
+                    let input = controller.input.clone();
                     drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
-                    crate::mouse_keyboard::perform_select_all().await;
+                    crate::mouse_keyboard::perform_select_all(&input).await;
                     
                     // ส่งข้อความยืนยันกลับไปยังไคลเอนต์
                     let response = create_message("action_completed", json!({
@@ -278,10 +320,19 @@ async fn handle_websocket_message(
                     // รับข้อมูลคีย์ที่จะกด
                     if let Some(key) = json_data.get("key").and_then(|v| v.as_str()) {
                         println!("Manually pressing key: {}", key);
+                        let input = controller.input.clone();
                         drop(controller); // ปล่อย lock ก่อนเรียกฟังก์ชันที่อาจใช้เวลานาน
                         
                         // เรียกใช้ฟังก์ชันกดคีย์บอร์ด
-                        let _ = crate::mouse_keyboard::keyboard_press_key(key).await;
+                        let result = crate::mouse_keyboard::keyboard_press_key(&input, key).await;
+                        
+                        // ล็อคใหม่เพื่อส่งข้อความกลับ
+                        let controller = automation_controller.lock().await;
+                        if let Err(err) = result {
+                            println!("Failed to press key {}: {}", key, err);
+                            send_error(&controller.clients, client_id, format!("Failed to press key {}: {}", key, err));
+                            return Ok(());
+                        }
                         
                         // ส่งข้อความยืนยันกลับไปยังไคลเอนต์
                         let response = create_message("action_completed", json!({
@@ -289,9 +340,6 @@ async fn handle_websocket_message(
                             "key": key,
                             "status": "success"
                         }));
-                        
-                        // ล็อคใหม่เพื่อส่งข้อความกลับ
-                        let controller = automation_controller.lock().await;
                         if let Some(client_sender) = controller.clients.get(client_id) {
                             let _ = client_sender.send(response);
                         }
@@ -332,7 +380,9 @@ async fn handle_websocket_message(
                 let config_msg = create_message("random_timing_updated", json!({
                     "enabled": controller.random_enabled,
                     "min_factor": controller.random_min,
-                    "max_factor": controller.random_max
+                    "max_factor": controller.random_max,
+                    "jitter_radius": controller.jitter.radius,
+                    "jitter_distribution": controller.jitter.distribution
                 }));
                 if let Some(client) = controller.clients.get(client_id) {
                     let _ = client.send(config_msg);
@@ -351,16 +401,24 @@ async fn handle_websocket_message(
             "add_step" => {
                 // เพิ่มขั้นตอนใหม่
                 if let Some(step_data) = json_data.get("data") {
-                    // Try to get step_type first, fallback to type, then use "unknown" as last resort
+                    // Try to get step_type first, fallback to type
                     let step_type = step_data.get("step_type")
                         .and_then(|v| v.as_str())
                         .or_else(|| step_data.get("type").and_then(|v| v.as_str()))
-                        .unwrap_or("unknown");
+                        .unwrap_or_default();
                     
-                    let new_step = crate::models::MacroStep {
-                        id: Uuid::new_v4().to_string(),
-                        type_: step_type.to_string(),
-                        data: step_data.clone(),
+                    let step_json = json!({
+                        "id": Uuid::new_v4().to_string(),
+                        "type": step_type,
+                        "data": step_data
+                    });
+                    let new_step = match validation::parse_steps_at(&[step_json], controller.steps.len()) {
+                        Ok(mut steps) => steps.remove(0),
+                        Err(problems) => {
+                            println!("Rejected invalid step: {}", validation::summarize(&problems));
+                            reject_steps(&controller.clients, client_id, &problems);
+                            return Ok(());
+                        }
                     };
                     
                     controller.steps.push(new_step);
@@ -374,88 +432,132 @@ async fn handle_websocket_message(
             "run_automation" => {
                 // เริ่มการทำงานอัตโนมัติ
                 if let Some(data) = json_data.get("data") {
-                    let loop_count = data.get("loop_count").and_then(|v| v.as_i64()).unwrap_or(1) as i32;
-                    
-                    controller.is_running = true;
-                    
-                    // แจ้งการอัปเดต
-                    let status_msg = create_message("status_update", json!({
-                        "status": "running",
-                        "message": format!("Running automation with {} loops", loop_count)
-                    }));
-                    broadcast_to_clients(&controller.clients, status_msg);
-                    println!("Started automation with {} loops", loop_count);
+                    let loop_count = match parse_loop_count(data) {
+                        Ok(loop_count) => loop_count,
+                        Err(reason) => {
+                            println!("Rejected run request: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid loop_count: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    let debug = data.get("debug").and_then(|v| v.as_bool()).unwrap_or(false);
+                    let on_error = match parse_error_policy(data) {
+                        Ok(policy) => policy,
+                        Err(reason) => {
+                            println!("Rejected run request: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid on_error: {}", reason));
+                            return Ok(());
+                        }
+                    };
                     
                     // ตรวจสอบว่ามีการส่ง steps มาหรือไม่
                     let steps_to_run = if let Some(steps_array) = data.get("steps").and_then(|v| v.as_array()) {
                         // ถ้ามีการส่ง steps มา ให้ใช้ steps ที่ส่งมาแทน
                         println!("Using steps sent from frontend: {} steps", steps_array.len());
                         
-                        let mut parsed_steps = Vec::new();
-                        for step_value in steps_array {
-                            if let (Some(id), Some(type_), Some(data)) = (
-                                step_value.get("id").and_then(|v| v.as_str()),
-                                step_value.get("type").and_then(|v| v.as_str()),
-                                step_value.get("data")
-                            ) {
-                                let step = crate::models::MacroStep {
-                                    id: id.to_string(),
-                                    type_: type_.to_string(),
-                                    data: data.clone(),
-                                };
-                                parsed_steps.push(step);
+                        match validation::parse_steps(steps_array) {
+                            Ok(steps) => steps,
+                            Err(problems) => {
+                                println!("Rejected automation run: {}", validation::summarize(&problems));
+                                reject_steps(&controller.clients, client_id, &problems);
+                                return Ok(());
                             }
                         }
-                        parsed_steps
                     } else {
                         // ถ้าไม่มี ให้ใช้ steps จาก controller ตามเดิม (เผื่อ backward compatibility)
                         println!("No steps sent from frontend, using stored steps: {} steps", controller.steps.len());
+                        let problems = validation::validate_steps(&controller.steps);
+                        if !problems.is_empty() {
+                            println!("Rejected automation run: {}", validation::summarize(&problems));
+                            reject_steps(&controller.clients, client_id, &problems);
+                            return Ok(());
+                        }
                         controller.steps.clone()
                     };
                     
+                    // จำลองการทำงานโดยไม่ส่ง input จริง
+                    let simulate = match parse_simulation(data) {
+                        Ok(simulate) => simulate,
+                        Err(reason) => {
+                            println!("Rejected automation run: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid simulation options: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    
+                    // ช่วงของขั้นตอนและรอบที่จะทำงาน (ถ้าไม่ระบุจะทำทั้งหมด)
+                    let range = match parse_step_range(data, steps_to_run.len(), loop_count) {
+                        Ok(range) => range,
+                        Err(reason) => {
+                            println!("Rejected automation run: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid run range: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    
                     // เรียกใช้ฟังก์ชันทำงานตามขั้นตอน
-                    let controller_clone = automation_controller.clone();
-                    execute_automation(controller_clone, steps_to_run, loop_count, None).await;
+                    let request = RunRequest {
+                        steps: steps_to_run,
+                        loop_count,
+                        selected_ids: None,
+                        random_timing: None,
+                        debug,
+                        range,
+                        on_error,
+                        simulate,
+                    };
+                    let description = if request.simulate.is_some() {
+                        format!("Simulating automation with {} loops", loop_count)
+                    } else if request.debug {
+                        format!("Debugging automation with {} loops", loop_count)
+                    } else {
+                        format!("Running automation with {} loops", loop_count)
+                    };
+                    start_run(&automation_controller, &mut controller, client_id, request, description);
                 }
             },
             "run_selected_steps" => {
                 // เริ่มการทำงานอัตโนมัติเฉพาะสเต็ปที่เลือก
                 if let Some(data) = json_data.get("data") {
+                    let debug = data.get("debug").and_then(|v| v.as_bool()).unwrap_or(false);
+                    let on_error = match parse_error_policy(data) {
+                        Ok(policy) => policy,
+                        Err(reason) => {
+                            println!("Rejected run request: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid on_error: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    
                     // ตรวจสอบว่ามีการส่ง steps มาโดยตรงหรือไม่
                     if let Some(steps_array) = data.get("steps").and_then(|v| v.as_array()) {
                         // รับ steps ที่ส่งมาโดยตรง
                         println!("Using steps sent directly from frontend: {} steps", steps_array.len());
                         
                         if !steps_array.is_empty() {
-                            controller.is_running = true;
-                            
-                            // แจ้งการอัปเดต
-                            let status_msg = create_message("status_update", json!({
-                                "status": "running",
-                                "message": format!("Running {} selected steps", steps_array.len())
-                            }));
-                            broadcast_to_clients(&controller.clients, status_msg);
-                            
                             // แปลง steps จาก JSON เป็น MacroStep
-                            let mut selected_steps = Vec::new();
-                            for step_value in steps_array {
-                                if let (Some(id), Some(type_), Some(data)) = (
-                                    step_value.get("id").and_then(|v| v.as_str()),
-                                    step_value.get("type").and_then(|v| v.as_str()),
-                                    step_value.get("data")
-                                ) {
-                                    let step = crate::models::MacroStep {
-                                        id: id.to_string(),
-                                        type_: type_.to_string(),
-                                        data: data.clone(),
-                                    };
-                                    selected_steps.push(step);
+                            let selected_steps = match validation::parse_steps(steps_array) {
+                                Ok(steps) => steps,
+                                Err(problems) => {
+                                    println!("Rejected selected steps run: {}", validation::summarize(&problems));
+                                    reject_steps(&controller.clients, client_id, &problems);
+                                    return Ok(());
                                 }
-                            }
+                            };
                             
                             // เรียกใช้ฟังก์ชันทำงานตามขั้นตอน
-                            let controller_clone = automation_controller.clone();
-                            execute_automation(controller_clone, selected_steps, 1, None).await;
+                            let request = RunRequest {
+                                steps: selected_steps,
+                                loop_count: 1,
+                                selected_ids: None,
+                                random_timing: None,
+                                debug,
+                                range: StepRange::default(),
+                                on_error,
+                                simulate: None,
+                            };
+                            let description = format!("Running {} selected steps", steps_array.len());
+                            start_run(&automation_controller, &mut controller, client_id, request, description);
                         }
                     } else if let Some(step_ids) = data.get("step_ids").and_then(|v| v.as_array()) {
                         // แบบเดิม - ใช้ step_ids
@@ -467,35 +569,224 @@ async fn handle_websocket_message(
                         println!("Selected step IDs: {:?}", selected_ids);
                         
                         if !selected_ids.is_empty() {
-                            controller.is_running = true;
-                            
-                            // แจ้งการอัปเดต
-                            let status_msg = create_message("status_update", json!({
-                                "status": "running",
-                                "message": format!("Running {} selected steps", selected_ids.len())
-                            }));
-                            broadcast_to_clients(&controller.clients, status_msg);
-                            println!("Started automation with {} selected steps", selected_ids.len());
+                            // ตรวจสอบเฉพาะขั้นตอนที่เลือก (index ยังอ้างอิงตำแหน่งในรายการทั้งหมด)
+                            let problems: Vec<StepDiagnostic> = validation::validate_steps(&controller.steps)
+                                .into_iter()
+                                .filter(|problem| problem.id.as_ref().is_some_and(|id| selected_ids.contains(id)))
+                                .collect();
+                            if !problems.is_empty() {
+                                println!("Rejected selected steps run: {}", validation::summarize(&problems));
+                                reject_steps(&controller.clients, client_id, &problems);
+                                return Ok(());
+                            }
                             
                             // เรียกใช้ฟังก์ชันทำงานตามขั้นตอน
-                            let steps_clone = controller.steps.clone();
-                            let controller_clone = automation_controller.clone();
-                            execute_automation(controller_clone, steps_clone, 1, Some(selected_ids)).await;
+                            let description = format!("Running {} selected steps", selected_ids.len());
+                            let request = RunRequest {
+                                steps: controller.steps.clone(),
+                                loop_count: 1,
+                                selected_ids: Some(selected_ids),
+                                random_timing: None,
+                                debug,
+                                range: StepRange::default(),
+                                on_error,
+                                simulate: None,
+                            };
+                            start_run(&automation_controller, &mut controller, client_id, request, description);
+                        }
+                    }
+                }
+            },
+            "enqueue_run" => {
+                // เพิ่มรอบการทำงานเข้าคิว ถ้ายังไม่มีรอบที่ทำงานอยู่จะเริ่มทันที
+                if let Some(data) = json_data.get("data") {
+                    let loop_count = match parse_loop_count(data) {
+                        Ok(loop_count) => loop_count,
+                        Err(reason) => {
+                            println!("Rejected queue entry: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid loop_count: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    let name = data.get("name").and_then(|v| v.as_str()).map(|s| s.to_string());
+                    let on_error = match parse_error_policy(data) {
+                        Ok(policy) => policy,
+                        Err(reason) => {
+                            println!("Rejected run request: {}", reason);
+                            send_error(&controller.clients, client_id, format!("Invalid on_error: {}", reason));
+                            return Ok(());
+                        }
+                    };
+                    
+                    let random_timing = match data.get("random_timing") {
+                        Some(config) => match parse_random_timing(config) {
+                            Ok(config) => Some(config),
+                            Err(reason) => {
+                                println!("Rejected queue entry: {}", reason);
+                                send_error(&controller.clients, client_id, format!("Invalid random_timing: {}", reason));
+                                return Ok(());
+                            }
+                        },
+                        None => None,
+                    };
+                    
+                    // ใช้ steps ที่ส่งมา หรือ steps ที่เก็บไว้ถ้าไม่ได้ส่งมา
+                    let steps = match data.get("steps").and_then(|v| v.as_array()) {
+                        Some(steps_array) => match validation::parse_steps(steps_array) {
+                            Ok(steps) => steps,
+                            Err(problems) => {
+                                println!("Rejected queue entry: {}", validation::summarize(&problems));
+                                reject_steps(&controller.clients, client_id, &problems);
+                                return Ok(());
+                            }
+                        },
+                        None => {
+                            let problems = validation::validate_steps(&controller.steps);
+                            if !problems.is_empty() {
+                                println!("Rejected queue entry: {}", validation::summarize(&problems));
+                                reject_steps(&controller.clients, client_id, &problems);
+                                return Ok(());
+                            }
+                            controller.steps.clone()
                         }
+                    };
+                    
+                    if steps.is_empty() {
+                        send_error(&controller.clients, client_id, "Cannot queue a run without steps".to_string());
+                        return Ok(());
+                    }
+                    
+                    let request = RunRequest {
+                        steps,
+                        loop_count,
+                        selected_ids: None,
+                        random_timing,
+                        debug: false,
+                        range: StepRange::default(),
+                        on_error,
+                        simulate: None,
+                    };
+                    let queue_id = controller.enqueue_run(name, request);
+                    println!("Queued run {} ({} entries waiting)", queue_id, controller.run_queue.len());
+                    
+                    // แจ้งการอัปเดต
+                    if !executor::start_next_queued(&automation_controller, &mut controller) {
+                        executor::broadcast_queue(&controller);
+                    }
+                }
+            },
+            "get_queue" => {
+                // ส่งรายการในคิวกลับไป
+                let queue_msg = create_message("queue_updated", json!({ "queue": controller.run_queue }));
+                if let Some(client) = controller.clients.get(client_id) {
+                    let _ = client.send(queue_msg);
+                }
+            },
+            "reorder_queue" => {
+                // เรียงลำดับคิวใหม่ ต้องส่ง queue_ids ของทุกรายการในคิว
+                let queue_ids: Vec<String> = json_data.get("data")
+                    .and_then(|d| d.get("queue_ids"))
+                    .and_then(|v| v.as_array())
+                    .map(|ids| ids.iter().filter_map(|id| id.as_str().map(|s| s.to_string())).collect())
+                    .unwrap_or_default();
+                
+                match controller.reorder_queue(&queue_ids) {
+                    Ok(()) => {
+                        executor::broadcast_queue(&controller);
+                        println!("Reordered run queue");
+                    },
+                    Err(reason) => {
+                        println!("Rejected queue reorder: {}", reason);
+                        send_error(&controller.clients, client_id, format!("Cannot reorder queue: {}", reason));
                     }
                 }
             },
+            "cancel_queued_run" => {
+                // ลบรายการที่ยังไม่เริ่มทำงานออกจากคิว
+                let queue_id = json_data.get("data")
+                    .and_then(|d| d.get("queue_id"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                
+                if controller.cancel_queued_run(queue_id) {
+                    executor::broadcast_queue(&controller);
+                    println!("Cancelled queued run {}", queue_id);
+                } else {
+                    send_error(&controller.clients, client_id, format!("Queued run not found: {}", queue_id));
+                }
+            },
+            "clear_queue" => {
+                // ล้างรายการที่รออยู่ทั้งหมด (ไม่กระทบรอบที่กำลังทำงาน)
+                controller.run_queue.clear();
+                executor::broadcast_queue(&controller);
+                println!("Cleared run queue");
+            },
+            "start_queue" => {
+                // เริ่มคิวต่อ (ใช้หลังจากสั่งหยุดรอบที่ทำงานอยู่ ซึ่งจะทำให้คิวหยุดรอ)
+                if !executor::start_next_queued(&automation_controller, &mut controller) {
+                    println!("Queue not started: {}", if controller.is_running() { "a run is in progress" } else { "queue is empty" });
+                }
+            },
             "stop_automation" => {
-                // หยุดการทำงานอัตโนมัติ
-                controller.is_running = false;
+                // หยุดการทำงานอัตโนมัติ executor จะตัดการรอที่ค้างอยู่และส่ง automation_completed เมื่อหยุดจริง
+                let active_run_id = controller.current_run.as_ref().map(|run| run.run_id.clone());
+                controller.stop_run();
                 
                 // แจ้งการอัปเดต
-                let status_msg = create_message("status_update", json!({
-                    "status": "stopped",
-                    "message": "Automation stopped"
-                }));
+                let status_msg = if let Some(run_id) = active_run_id {
+                    create_message("status_update", json!({
+                        "status": "stopping",
+                        "message": "Stopping automation...",
+                        "run_id": run_id
+                    }))
+                } else {
+                    create_message("status_update", json!({
+                        "status": "stopped",
+                        "message": "Automation stopped"
+                    }))
+                };
                 broadcast_to_clients(&controller.clients, status_msg);
-                println!("Stopped automation");
+                println!("Stop requested");
+            },
+            "pause_automation" => {
+                // หยุดชั่วคราว executor จะหยุดรอก่อนเริ่มขั้นตอนถัดไป
+                if controller.is_running() && !controller.is_paused() {
+                    controller.set_paused(true);
+                    println!("Pause requested");
+                }
+            },
+            "resume_automation" => {
+                // ทำงานต่อจากขั้นตอนที่หยุดไว้
+                if let Some(run_id) = controller.current_run.as_ref().filter(|_| controller.is_paused()).map(|run| run.run_id.clone()) {
+                    controller.set_paused(false);
+                    
+                    // แจ้งการอัปเดต
+                    let status_msg = create_message("status_update", json!({
+                        "status": "running",
+                        "message": "Automation resumed",
+                        "run_id": run_id
+                    }));
+                    broadcast_to_clients(&controller.clients, status_msg);
+                    println!("Resumed automation");
+                }
+            },
+            "debug_step" | "debug_continue" => {
+                // สั่งรอบ debug ที่หยุดอยู่ให้ทำขั้นตอนถัดไป หรือทำงานต่อจนถึง breakpoint ถัดไป
+                let command = if event_type == "debug_step" { DebugCommand::Step } else { DebugCommand::Continue };
+                if controller.send_debug_command(command) {
+                    println!("Sent debug command {:?}", command);
+                } else {
+                    send_error(&controller.clients, client_id, "No debug run in progress".to_string());
+                }
+            },
+            "debug_abort" => {
+                // ยกเลิกรอบ debug executor จะส่ง automation_completed (stopped) เมื่อหยุดจริง
+                if controller.debug_commands.is_some() {
+                    controller.stop_run();
+                    println!("Debug run aborted");
+                } else {
+                    send_error(&controller.clients, client_id, "No debug run in progress".to_string());
+                }
             },
             "start_recording" => {
                 // เริ่มการบันทึก
@@ -573,40 +864,192 @@ async fn handle_websocket_message(
                     let enabled = data.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
                     let min_factor = data.get("min_factor").and_then(|v| v.as_f64()).unwrap_or(0.8);
                     let max_factor = data.get("max_factor").and_then(|v| v.as_f64()).unwrap_or(1.2);
+                    // ถ้าไม่ส่งการสุ่มตำแหน่งมาจะใช้ค่าเดิม
+                    let jitter = match parse_jitter(data, controller.jitter) {
+                        Ok(jitter) => jitter,
+                        Err(reason) => {
+                            send_error(&controller.clients, client_id, format!("Invalid random_timing: {}", reason));
+                            return Ok(());
+                        }
+                    };
                     
                     controller.random_enabled = enabled;
                     controller.random_min = min_factor as f32;
                     controller.random_max = max_factor as f32;
+                    controller.jitter = jitter;
                     
                     // แจ้งการอัปเดต
                     let config_msg = create_message("random_timing_updated", json!({
                         "enabled": enabled,
                         "min_factor": min_factor,
-                        "max_factor": max_factor
+                        "max_factor": max_factor,
+                        "jitter_radius": jitter.radius,
+                        "jitter_distribution": jitter.distribution
                     }));
                     broadcast_to_clients(&controller.clients, config_msg);
-                    println!("Updated random timing: enabled={}, min={}, max={}", enabled, min_factor, max_factor);
+                    println!(
+                        "Updated random timing: enabled={}, min={}, max={}, jitter={}px ({})",
+                        enabled, min_factor, max_factor, jitter.radius, jitter.distribution.as_str()
+                    );
+                }
+            },
+            "get_timing_profile" => {
+                // ส่งชุดเวลาหน่วงระหว่าง input ที่ใช้อยู่
+                let profile_msg = create_message("timing_profile_updated", json!({ "profile": controller.input.profile() }));
+                if let Some(client) = controller.clients.get(client_id) {
+                    let _ = client.send(profile_msg);
+                }
+            },
+            "set_timing_profile" => {
+                // เปลี่ยนชุดเวลาหน่วง มีผลกับคำสั่ง input ถัดไป (รวมถึงรอบที่กำลังทำงาน)
+                let profile = match json_data.get("data").and_then(|d| d.get("profile")).and_then(|v| v.as_str()) {
+                    Some(profile) => profile.parse::<TimingProfile>(),
+                    None => Err("Missing profile".to_string()),
+                };
+                match profile {
+                    Ok(profile) => {
+                        controller.input.set_profile(profile);
+                        let profile_msg = create_message("timing_profile_updated", json!({ "profile": profile }));
+                        broadcast_to_clients(&controller.clients, profile_msg);
+                        println!("Timing profile set to {}", profile);
+                    },
+                    Err(reason) => send_error(&controller.clients, client_id, format!("Invalid timing profile: {}", reason)),
+                }
+            },
+            "get_display_layout" => {
+                // ส่งรายชื่อจอปัจจุบันและวิธีจัดการจอที่ไม่ได้เชื่อมต่อ
+                let layout = DisplayLayout::query(&controller.displays, controller.monitor_fallback).await;
+                if let Some(client) = controller.clients.get(client_id) {
+                    let _ = client.send(display_layout_message(&layout));
+                }
+            },
+            "set_monitor_fallback" => {
+                // เปลี่ยนวิธีเล่นขั้นตอนที่บันทึกบนจอซึ่งไม่ได้เชื่อมต่อ มีผลกับรอบการทำงานถัดไป
+                let fallback = match json_data.get("data").and_then(|d| d.get("fallback")).and_then(|v| v.as_str()) {
+                    Some(fallback) => fallback.parse::<MonitorFallback>(),
+                    None => Err("Missing fallback".to_string()),
+                };
+                match fallback {
+                    Ok(fallback) => {
+                        controller.monitor_fallback = fallback;
+                        let layout = DisplayLayout::query(&controller.displays, fallback).await;
+                        broadcast_to_clients(&controller.clients, display_layout_message(&layout));
+                        println!("Monitor fallback set to {}", fallback.as_str());
+                    },
+                    Err(reason) => send_error(&controller.clients, client_id, format!("Invalid monitor fallback: {}", reason)),
+                }
+            },
+            "convert_coordinates" => {
+                // แปลงตำแหน่งของ mouse_move และคลิกทั้ง macro เป็น pixel หรือสัดส่วนของจอที่แต่ละขั้นตอนอยู่
+                // ถ้าส่ง steps มาจะตอบกลับเฉพาะผู้ส่ง ถ้าไม่ส่งจะแปลง steps ที่เก็บไว้แล้วแจ้งทุกไคลเอนต์
+                let data = json_data.get("data").cloned().unwrap_or(Value::Null);
+                let provided = data.get("steps").and_then(|v| v.as_array());
+                if provided.is_none() && controller.is_running() {
+                    send_error(&controller.clients, client_id, "Cannot convert steps while automation is running".to_string());
+                    return Ok(());
+                }
+                let (to, screen, reference) = match parse_coordinate_conversion(&data) {
+                    Ok(conversion) => conversion,
+                    Err(reason) => {
+                        send_error(&controller.clients, client_id, format!("Invalid convert_coordinates: {}", reason));
+                        return Ok(());
+                    }
+                };
+                // จอที่ขั้นตอนเทียบไว้ต้องเชื่อมต่ออยู่ จึงไม่ใช้ monitor fallback
+                let current = DisplayLayout::query(&controller.displays, MonitorFallback::Fail).await;
+                // ถ้าระบุขนาดหน้าจอจะถือว่ามีจอเดียว (ใช้ชื่อของจอหลักปัจจุบัน) ถ้าไม่ระบุจะใช้การจัดวางจอปัจจุบัน
+                let layout = match screen {
+                    Some(screen) => {
+                        let primary = current.primary();
+                        DisplayLayout {
+                            monitors: vec![Monitor {
+                                id: primary.map_or_else(|| "screen".to_string(), |primary| primary.id.clone()),
+                                x: screen.x,
+                                y: screen.y,
+                                width: screen.width,
+                                height: screen.height,
+                                scale: primary.map_or(1.0, |primary| primary.scale),
+                                primary: true,
+                            }],
+                            fallback: MonitorFallback::Fail,
+                        }
+                    },
+                    None if current.monitors.is_empty() => {
+                        send_error(&controller.clients, client_id, "Cannot read the screen size, send width and height".to_string());
+                        return Ok(());
+                    },
+                    None => current,
+                };
+
+                let mut steps = match provided {
+                    Some(steps_array) => match validation::parse_steps(steps_array) {
+                        Ok(steps) => steps,
+                        Err(problems) => {
+                            reject_steps(&controller.clients, client_id, &problems);
+                            return Ok(());
+                        }
+                    },
+                    None => controller.steps.clone(),
+                };
+                // ขั้นตอนที่เทียบกับจอใดก็ตามจะถูกย้ายมาเทียบกับหน้าจอที่ระบุขนาด โดยคงระยะจากมุมจอไว้
+                let screen_monitor = screen.and_then(|_| layout.primary().cloned());
+                let mut problems = Vec::new();
+                for (index, step) in steps.iter_mut().enumerate() {
+                    let mut convert = |position, anchor: &mut Option<MonitorAnchor>| {
+                        if let (Some(screen), Some(anchor)) = (&screen_monitor, anchor.as_mut()) {
+                            anchor.id = screen.id.clone();
+                            anchor.scale = screen.scale;
+                        }
+                        layout.convert(position, anchor, to, reference)
+                    };
+                    problems.extend(
+                        step.action
+                            .convert_coordinates(&mut convert)
+                            .into_iter()
+                            .map(|err| StepDiagnostic::new(index, Some(step.id.clone()), err)),
+                    );
+                }
+                // ไม่แปลงเลยถ้ามีขั้นตอนที่แปลงไม่ได้ เพื่อไม่ให้ macro มีทั้งสองแบบปนกัน
+                if !problems.is_empty() {
+                    reject_steps(&controller.clients, client_id, &problems);
+                    return Ok(());
                 }
+                println!("Converted {} steps to {} coordinates", steps.len(), to.as_str());
+
+                if provided.is_some() {
+                    let converted_msg = create_message("coordinates_converted", json!({ "steps": steps }));
+                    if let Some(client) = controller.clients.get(client_id) {
+                        let _ = client.send(converted_msg);
+                    }
+                } else {
+                    controller.steps = steps;
+                    let steps_msg = create_message("steps_updated", json!({ "steps": controller.steps }));
+                    broadcast_to_clients(&controller.clients, steps_msg);
+                }
+            },
+            "validate_steps" => {
+                // ตรวจสอบขั้นตอนโดยไม่เปลี่ยนแปลงอะไร ใช้ steps ที่ส่งมาหรือ steps ที่เก็บไว้
+                let problems = match json_data.get("data").and_then(|d| d.get("steps")).and_then(|v| v.as_array()) {
+                    Some(steps_array) => validation::parse_steps(steps_array).err().unwrap_or_default(),
+                    None => validation::validate_steps(&controller.steps),
+                };
+                
+                println!("Validated steps: {}", validation::summarize(&problems));
+                send_validation_result(&controller.clients, client_id, &problems);
             },
             "update_steps_order" => {
                 // อัปเดตลำดับของขั้นตอน
                 if let Some(data) = json_data.get("data") {
                     if let Some(steps_data) = data.get("steps").and_then(|v| v.as_array()) {
                         // แปลง JSON steps เป็น MacroStep objects
-                        let mut new_steps = Vec::new();
-                        for step_json in steps_data {
-                            if let (Some(id), Some(type_), Some(data)) = (
-                                step_json.get("id").and_then(|v| v.as_str()),
-                                step_json.get("type").and_then(|v| v.as_str()),
-                                step_json.get("data")
-                            ) {
-                                new_steps.push(crate::models::MacroStep {
-                                    id: id.to_string(),
-                                    type_: type_.to_string(),
-                                    data: data.clone(),
-                                });
+                        let new_steps = match validation::parse_steps(steps_data) {
+                            Ok(steps) => steps,
+                            Err(problems) => {
+                                println!("Rejected steps update: {}", validation::summarize(&problems));
+                                reject_steps(&controller.clients, client_id, &problems);
+                                return Ok(());
                             }
-                        }
+                        };
                         
                         // อัปเดตขั้นตอนในคอนโทรลเลอร์
                         controller.steps = new_steps;
@@ -640,15 +1083,275 @@ pub fn create_message(event_type: &str, data: Value) -> Message {
 }
 
 // ส่งข้อความไปยังผู้ใช้ทั้งหมด
-fn broadcast_to_clients(
+pub(crate) fn broadcast_to_clients(
     clients: &std::collections::HashMap<String, mpsc::UnboundedSender<Message>>,
     message: Message,
 ) {
-    for (_, client) in clients {
+    for client in clients.values() {
         let _ = client.send(message.clone());
     }
 }
 
+// ส่งข้อความแจ้งข้อผิดพลาดไปยังผู้ใช้ที่ส่งคำสั่งมา
+fn send_error(
+    clients: &std::collections::HashMap<String, mpsc::UnboundedSender<Message>>,
+    client_id: &str,
+    message: String,
+) {
+    let error_msg = create_message("status_update", json!({
+        "status": "error",
+        "message": message
+    }));
+    if let Some(client) = clients.get(client_id) {
+        let _ = client.send(error_msg);
+    }
+}
+
+// เริ่มรอบการทำงานใหม่ ถ้ามีรอบอื่นกำลังทำงานอยู่จะไม่เริ่มและแจ้ง run_rejected กลับไปยังผู้ส่งคำสั่ง
+fn start_run(
+    automation_controller: &Arc<Mutex<AutomationController>>,
+    controller: &mut AutomationController,
+    client_id: &str,
+    request: RunRequest,
+    description: String,
+) {
+    let run = match controller.begin_run() {
+        Ok(run) => run,
+        Err(active) => {
+            println!("Rejected run request: run {} is still in progress", active.run_id);
+            let rejected_msg = create_message("run_rejected", json!({
+                "message": "Another automation is already running",
+                "active_run_id": active.run_id
+            }));
+            if let Some(client) = controller.clients.get(client_id) {
+                let _ = client.send(rejected_msg);
+            }
+            return;
+        }
+    };
+    
+    executor::launch_run(automation_controller, controller, request, run, description, None);
+}
+
+// อ่าน start_index, end_index และ start_loop ของ run_automation และตรวจสอบกับจำนวนขั้นตอนและรอบ
+fn parse_step_range(data: &Value, step_count: usize, loop_count: i32) -> Result<StepRange, String> {
+    let read_index = |field: &str| -> Result<Option<u64>, String> {
+        match data.get(field) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| format!("{} must be a non-negative integer, got {}", field, value)),
+        }
+    };
+
+    let start_index = read_index("start_index")?.unwrap_or(0) as usize;
+    let end_index = read_index("end_index")?.map(|index| index as usize);
+    let start_loop = read_index("start_loop")?.unwrap_or(0);
+
+    if start_index > 0 && start_index >= step_count {
+        return Err(format!("start_index {} is out of range ({} steps)", start_index, step_count));
+    }
+    if let Some(end_index) = end_index {
+        if end_index >= step_count {
+            return Err(format!("end_index {} is out of range ({} steps)", end_index, step_count));
+        }
+        if end_index < start_index {
+            return Err(format!("end_index {} is before start_index {}", end_index, start_index));
+        }
+    }
+    if start_loop > 0 && start_loop >= loop_count.max(0) as u64 {
+        return Err(format!("start_loop {} is out of range ({} loops)", start_loop, loop_count));
+    }
+
+    Ok(StepRange {
+        start_index,
+        end_index,
+        start_loop: start_loop as u32,
+    })
+}
+
+// อ่าน simulate และ time_scale ของ run_automation (time_scale ใช้ได้เฉพาะตอนจำลอง)
+fn parse_simulation(data: &Value) -> Result<Option<Simulation>, String> {
+    if !data.get("simulate").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let time_scale = match data.get("time_scale") {
+        None | Some(Value::Null) => 1.0,
+        Some(value) => value
+            .as_f64()
+            .filter(|scale| scale.is_finite() && *scale >= 0.0)
+            .ok_or_else(|| format!("time_scale must be a non-negative number, got {}", value))?,
+    };
+    Ok(Some(Simulation { time_scale }))
+}
+
+// อ่านการแปลงตำแหน่ง {to, width, height, reference} (ขนาดหน้าจอไม่ระบุก็ได้)
+fn parse_coordinate_conversion(data: &Value) -> Result<(CoordinateSpace, Option<Rect>, Option<Rect>), String> {
+    let to: CoordinateSpace = match data.get("to") {
+        Some(Value::String(to)) => to.parse()?,
+        None | Some(Value::Null) => return Err("to is required (absolute or normalized)".to_string()),
+        Some(other) => return Err(format!("to must be a string, got {}", other)),
+    };
+    let dimension = |field: &str| match data.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_i64()
+            .and_then(|n| i32::try_from(n).ok())
+            .filter(|n| *n > 0)
+            .map(Some)
+            .ok_or_else(|| format!("{} must be a positive number of pixels, got {}", field, value)),
+    };
+    let screen = match (dimension("width")?, dimension("height")?) {
+        (Some(width), Some(height)) => Some(Rect { x: 0, y: 0, width, height }),
+        (None, None) => None,
+        _ => return Err("width and height must be given together".to_string()),
+    };
+    let reference = match data.get("reference") {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let reference: Rect = serde_json::from_value(value.clone())
+                .map_err(|_| "reference must be an object with x, y, width and height".to_string())?;
+            if reference.width <= 0 || reference.height <= 0 {
+                return Err("reference width and height must be positive".to_string());
+            }
+            Some(reference)
+        },
+    };
+    if reference.is_some() && to == CoordinateSpace::Absolute {
+        return Err("reference can only be used when converting to normalized".to_string());
+    }
+    Ok((to, screen, reference))
+}
+
+// อ่านจำนวนรอบของการทำงาน (ถ้าไม่ระบุคือ 1 รอบ)
+fn parse_loop_count(data: &Value) -> Result<i32, String> {
+    match data.get("loop_count") {
+        None | Some(Value::Null) => Ok(1),
+        Some(value) => value
+            .as_i64()
+            .filter(|n| *n >= 1)
+            .and_then(|n| i32::try_from(n).ok())
+            .ok_or_else(|| format!("must be a whole number from 1 to {}, got {}", i32::MAX, value)),
+    }
+}
+
+// อ่าน on_error ค่าเริ่มต้นของรอบการทำงาน (ถ้าไม่ระบุจะทำขั้นตอนถัดไปต่อเหมือนเดิม)
+fn parse_error_policy(data: &Value) -> Result<ErrorPolicy, String> {
+    match data.get("on_error") {
+        None | Some(Value::Null) => Ok(ErrorPolicy::default()),
+        Some(Value::String(policy)) => policy.parse(),
+        Some(other) => Err(format!("expected a string, got {}", other)),
+    }
+}
+
+// แปลงการตั้งค่าการสุ่มเวลาของรายการในคิว
+fn parse_random_timing(value: &Value) -> Result<RandomTimingConfig, String> {
+    let config: RandomTimingConfig = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    if !(config.min_factor > 0.0 && config.min_factor <= config.max_factor) {
+        return Err(format!(
+            "min_factor must be positive and not greater than max_factor (got {} - {})",
+            config.min_factor, config.max_factor
+        ));
+    }
+    config.jitter.validate()?;
+    Ok(config)
+}
+
+// อ่าน jitter_radius / jitter_distribution จากการตั้งค่า ฟิลด์ที่ไม่ได้ส่งมาจะใช้ค่าจาก current
+fn parse_jitter(data: &Value, current: CoordinateJitter) -> Result<CoordinateJitter, String> {
+    let mut jitter = current;
+    if let Some(radius) = data.get("jitter_radius").filter(|v| !v.is_null()) {
+        jitter.radius = radius
+            .as_f64()
+            .ok_or_else(|| format!("jitter_radius must be a number, got {}", radius))?;
+    }
+    if let Some(distribution) = data.get("jitter_distribution").filter(|v| !v.is_null()) {
+        jitter.distribution = distribution
+            .as_str()
+            .ok_or_else(|| format!("jitter_distribution must be a string, got {}", distribution))?
+            .parse()?;
+    }
+    jitter.validate()?;
+    Ok(jitter)
+}
+
+// รายชื่อจอและวิธีจัดการจอที่ไม่ได้เชื่อมต่อ
+fn display_layout_message(layout: &DisplayLayout) -> Message {
+    create_message("display_layout", json!({
+        "monitors": layout.monitors,
+        "fallback": layout.fallback
+    }))
+}
+
+// ข้อมูลคลิปบอร์ดที่ส่งกลับไปยังไคลเอนต์ (รูปภาพเข้ารหัสเป็น base64)
+fn clipboard_content_json(format: ClipboardFormat, content: Option<&ClipboardContent>) -> Value {
+    match content {
+        Some(ClipboardContent::Text(text)) => json!({ "format": format.as_str(), "available": true, "text": text }),
+        Some(ClipboardContent::Html { html, alt_text }) => json!({
+            "format": format.as_str(),
+            "available": true,
+            "html": html,
+            "text": alt_text
+        }),
+        Some(ClipboardContent::Image { mime, bytes }) => json!({
+            "format": format.as_str(),
+            "available": true,
+            "mime": mime,
+            "data": base64::engine::general_purpose::STANDARD.encode(bytes)
+        }),
+        None => json!({ "format": format.as_str(), "available": false }),
+    }
+}
+
+// อ่านข้อมูลที่จะตั้งให้คลิปบอร์ดจากคำสั่ง set_clipboard
+// image: { mime, data (base64) } หรือ html (ใช้ text เป็นข้อความสำรอง) หรือ text อย่างเดียว
+fn parse_clipboard_content(data: &Value) -> Result<Option<ClipboardContent>, String> {
+    if let Some(image) = data.get("image").filter(|v| !v.is_null()) {
+        let mime = image.get("mime").and_then(|v| v.as_str()).unwrap_or("image/png").to_string();
+        let encoded = image
+            .get("data")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| "image.data must be a base64 string".to_string())?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("image.data is not valid base64: {}", e))?;
+        return Ok(Some(ClipboardContent::Image { mime, bytes }));
+    }
+
+    let text = data.get("text").and_then(|v| v.as_str()).map(|s| s.to_string());
+    if let Some(html) = data.get("html").and_then(|v| v.as_str()) {
+        return Ok(Some(ClipboardContent::Html { html: html.to_string(), alt_text: text }));
+    }
+    Ok(text.map(ClipboardContent::Text))
+}
+
+// ส่งผลการตรวจสอบขั้นตอนไปยังผู้ใช้ที่ส่งคำสั่งมา
+fn send_validation_result(
+    clients: &std::collections::HashMap<String, mpsc::UnboundedSender<Message>>,
+    client_id: &str,
+    problems: &[StepDiagnostic],
+) {
+    let result_msg = create_message("steps_validated", json!({
+        "valid": problems.is_empty(),
+        "problems": problems
+    }));
+    if let Some(client) = clients.get(client_id) {
+        let _ = client.send(result_msg);
+    }
+}
+
+// แจ้งว่าไม่รับคำสั่งเพราะมีขั้นตอนที่ไม่ถูกต้อง พร้อมรายการปัญหา
+fn reject_steps(
+    clients: &std::collections::HashMap<String, mpsc::UnboundedSender<Message>>,
+    client_id: &str,
+    problems: &[StepDiagnostic],
+) {
+    send_validation_result(clients, client_id, problems);
+    send_error(clients, client_id, validation::summarize(problems));
+}
+
 // ฟังก์ชันสำหรับการส่งตำแหน่งเมาส์เรียลไทม์
 #[allow(dead_code)]
 pub fn start_mouse_position_tracking(automation_controller: std::sync::Arc<tokio::sync::Mutex<crate::automation::AutomationController>>) {
@@ -682,549 +1385,319 @@ pub fn start_mouse_position_tracking(automation_controller: std::sync::Arc<tokio
     });
 }
 
-// ฟังก์ชันสำหรับทำงานตามขั้นตอนที่กำหนด
-async fn execute_automation(
-    controller: Arc<Mutex<AutomationController>>,
-    steps: Vec<crate::models::MacroStep>,
-    loop_count: i32,
-    selected_ids: Option<Vec<String>>,
-) {
-    println!("Starting execute_automation function");
-    // สร้าง task ใหม่เพื่อป้องกันการบล็อค thread
+// เพิ่มฟังก์ชันสำหรับบันทึกเหตุการณ์เมาส์และแป้นพิมพ์
+fn start_event_recorder(controller: Arc<Mutex<AutomationController>>) {
     tokio::spawn(async move {
-        // เก็บ clients ไว้เพื่อส่งอัพเดทสถานะ
-        let clients_clone = {
-            let controller = controller.lock().await;
-            controller.clients.clone()
+        // การจัดวางจอตอนเริ่มบันทึก ใช้เก็บตำแหน่งเมาส์เทียบกับจอ
+        let displays = {
+            let (provider, fallback) = {
+                let controller = controller.lock().await;
+                (controller.displays.clone(), controller.monitor_fallback)
+            };
+            DisplayLayout::query(&provider, fallback).await
         };
         
-        // เก็บ steps ไว้ในตัวแปรใหม่เพื่อใช้หา index
-        let steps_for_index = steps.clone();
+        // วนอ่านอุปกรณ์บน thread ของตัวเอง (DeviceState ของ X11 ส่งข้าม thread ไม่ได้)
+        // แล้วส่งขั้นตอนที่บันทึกได้กลับมาเพิ่มใน controller ที่นี่
+        let (steps, mut recorded) = tokio::sync::mpsc::unbounded_channel();
+        let recorder_controller = controller.clone();
+        std::thread::spawn(move || record_input_events(recorder_controller, displays, steps));
         
-        // กรองเฉพาะขั้นตอนที่เลือก (ถ้ามีการระบุ)
-        let filtered_steps = if let Some(ids) = &selected_ids {
-            println!("Filtering steps by selected IDs: {:?}", ids);
-            steps.iter()
-                .filter(|step| ids.contains(&step.id))
-                .cloned()
-                .collect::<Vec<_>>()
-        } else {
-            steps
+        while let Some((step_type, data)) = recorded.recv().await {
+            add_recorded_step(&controller, step_type, &data).await;
+        }
+    });
+}
+
+// ขั้นตอนที่ thread บันทึกส่งกลับมา (ชนิดขั้นตอน, data)
+type RecordedSteps = tokio::sync::mpsc::UnboundedSender<(&'static str, Value)>;
+
+// ติดตามเมาส์และคีย์บอร์ดจนกว่าจะหยุดบันทึก
+fn record_input_events(controller: Arc<Mutex<AutomationController>>, displays: DisplayLayout, steps: RecordedSteps) {
+    // device_query หรือ evdev (Wayland) ตามการตั้งค่า
+    let device_state = crate::input_monitor::from_env();
+    let mut _last_position = (0, 0); // Prefixed with underscore to indicate intentionally unused
+    let mut last_mouse_buttons = vec![false; 10]; // เก็บสถานะปุ่มเมาส์ล่าสุด
+    let mut last_keys = device_state.pressed_keys();
+    let mut key_combo_buffer = Vec::new(); // เก็บปุ่มที่กำลังถูกกดพร้อมกัน
+    let mut key_combo_timer = std::time::Instant::now(); // จับเวลาการกดปุ่มพร้อมกัน
+    let mut mouse_click_cooldown = false; // ป้องกันการบันทึกซ้ำ
+    let mut last_click_time = std::time::Instant::now(); // เวลาคลิกล่าสุด
+    let mut wheel_events = device_state.subscribe_wheel_events();
+    let mut pending_scroll: Option<PendingScroll> = None; // wheel ที่เลื่อนต่อเนื่องกันและยังไม่ได้บันทึก
+    
+    // จับเหตุการณ์ทุก 50 มิลลิวินาที (20 FPS)
+    let poll_interval = std::time::Duration::from_millis(50);
+    
+    // เริ่มด้วยข้อความว่าเริ่มบันทึก
+    println!("Event recorder started, tracking mouse clicks and key presses");
+    if !device_state.knows_cursor_position() {
+        println!("The {} monitor only estimates the cursor position, clicks are recorded without mouse moves", device_state.name());
+    }
+    println!("DEBUG: Press each mouse button to see which index it corresponds to");
+    
+    // เพิ่มฟังก์ชันเพื่อช่วยตรวจสอบ button index
+    let mut test_mode_counter = 20; // จำนวนรอบที่จะให้แสดง debug info
+    
+    loop {
+        // เช็คว่ายังอยู่ในโหมดบันทึกหรือไม่
+        let is_recording = {
+            let controller = controller.blocking_lock();
+            controller.is_recording
         };
         
-        println!("Executing {} steps with {} loops", filtered_steps.len(), loop_count);
-        
-        for loop_index in 0..loop_count {
-            println!("Starting loop {}/{}", loop_index + 1, loop_count);
-            
-            // เช็คว่ายังทำงานอยู่หรือไม่
-            {
-                let controller = controller.lock().await;
-                if !controller.is_running {
-                    println!("Automation stopped");
-                    break;
-                }
-            }
-            
-            for (step_index, step) in filtered_steps.iter().enumerate() {
-                // ส่งสถานะปัจจุบัน
-                let current_index = if let Some(_ids) = &selected_ids {
-                    // หา index ใน steps เดิม
-                    steps_for_index.iter().position(|s| s.id == step.id).unwrap_or(step_index)
-                } else {
-                    step_index
-                };
-                
-                let step_msg = create_message("step_executing", json!({
-                    "index": current_index,
-                    "total_steps": filtered_steps.len(),
-                    "completed_steps": step_index,
-                    "loop_index": loop_index,
-                    "total_loops": loop_count
-                }));
-                broadcast_to_clients(&clients_clone, step_msg);
-                
-                // เช็คว่ายังทำงานอยู่หรือไม่
-                {
-                    let controller = controller.lock().await;
-                    if !controller.is_running {
-                        println!("Automation stopped during execution");
-                        break;
-                    }
-                }
-                
-                // ทำงานตามประเภทของขั้นตอน
-                let step_type = step.type_.as_str();
-                println!("Executing step {}: {}", step_index + 1, step_type);
-                
-                match step_type {
-                    "mouse_move" => {
-                        // ดึงพิกัด x, y
-                        if let (Some(x), Some(y)) = (
-                            step.data.get("x").and_then(|v| v.as_i64()),
-                            step.data.get("y").and_then(|v| v.as_i64()),
-                        ) {
-                            println!("Moving mouse to position: ({}, {})", x, y);
-                            
-                            // เรียกใช้ฟังก์ชันควบคุมเมาส์
-                            crate::mouse_keyboard::mouse_move(x as i32, y as i32).await;
-                            println!("Mouse move completed");
-                        }
-                    },
-                    "mouse_click" => {
-                        // ดึงข้อมูลปุ่มที่คลิก
-                        let button_str = step.data.get("button")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("left");
-                        
-                        println!("Clicking {} mouse button", button_str);
-                        
-                        // แปลงเป็น MouseButton enum
-                        let button = match button_str.to_lowercase().as_str() {
-                            "right" => crate::mouse_keyboard::MouseButton::Right,
-                            "middle" => crate::mouse_keyboard::MouseButton::Middle,
-                            _ => crate::mouse_keyboard::MouseButton::Left,
-                        };
-                        
-                        // เรียกใช้ฟังก์ชันคลิกเมาส์
-                        crate::mouse_keyboard::mouse_click(button).await;
-                        println!("Mouse click completed");
-                    },
-                    "mouse_double_click" => {
-                        // ดึงข้อมูลปุ่มที่คลิก (เหมือน mouse_click)
-                        let button_str = step.data.get("button")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("left");
-                        
-                        println!("Double clicking {} mouse button", button_str);
-                        
-                        // แปลงเป็น MouseButton enum
-                        let button = match button_str.to_lowercase().as_str() {
-                            "right" => crate::mouse_keyboard::MouseButton::Right,
-                            "middle" => crate::mouse_keyboard::MouseButton::Middle,
-                            _ => crate::mouse_keyboard::MouseButton::Left,
-                        };
-                        
-                        // เรียกใช้ฟังก์ชันดับเบิลคลิกเมาส์
-                        crate::mouse_keyboard::mouse_double_click(button).await;
-                        println!("Mouse double click completed");
-                    },
-                    "key_press" => {
-                        // ดึงข้อมูลคีย์ที่จะกด
-                        if let Some(key) = step.data.get("key").and_then(|v| v.as_str()) {
-                            println!("Pressing key: {}", key);
-                            
-                            // เรียกใช้ฟังก์ชันกดคีย์บอร์ด
-                            let _ = crate::mouse_keyboard::keyboard_press_key(key).await;
-                            println!("Key press completed");
-                        }
-                    },
-                    "wait" => {
-                        // เป็นขั้นตอนการรอ ไม่ต้องทำอะไรเพิ่มเติม เพราะทุก step มีการรอตามเวลาที่กำหนดอยู่แล้ว
-                        println!("Wait step - will continue with normal wait time");
-                    },
-                    "group" => {
-                        // กรณีนี้ไม่ควรเกิดขึ้นเพราะได้แยกขั้นตอนใน group ออกมาตั้งแต่ใน frontend แล้ว
-                        println!("Group step encountered - should not happen as groups are processed in frontend");
-                    },
-                    _ => {
-                        println!("Unknown step type: {}", step_type);
-                    }
-                }
-                
-                // รอตามเวลาที่กำหนดในขั้นตอน
-                let wait_time = step.data.get("wait_time")
-                    .and_then(|v| v.as_f64())
-                    .unwrap_or(1.0);
-                
-                let randomize = step.data.get("randomize")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-                
-                let mut actual_wait = wait_time;
-                
-                // ปรับเวลารอถ้าเปิดใช้งานการสุ่ม
-                if randomize {
-                    // ดึงค่าจาก controller
-                    let (min_factor, max_factor) = {
-                        let controller = controller.lock().await;
-                        (controller.random_min as f64, controller.random_max as f64)
-                    };
-                    
-                    use rand::Rng;
-                    let mut rng = rand::thread_rng();
-                    let factor = min_factor + rng.gen::<f64>() * (max_factor - min_factor);
-                    
-                    actual_wait *= factor;
-                    println!("Randomized wait time: {:.2}s (base: {:.2}s, factor: {:.2})", 
-                             actual_wait, wait_time, factor);
-                } else {
-                    println!("Waiting for {:.2}s", wait_time);
-                }
-                
-                // รอตามเวลาที่คำนวณไว้
-                tokio::time::sleep(Duration::from_secs_f64(actual_wait)).await;
+        if !is_recording {
+            if let Some(scroll) = pending_scroll.take() {
+                record_scroll(&steps, &displays, scroll);
             }
+            println!("Event recorder stopping...");
+            break;
         }
         
-        // แจ้งว่าการทำงานเสร็จสิ้น
-        let complete_msg = create_message("automation_completed", json!({}));
-        broadcast_to_clients(&clients_clone, complete_msg);
+        // ตรวจสอบตำแหน่งเมาส์
+        let current_position = device_state.get_mouse().coords;
+        // evdev ที่มีแต่เมาส์ REL ไม่รู้ตำแหน่งจริงของ cursor
+        let known_position = device_state.knows_cursor_position().then_some(current_position);
         
-        // รีเซ็ตสถานะการทำงาน
-        {
-            let mut controller = controller.lock().await;
-            controller.is_running = false;
-            
-            // แจ้งสถานะล่าสุด
-            let status_msg = create_message("status_update", json!({
-                "status": "idle",
-                "message": "Automation completed"
-            }));
-            broadcast_to_clients(&controller.clients, status_msg);
-        }
+        // อัปเดตตำแหน่งเมาส์ล่าสุด (ใช้เพื่อการอ้างอิงในอนาคต)
+        _last_position = current_position;
         
-        println!("Automation execution completed");
-    });
-}
-
-// เพิ่มฟังก์ชันสำหรับบันทึกเหตุการณ์เมาส์และแป้นพิมพ์
-fn start_event_recorder(controller: Arc<Mutex<AutomationController>>) {
-    // สร้าง thread สำหรับติดตามตำแหน่งเมาส์
-    tokio::spawn(async move {
-        let device_state = DeviceState::new();
-        let mut _last_position = (0, 0); // Prefixed with underscore to indicate intentionally unused
-        let mut last_mouse_buttons = vec![false; 10]; // เก็บสถานะปุ่มเมาส์ล่าสุด
-        let mut last_keys = device_state.get_keys();
-        let mut key_combo_buffer = Vec::new(); // เก็บปุ่มที่กำลังถูกกดพร้อมกัน
-        let mut key_combo_timer = std::time::Instant::now(); // จับเวลาการกดปุ่มพร้อมกัน
-        let mut mouse_click_cooldown = false; // ป้องกันการบันทึกซ้ำ
-        let mut last_click_time = std::time::Instant::now(); // เวลาคลิกล่าสุด
+        // ตรวจสอบการคลิกเมาส์โดยใช้ device_query
+        let mouse_state = device_state.get_mouse();
         
-        // จับเหตุการณ์ทุก 50 มิลลิวินาที (20 FPS)
-        let poll_interval = std::time::Duration::from_millis(50);
-        
-        // เริ่มด้วยข้อความว่าเริ่มบันทึก
-        println!("Event recorder started, tracking mouse clicks and key presses");
-        println!("DEBUG: Press each mouse button to see which index it corresponds to");
+        // รวม wheel ที่เลื่อนต่อเนื่องกันเป็นขั้นตอน scroll เดียว
+        loop {
+            match wheel_events.try_recv() {
+                Ok(wheel) => {
+                    let scroll = pending_scroll.get_or_insert(PendingScroll {
+                        position: known_position,
+                        dx: 0,
+                        dy: 0,
+                        last_event: std::time::Instant::now(),
+                    });
+                    scroll.dx += wheel.dx;
+                    scroll.dy += wheel.dy;
+                    scroll.last_event = std::time::Instant::now();
+                },
+                Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
         
-        // เพิ่มฟังก์ชันเพื่อช่วยตรวจสอบ button index
-        let mut test_mode_counter = 20; // จำนวนรอบที่จะให้แสดง debug info
+        // บันทึกเมื่อหยุดเลื่อนไป 300ms หรือเมื่อเริ่มคลิก/กดปุ่ม เพื่อให้ลำดับขั้นตอนถูกต้อง
+        let scroll_finished = pending_scroll.as_ref().is_some_and(|scroll| {
+            scroll.last_event.elapsed().as_millis() >= 300
+                || mouse_state.button_pressed.iter().any(|&pressed| pressed)
+                || !device_state.pressed_keys().is_empty()
+        });
+        if scroll_finished {
+            if let Some(scroll) = pending_scroll.take() {
+                record_scroll(&steps, &displays, scroll);
+            }
+        }
         
-        loop {
-            // เช็คว่ายังอยู่ในโหมดบันทึกหรือไม่
-            let is_recording = {
-                let controller = controller.lock().await;
-                controller.is_recording
-            };
-            
-            if !is_recording {
-                println!("Event recorder stopping...");
-                break;
+        // Debug mode - แสดงข้อมูลปุ่มเมาส์ทุกครั้งที่มีการกด
+        if test_mode_counter > 0 && !mouse_state.button_pressed.is_empty() {
+            println!("DEBUG - Mouse button state: {:?}", mouse_state.button_pressed);
+            for (i, &pressed) in mouse_state.button_pressed.iter().enumerate() {
+                if pressed {
+                    println!("DEBUG - Button at index {} is pressed", i);
+                }
             }
-            
-            // ตรวจสอบตำแหน่งเมาส์
-            let current_position = crate::mouse_keyboard::get_cursor_position();
-            
-            // อัปเดตตำแหน่งเมาส์ล่าสุด (ใช้เพื่อการอ้างอิงในอนาคต)
-            _last_position = current_position;
-            
-            // ตรวจสอบการคลิกเมาส์โดยใช้ device_query
-            let mouse_state = device_state.get_mouse();
-            
-            // Debug mode - แสดงข้อมูลปุ่มเมาส์ทุกครั้งที่มีการกด
-            if test_mode_counter > 0 && !mouse_state.button_pressed.is_empty() {
-                println!("DEBUG - Mouse button state: {:?}", mouse_state.button_pressed);
+            test_mode_counter -= 1;
+        }
+        
+        // ตรวจสอบ cooldown การคลิก (ป้องกันการบันทึกซ้ำ)
+        if mouse_click_cooldown {
+            if last_click_time.elapsed().as_millis() > 300 { // รอ 300ms ก่อนจะให้บันทึกการคลิกใหม่ได้
+                mouse_click_cooldown = false;
+            } else {
+                // ข้ามการตรวจสอบการคลิกในรอบนี้
+                // อัปเดตสถานะปุ่มเมาส์เพื่อเตรียมพร้อมสำหรับการคลิกถัดไป
+                last_mouse_buttons = vec![false; 10];
                 for (i, &pressed) in mouse_state.button_pressed.iter().enumerate() {
-                    if pressed {
-                        println!("DEBUG - Button at index {} is pressed", i);
+                    if i < last_mouse_buttons.len() {
+                        last_mouse_buttons[i] = pressed;
                     }
                 }
-                test_mode_counter -= 1;
-            }
-            
-            // ตรวจสอบ cooldown การคลิก (ป้องกันการบันทึกซ้ำ)
-            if mouse_click_cooldown {
-                if last_click_time.elapsed().as_millis() > 300 { // รอ 300ms ก่อนจะให้บันทึกการคลิกใหม่ได้
-                    mouse_click_cooldown = false;
-                } else {
-                    // ข้ามการตรวจสอบการคลิกในรอบนี้
-                    // อัปเดตสถานะปุ่มเมาส์เพื่อเตรียมพร้อมสำหรับการคลิกถัดไป
-                    last_mouse_buttons = vec![false; 10];
-                    for (i, &pressed) in mouse_state.button_pressed.iter().enumerate() {
-                        if i < last_mouse_buttons.len() {
-                            last_mouse_buttons[i] = pressed;
-                        }
-                    }
-                    
-                    // ตรวจสอบการกดปุ่มคีย์บอร์ด (ยังคงตรวจสอบปุ่มคีย์บอร์ดตามปกติ)
-                    let current_keys = device_state.get_keys();
+                
+                // ตรวจสอบการกดปุ่มคีย์บอร์ด (ยังคงตรวจสอบปุ่มคีย์บอร์ดตามปกติ)
+                let current_keys = device_state.pressed_keys();
+                
+                // เพิ่มการตรวจสอบพิเศษสำหรับปุ่ม F7 และปุ่มฟังก์ชันอื่นๆ
+                if !current_keys.is_empty() {
+                    // ตรวจสอบว่ามีการกดปุ่มฟังก์ชันหรือไม่
+                    let has_function_key = current_keys.iter().any(|k| {
+                        matches!(k, Key::F(1..=12))
+                    });
                     
-                    // เพิ่มการตรวจสอบพิเศษสำหรับปุ่ม F7 และปุ่มฟังก์ชันอื่นๆ
-                    if !current_keys.is_empty() {
-                        // ตรวจสอบว่ามีการกดปุ่มฟังก์ชันหรือไม่
-                        let has_function_key = current_keys.iter().any(|k| {
-                            matches!(k, 
-                                device_query::Keycode::F1 | device_query::Keycode::F2 | 
-                                device_query::Keycode::F3 | device_query::Keycode::F4 | 
-                                device_query::Keycode::F5 | device_query::Keycode::F6 | 
-                                device_query::Keycode::F7 | device_query::Keycode::F8 | 
-                                device_query::Keycode::F9 | device_query::Keycode::F10 | 
-                                device_query::Keycode::F11 | device_query::Keycode::F12
-                            )
-                        });
+                    // ถ้ามีการกดปุ่มฟังก์ชัน ให้แยกออกมาบันทึกแค่ปุ่มฟังก์ชันเท่านั้น
+                    if has_function_key {
+                        // แยกเฉพาะปุ่มฟังก์ชัน
+                        let function_keys: Vec<_> = current_keys.iter().filter(|k| {
+                            matches!(k, Key::F(1..=12))
+                        }).collect();
                         
-                        // ถ้ามีการกดปุ่มฟังก์ชัน ให้แยกออกมาบันทึกแค่ปุ่มฟังก์ชันเท่านั้น
-                        if has_function_key {
-                            // แยกเฉพาะปุ่มฟังก์ชัน
-                            let function_keys: Vec<_> = current_keys.iter().filter(|k| {
-                                matches!(k, 
-                                    device_query::Keycode::F1 | device_query::Keycode::F2 | 
-                                    device_query::Keycode::F3 | device_query::Keycode::F4 | 
-                                    device_query::Keycode::F5 | device_query::Keycode::F6 | 
-                                    device_query::Keycode::F7 | device_query::Keycode::F8 | 
-                                    device_query::Keycode::F9 | device_query::Keycode::F10 | 
-                                    device_query::Keycode::F11 | device_query::Keycode::F12
-                                )
-                            }).collect();
+                        // ถ้ามีปุ่มฟังก์ชันเพียงปุ่มเดียว ให้ข้ามการบันทึก - ปุ่มฟังก์ชันใช้สำหรับควบคุมแอพฯ เท่านั้น
+                        if function_keys.len() == 1 {
+                            // ไม่บันทึกปุ่มฟังก์ชันเนื่องจากเป็นปุ่มลัดของแอพฯ
+                            let key_name = format!("{:?}", function_keys[0]);
+                            println!("Skipping recording function key {} as it's used as application shortcut", key_name);
                             
-                            // ถ้ามีปุ่มฟังก์ชันเพียงปุ่มเดียว ให้ข้ามการบันทึก - ปุ่มฟังก์ชันใช้สำหรับควบคุมแอพฯ เท่านั้น
-                            if function_keys.len() == 1 {
-                                // ไม่บันทึกปุ่มฟังก์ชันเนื่องจากเป็นปุ่มลัดของแอพฯ
-                                let key_name = format!("{:?}", function_keys[0]);
-                                println!("Skipping recording function key {} as it's used as application shortcut", key_name);
-                                
-                                // ข้ามการตรวจสอบคีย์คอมโบในรอบนี้
-                                last_keys = current_keys;
-                                tokio::time::sleep(poll_interval).await;
-                                continue;
-                            }
+                            // ข้ามการตรวจสอบคีย์คอมโบในรอบนี้
+                            last_keys = current_keys;
+                            std::thread::sleep(poll_interval);
+                            continue;
                         }
                     }
                 }
             }
-            
-            // ฟังก์ชันช่วยบันทึกตำแหน่งเมาส์และการคลิก
-            async fn record_mouse_click(
-                controller: &Arc<Mutex<AutomationController>>, 
-                position: (i32, i32),
-                button: &str
-            ) {
-                // 1. บันทึกตำแหน่งเมาส์
-                let move_data = json!({
-                    "type": "mouse_move",
-                    "x": position.0,
-                    "y": position.1,
-                    "wait_time": 0.2,
-                    "randomize": false
-                });
+        }
+        
+        // ฟังก์ชันช่วยบันทึกตำแหน่งเมาส์และการคลิก
+        fn record_mouse_click(
+            steps: &RecordedSteps, 
+            displays: &DisplayLayout,
+            position: Option<(i32, i32)>,
+            button: &str
+        ) {
+            // 1. บันทึกตำแหน่งเมาส์ (ถ้า monitor รู้ตำแหน่งจริง ไม่เช่นนั้นคลิกที่ตำแหน่งเดิมของ cursor)
+            if let Some(position) = position {
+                let move_data = recorded_move_data(displays, position);
                 
-                add_recorded_step(controller, "mouse_move", &move_data).await;
+                send_recorded_step(steps, "mouse_move", move_data);
                 println!("Recorded: Mouse position ({}, {})", position.0, position.1);
-                
-                // 2. บันทึกการคลิก
-                let click_data = json!({
-                    "type": "mouse_click",
-                    "button": button,
-                    "wait_time": 0.5,
-                    "randomize": false
-                });
-                
-                add_recorded_step(controller, "mouse_click", &click_data).await;
-                println!("Recorded: {} mouse click at position ({}, {})", button, position.0, position.1);
             }
             
-            // ปรับปรุงการตรวจจับการคลิกเมาส์
-            let mut button_clicked = false;
+            // 2. บันทึกการคลิก
+            let click_data = json!({
+                "type": "mouse_click",
+                "button": button,
+                "wait_time": 0.5,
+                "randomize": false
+            });
             
-            // แก้ไขการตรวจจับปุ่มซ้าย - index 1 คือซ้าย (ถูกต้องแล้ว)
-            if mouse_state.button_pressed.get(1).map_or(false, |&pressed| pressed) && !last_mouse_buttons[1] {
-                record_mouse_click(&controller, current_position, "left").await;
-                button_clicked = true;
-            }
-            
-            // แก้ไขการตรวจจับปุ่มขวา - อาจเป็น index 2 ตามที่ผู้ใช้รายงาน
-            if !button_clicked && mouse_state.button_pressed.get(2).map_or(false, |&pressed| pressed) && !last_mouse_buttons[2] {
-                record_mouse_click(&controller, current_position, "right").await;
+            send_recorded_step(steps, "mouse_click", click_data);
+            println!("Recorded: {} mouse click", button);
+        }
+        
+        // ปรับปรุงการตรวจจับการคลิกเมาส์
+        let mut button_clicked = false;
+        
+        // แก้ไขการตรวจจับปุ่มซ้าย - index 1 คือซ้าย (ถูกต้องแล้ว)
+        if mouse_state.button_pressed.get(1).is_some_and(|&pressed| pressed) && !last_mouse_buttons[1] {
+            record_mouse_click(&steps, &displays, known_position, "left");
+            button_clicked = true;
+        }
+        
+        // แก้ไขการตรวจจับปุ่มขวา - อาจเป็น index 2 ตามที่ผู้ใช้รายงาน
+        if !button_clicked && mouse_state.button_pressed.get(2).is_some_and(|&pressed| pressed) && !last_mouse_buttons[2] {
+            record_mouse_click(&steps, &displays, known_position, "right");
+            button_clicked = true;
+        }
+        
+        // ตรวจจับปุ่มกลาง - แก้ไขเป็น index 0 หรือ index 3, 4
+        if !button_clicked {
+            // ลองตรวจสอบหลายตำแหน่งที่อาจเป็นไปได้สำหรับปุ่มกลาง
+            if (mouse_state.button_pressed.first().is_some_and(|&pressed| pressed) && !last_mouse_buttons[0]) ||
+               (mouse_state.button_pressed.get(3).is_some_and(|&pressed| pressed) && !last_mouse_buttons[3]) ||
+               (mouse_state.button_pressed.get(4).is_some_and(|&pressed| pressed) && !last_mouse_buttons[4]) {
+                
+                // แสดงข้อมูลเพื่อตรวจสอบว่าตำแหน่งใดถูกกด
+                println!("Middle button detected - full button state: {:?}", mouse_state.button_pressed);
+                record_mouse_click(&steps, &displays, known_position, "middle");
                 button_clicked = true;
             }
-            
-            // ตรวจจับปุ่มกลาง - แก้ไขเป็น index 0 หรือ index 3, 4
-            if !button_clicked {
-                // ลองตรวจสอบหลายตำแหน่งที่อาจเป็นไปได้สำหรับปุ่มกลาง
-                if (mouse_state.button_pressed.get(0).map_or(false, |&pressed| pressed) && !last_mouse_buttons[0]) ||
-                   (mouse_state.button_pressed.get(3).map_or(false, |&pressed| pressed) && !last_mouse_buttons[3]) ||
-                   (mouse_state.button_pressed.get(4).map_or(false, |&pressed| pressed) && !last_mouse_buttons[4]) {
-                    
-                    // แสดงข้อมูลเพื่อตรวจสอบว่าตำแหน่งใดถูกกด
-                    println!("Middle button detected - full button state: {:?}", mouse_state.button_pressed);
-                    record_mouse_click(&controller, current_position, "middle").await;
-                    button_clicked = true;
-                }
-            }
-            
-            if button_clicked {
-                mouse_click_cooldown = true;
-                last_click_time = std::time::Instant::now();
-            }
-            
-            // บันทึกสถานะเมาส์ล่าสุด
-            last_mouse_buttons = vec![false; 10];
-            for (i, &pressed) in mouse_state.button_pressed.iter().enumerate() {
-                if i < last_mouse_buttons.len() {
-                    last_mouse_buttons[i] = pressed;
-                }
+        }
+        
+        if button_clicked {
+            mouse_click_cooldown = true;
+            last_click_time = std::time::Instant::now();
+        }
+        
+        // บันทึกสถานะเมาส์ล่าสุด
+        last_mouse_buttons = vec![false; 10];
+        for (i, &pressed) in mouse_state.button_pressed.iter().enumerate() {
+            if i < last_mouse_buttons.len() {
+                last_mouse_buttons[i] = pressed;
             }
+        }
+        
+        // ตรวจสอบการกดปุ่มคีย์บอร์ด
+        let current_keys = device_state.pressed_keys();
+        
+        // เพิ่มการตรวจสอบพิเศษสำหรับปุ่ม F7 และปุ่มฟังก์ชันอื่นๆ
+        if !current_keys.is_empty() {
+            // ตรวจสอบว่ามีการกดปุ่มฟังก์ชันหรือไม่
+            let has_function_key = current_keys.iter().any(|k| {
+                matches!(k, Key::F(1..=12))
+            });
             
-            // ตรวจสอบการกดปุ่มคีย์บอร์ด
-            let current_keys = device_state.get_keys();
-            
-            // เพิ่มการตรวจสอบพิเศษสำหรับปุ่ม F7 และปุ่มฟังก์ชันอื่นๆ
-            if !current_keys.is_empty() {
-                // ตรวจสอบว่ามีการกดปุ่มฟังก์ชันหรือไม่
-                let has_function_key = current_keys.iter().any(|k| {
-                    matches!(k, 
-                        device_query::Keycode::F1 | device_query::Keycode::F2 | 
-                        device_query::Keycode::F3 | device_query::Keycode::F4 | 
-                        device_query::Keycode::F5 | device_query::Keycode::F6 | 
-                        device_query::Keycode::F7 | device_query::Keycode::F8 | 
-                        device_query::Keycode::F9 | device_query::Keycode::F10 | 
-                        device_query::Keycode::F11 | device_query::Keycode::F12
-                    )
-                });
+            // ถ้ามีการกดปุ่มฟังก์ชัน ให้แยกออกมาบันทึกแค่ปุ่มฟังก์ชันเท่านั้น
+            if has_function_key {
+                // แยกเฉพาะปุ่มฟังก์ชัน
+                let function_keys: Vec<_> = current_keys.iter().filter(|k| {
+                    matches!(k, Key::F(1..=12))
+                }).collect();
                 
-                // ถ้ามีการกดปุ่มฟังก์ชัน ให้แยกออกมาบันทึกแค่ปุ่มฟังก์ชันเท่านั้น
-                if has_function_key {
-                    // แยกเฉพาะปุ่มฟังก์ชัน
-                    let function_keys: Vec<_> = current_keys.iter().filter(|k| {
-                        matches!(k, 
-                            device_query::Keycode::F1 | device_query::Keycode::F2 | 
-                            device_query::Keycode::F3 | device_query::Keycode::F4 | 
-                            device_query::Keycode::F5 | device_query::Keycode::F6 | 
-                            device_query::Keycode::F7 | device_query::Keycode::F8 | 
-                            device_query::Keycode::F9 | device_query::Keycode::F10 | 
-                            device_query::Keycode::F11 | device_query::Keycode::F12
-                        )
-                    }).collect();
+                // ถ้ามีปุ่มฟังก์ชันเพียงปุ่มเดียว ให้ข้ามการบันทึก - ปุ่มฟังก์ชันใช้สำหรับควบคุมแอพฯ เท่านั้น
+                if function_keys.len() == 1 {
+                    // ไม่บันทึกปุ่มฟังก์ชันเนื่องจากเป็นปุ่มลัดของแอพฯ
+                    let key_name = format!("{:?}", function_keys[0]);
+                    println!("Skipping recording function key {} as it's used as application shortcut", key_name);
                     
-                    // ถ้ามีปุ่มฟังก์ชันเพียงปุ่มเดียว ให้ข้ามการบันทึก - ปุ่มฟังก์ชันใช้สำหรับควบคุมแอพฯ เท่านั้น
-                    if function_keys.len() == 1 {
-                        // ไม่บันทึกปุ่มฟังก์ชันเนื่องจากเป็นปุ่มลัดของแอพฯ
-                        let key_name = format!("{:?}", function_keys[0]);
-                        println!("Skipping recording function key {} as it's used as application shortcut", key_name);
-                        
-                        // ข้ามการตรวจสอบคีย์คอมโบในรอบนี้
-                        last_keys = current_keys;
-                        tokio::time::sleep(poll_interval).await;
-                        continue;
-                    }
+                    // ข้ามการตรวจสอบคีย์คอมโบในรอบนี้
+                    last_keys = current_keys;
+                    std::thread::sleep(poll_interval);
+                    continue;
                 }
             }
-            
-            // จัดการกับคีย์คอมโบ (2-4 ปุ่ม)
-            if !current_keys.is_empty() {
-                // ถ้ามีการเพิ่มปุ่มใหม่หรือลดปุ่ม อัปเดตบัฟเฟอร์
-                if current_keys.len() != last_keys.len() || !current_keys.iter().all(|k| last_keys.contains(k)) {
-                    // ตรวจสอบว่าเป็นการเริ่มกดปุ่มใหม่หรือไม่
-                    if last_keys.is_empty() && !current_keys.is_empty() {
-                        // เริ่มคอมโบใหม่
-                        key_combo_buffer = current_keys.clone();
-                        key_combo_timer = std::time::Instant::now();
-                    } else if !current_keys.is_empty() {
-                        // เพิ่มปุ่มเข้าไปในคอมโบ (ถ้ายังไม่มี)
-                        for key in &current_keys {
-                            if !key_combo_buffer.contains(key) {
-                                key_combo_buffer.push(*key);
-                            }
-                        }
-                        
-                        // ถ้าคอมโบมีขนาดระหว่าง 2-4 ปุ่ม และเวลาผ่านไปเพียงพอ (300ms) บันทึกคอมโบ
-                        if key_combo_buffer.len() >= 2 && key_combo_buffer.len() <= 4 && 
-                           key_combo_timer.elapsed().as_millis() >= 300 && 
-                           current_keys.len() < key_combo_buffer.len() {
-                            // แปลงปุ่มเป็น string
-                            let key_combo = key_combo_buffer.iter()
-                                .map(|k| format!("{:?}", k))
-                                .collect::<Vec<String>>()
-                                .join("+");
-                            
-                            let step_data = json!({
-                                "type": "key_press",
-                                "key": key_combo,
-                                "wait_time": 0.3,
-                                "randomize": false
-                            });
-                            
-                            add_recorded_step(&controller, "key_press", &step_data).await;
-                            println!("Recorded: Key combination {}", key_combo);
-                            
-                            // รีเซ็ตบัฟเฟอร์หลังจากบันทึก
-                            key_combo_buffer.clear();
+        }
+        
+        // จัดการกับคีย์คอมโบ (2-4 ปุ่ม)
+        if !current_keys.is_empty() {
+            // ถ้ามีการเพิ่มปุ่มใหม่หรือลดปุ่ม อัปเดตบัฟเฟอร์
+            if current_keys.len() != last_keys.len() || !current_keys.iter().all(|k| last_keys.contains(k)) {
+                // ตรวจสอบว่าเป็นการเริ่มกดปุ่มใหม่หรือไม่
+                if last_keys.is_empty() && !current_keys.is_empty() {
+                    // เริ่มคอมโบใหม่
+                    key_combo_buffer = current_keys.clone();
+                    key_combo_timer = std::time::Instant::now();
+                } else if !current_keys.is_empty() {
+                    // เพิ่มปุ่มเข้าไปในคอมโบ (ถ้ายังไม่มี)
+                    for key in &current_keys {
+                        if !key_combo_buffer.contains(key) {
+                            key_combo_buffer.push(*key);
                         }
                     }
-                }
-            } else if !key_combo_buffer.is_empty() {
-                // ปล่อยปุ่มทั้งหมด - ตรวจสอบว่าควรบันทึกคอมโบหรือไม่
-                if key_combo_buffer.len() >= 2 && key_combo_buffer.len() <= 4 {
-                    // แปลงปุ่มเป็น string และตรวจสอบว่ามีปุ่ม Shift หรือไม่
-                    let has_shift = key_combo_buffer.iter().any(|k| matches!(k, device_query::Keycode::LShift | device_query::Keycode::RShift));
-                    
-                    // แปลงคีย์เป็น lowercase ถ้าไม่มีการกด Shift
-                    let key_combo = key_combo_buffer.iter()
-                        .map(|k| {
-                            let key_str = format!("{:?}", k);
-                            if !has_shift && is_letter_key(k) {
-                                key_str.to_lowercase()
-                            } else {
-                                key_str
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join("+");
-                    
-                    let step_data = json!({
-                        "type": "key_press",
-                        "key": key_combo,
-                        "wait_time": 0.3,
-                        "randomize": false
-                    });
-                    
-                    add_recorded_step(&controller, "key_press", &step_data).await;
-                    println!("Recorded: Key combination {}", key_combo);
-                } else if key_combo_buffer.len() == 1 {
-                    // บันทึกการกดปุ่มเดี่ยว
-                    let key = key_combo_buffer[0];
-                    let key_str = format!("{:?}", key);
                     
-                    // ตรวจสอบว่าเป็นตัวอักษรหรือไม่ และทำให้เป็นตัวพิมพ์เล็กเสมอ
-                    let lowercase_key = if is_letter_key(&key) {
-                        key_str.to_lowercase()
-                    } else {
-                        key_str
-                    };
-                    
-                    let step_data = json!({
-                        "type": "key_press",
-                        "key": lowercase_key,
-                        "wait_time": 0.3,
-                        "randomize": false
-                    });
-                    
-                    add_recorded_step(&controller, "key_press", &step_data).await;
-                    println!("Recorded: Key press {} (as lowercase)", lowercase_key);
+                    // ถ้าคอมโบมีขนาดระหว่าง 2-4 ปุ่ม และเวลาผ่านไปเพียงพอ (300ms) บันทึกคอมโบ
+                    if key_combo_buffer.len() >= 2 && key_combo_buffer.len() <= 4 && 
+                       key_combo_timer.elapsed().as_millis() >= 300 && 
+                       current_keys.len() < key_combo_buffer.len() {
+                        record_key_chords(&steps, &key_combo_buffer);
+                        
+                        // รีเซ็ตบัฟเฟอร์หลังจากบันทึก
+                        key_combo_buffer.clear();
+                    }
                 }
-                
-                // รีเซ็ตบัฟเฟอร์
-                key_combo_buffer.clear();
+            }
+        } else if !key_combo_buffer.is_empty() {
+            // ปล่อยปุ่มทั้งหมด - ตรวจสอบว่าควรบันทึกคอมโบหรือไม่
+            if key_combo_buffer.len() <= 4 {
+                record_key_chords(&steps, &key_combo_buffer);
             }
             
-            // บันทึกปุ่มที่กดล่าสุด
-            last_keys = current_keys;
-            
-            // รอก่อนตรวจสอบอีกครั้ง
-            tokio::time::sleep(poll_interval).await;
+            // รีเซ็ตบัฟเฟอร์
+            key_combo_buffer.clear();
         }
-    });
+        
+        // บันทึกปุ่มที่กดล่าสุด
+        last_keys = current_keys;
+        
+        // รอก่อนตรวจสอบอีกครั้ง
+        std::thread::sleep(poll_interval);
+    }
 }
 
 // ฟังก์ชันช่วยในการเพิ่มขั้นตอนที่บันทึกได้
@@ -1233,10 +1706,12 @@ async fn add_recorded_step(controller: &Arc<Mutex<AutomationController>>, step_t
     let step_id = Uuid::new_v4().to_string();
     
     // สร้าง MacroStep ใหม่
-    let new_step = crate::models::MacroStep {
-        id: step_id,
-        type_: step_type.to_string(),
-        data: data.clone(),
+    let new_step = match MacroStep::from_legacy(step_id, step_type, data) {
+        Ok(step) => step,
+        Err(err) => {
+            println!("Failed to record {} step: {}", step_type, err);
+            return;
+        }
     };
     
     // เพิ่มขั้นตอนเข้าไปใน controller
@@ -1254,21 +1729,290 @@ async fn add_recorded_step(controller: &Arc<Mutex<AutomationController>>, step_t
     }
 }
 
-// เพิ่มฟังก์ชันเพื่อตรวจสอบว่าคีย์เป็นตัวอักษรหรือไม่
-fn is_letter_key(key: &device_query::Keycode) -> bool {
-    matches!(key, 
-        device_query::Keycode::A | device_query::Keycode::B | 
-        device_query::Keycode::C | device_query::Keycode::D | 
-        device_query::Keycode::E | device_query::Keycode::F | 
-        device_query::Keycode::G | device_query::Keycode::H | 
-        device_query::Keycode::I | device_query::Keycode::J | 
-        device_query::Keycode::K | device_query::Keycode::L | 
-        device_query::Keycode::M | device_query::Keycode::N | 
-        device_query::Keycode::O | device_query::Keycode::P | 
-        device_query::Keycode::Q | device_query::Keycode::R | 
-        device_query::Keycode::S | device_query::Keycode::T | 
-        device_query::Keycode::U | device_query::Keycode::V | 
-        device_query::Keycode::W | device_query::Keycode::X | 
-        device_query::Keycode::Y | device_query::Keycode::Z
-    )
-} 
\ No newline at end of file
+// ส่งขั้นตอนที่บันทึกกลับไปให้ task ของ start_event_recorder (ถ้า task จบไปแล้วก็ทิ้งไป)
+fn send_recorded_step(steps: &RecordedSteps, step_type: &'static str, data: Value) {
+    let _ = steps.send((step_type, data));
+}
+
+// data ของขั้นตอน mouse_move ที่บันทึก พร้อมตำแหน่งเทียบกับจอที่เมาส์อยู่ (ถ้ารู้การจัดวางจอ)
+fn recorded_move_data(displays: &DisplayLayout, position: (i32, i32)) -> Value {
+    let mut move_data = json!({
+        "type": "mouse_move",
+        "x": position.0,
+        "y": position.1,
+        "wait_time": 0.2,
+        "randomize": false
+    });
+    if let Some(anchor) = displays.anchor(position.0, position.1) {
+        move_data["monitor"] = json!(anchor);
+    }
+    move_data
+}
+
+// wheel ที่เลื่อนต่อเนื่องกันระหว่างบันทึก (หน่วยเป็น 1/WHEEL_DELTA ของรอย)
+struct PendingScroll {
+    position: Option<(i32, i32)>,
+    dx: i32,
+    dy: i32,
+    last_event: std::time::Instant,
+}
+
+// บันทึกตำแหน่งเมาส์ตอนเริ่มเลื่อนแล้วตามด้วยขั้นตอน scroll
+fn record_scroll(steps: &RecordedSteps, displays: &DisplayLayout, scroll: PendingScroll) {
+    let delta = crate::wheel::WHEEL_DELTA as f64;
+    let dx = (scroll.dx as f64 / delta).round() as i32;
+    let dy = (scroll.dy as f64 / delta).round() as i32;
+    if dx == 0 && dy == 0 {
+        return;
+    }
+    
+    if let Some(position) = scroll.position {
+        let move_data = recorded_move_data(displays, position);
+        send_recorded_step(steps, "mouse_move", move_data);
+    }
+    
+    let scroll_data = json!({
+        "type": "scroll",
+        "dx": dx,
+        "dy": dy,
+        "wait_time": 0.5,
+        "randomize": false
+    });
+    send_recorded_step(steps, "scroll", scroll_data);
+    println!("Recorded: Scroll {},{}", dx, dy);
+}
+
+// บันทึกปุ่มที่กดพร้อมกันเป็นขั้นตอน key_press ด้วยชื่อคีย์มาตรฐานที่ keyboard_press_key กดซ้ำได้
+fn record_key_chords(steps: &RecordedSteps, keys: &[Key]) {
+    for chord in crate::key_chord::chords_for_keys(keys) {
+        let key = chord.to_string();
+        let step_data = json!({
+            "type": "key_press",
+            "key": key,
+            "wait_time": 0.3,
+            "randomize": false
+        });
+        
+        send_recorded_step(steps, "key_press", step_data);
+        println!("Recorded: Key press {}", key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_backend::RecordingInputBackend;
+    use std::time::Duration;
+
+    // ไคลเอนต์จำลองที่ส่งคำสั่งผ่าน handle_websocket_message และเก็บข้อความที่ได้รับไว้ตรวจสอบ
+    struct Client {
+        controller: Arc<Mutex<AutomationController>>,
+        recorder: Arc<RecordingInputBackend>,
+        events: mpsc::UnboundedReceiver<Message>,
+    }
+
+    impl Client {
+        fn new() -> Self {
+            let recorder = Arc::new(RecordingInputBackend::new());
+            let mut controller = AutomationController::with_input(recorder.clone());
+            controller.input.set_profile(TimingProfile::Fast);
+            let (tx, events) = mpsc::unbounded_channel();
+            controller.clients.insert("test".to_string(), tx);
+            Self { controller: Arc::new(Mutex::new(controller)), recorder, events }
+        }
+
+        async fn send(&self, event_type: &str, data: Value) {
+            let message = Message::text(json!({ "type": event_type, "data": data }).to_string());
+            handle_websocket_message(message, self.controller.clone(), "test").await.unwrap();
+        }
+
+        // ข้อความถัดไปทั้งชนิดและ data
+        async fn next_message(&mut self) -> (String, Value) {
+            let message = tokio::time::timeout(Duration::from_secs(5), self.events.recv())
+                .await
+                .expect("no message")
+                .expect("client channel closed");
+            let value: Value = serde_json::from_str(message.to_str().unwrap()).unwrap();
+            (value["type"].as_str().unwrap().to_string(), value["data"].clone())
+        }
+
+        // รอข้อความชนิดที่กำหนดแล้วคืนค่า data (ข้ามข้อความชนิดอื่น)
+        async fn next_event(&mut self, event_type: &str) -> Value {
+            loop {
+                let (type_, data) = self.next_message().await;
+                if type_ == event_type {
+                    return data;
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_a_second_run_while_one_is_active() {
+        let mut client = Client::new();
+        client.send("run_automation", json!({
+            "steps": [{ "id": "1", "type": "wait", "data": { "wait_time": 30 } }],
+        })).await;
+        let running = client.next_event("step_executing").await;
+
+        client.send("run_automation", json!({
+            "steps": [{ "id": "1", "type": "key_press", "data": { "key": "a", "wait_time": 0 } }],
+        })).await;
+        let rejected = client.next_event("run_rejected").await;
+        assert_eq!(rejected["active_run_id"], running["run_id"]);
+
+        client.controller.lock().await.stop_run();
+        let completed = client.next_event("automation_completed").await;
+        assert_eq!(completed["run_id"], running["run_id"]);
+        // รอบที่ถูกปฏิเสธไม่ได้ส่ง input
+        assert_eq!(client.recorder.take_actions(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn tags_every_event_of_a_run_with_its_run_id() {
+        let mut client = Client::new();
+        client.send("run_automation", json!({
+            "steps": [
+                { "id": "1", "type": "key_press", "data": { "key": "a", "wait_time": 0 } },
+                { "id": "2", "type": "key_press", "data": { "key": "b", "wait_time": 0 } },
+            ],
+        })).await;
+
+        let mut run_ids = Vec::new();
+        loop {
+            let (type_, data) = client.next_message().await;
+            if type_ == "step_executing" || type_ == "automation_completed" {
+                run_ids.push(data["run_id"].clone());
+            }
+            if type_ == "automation_completed" {
+                break;
+            }
+        }
+        assert_eq!(run_ids.len(), 3);
+        assert!(run_ids[0].is_string());
+        assert!(run_ids.iter().all(|run_id| *run_id == run_ids[0]), "{:?}", run_ids);
+    }
+
+    // ขั้นตอนที่บันทึกบนจอที่สอง (ทางขวาของจอหลัก) และคลิกกลางจอหลัก
+    fn steps_on_two_monitors() -> Value {
+        json!([
+            { "id": "1", "type": "mouse_move", "data": {
+                "x": 2560, "y": 180, "monitor": { "id": "side", "x": 640, "y": 180, "scale": 1.5 },
+            } },
+            { "id": "2", "type": "mouse_click", "data": { "button": "left", "x": 960, "y": 540 } },
+        ])
+    }
+
+    fn two_monitors() -> crate::display::FakeDisplays {
+        crate::display::FakeDisplays::new(vec![
+            Monitor { id: "main".to_string(), x: 0, y: 0, width: 1920, height: 1080, scale: 1.0, primary: true },
+            Monitor { id: "side".to_string(), x: 1920, y: 0, width: 1280, height: 720, scale: 1.5, primary: false },
+        ])
+    }
+
+    fn positions(converted: &Value) -> Vec<(f64, f64, String)> {
+        converted["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|step| {
+                let data = &step["data"];
+                (data["x"].as_f64().unwrap(), data["y"].as_f64().unwrap(), data["monitor"]["id"].as_str().unwrap().to_string())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn normalizes_steps_against_the_monitor_they_were_recorded_on() {
+        let mut client = Client::new();
+        client.controller.lock().await.displays = Arc::new(two_monitors());
+        client.send("convert_coordinates", json!({ "to": "normalized", "steps": steps_on_two_monitors() })).await;
+
+        let converted = client.next_event("coordinates_converted").await;
+        assert_eq!(positions(&converted), vec![(0.5, 0.25, "side".to_string()), (0.5, 0.5, "main".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn maps_monitor_anchors_onto_an_explicit_screen() {
+        let mut client = Client::new();
+        client.controller.lock().await.displays = Arc::new(two_monitors());
+        client.send("convert_coordinates", json!({
+            "to": "normalized", "width": 1280, "height": 720, "steps": steps_on_two_monitors(),
+        })).await;
+
+        // ขั้นตอนบนจอที่สองคงระยะจากมุมจอไว้บนหน้าจอที่ระบุ แทนการถูกปฏิเสธเพราะไม่พบจอ
+        let converted = client.next_event("coordinates_converted").await;
+        assert_eq!(positions(&converted), vec![(0.5, 0.25, "main".to_string()), (0.75, 0.75, "main".to_string())]);
+        assert_eq!(converted["steps"][0]["data"]["monitor"], json!({ "id": "main", "x": 640, "y": 180, "scale": 1.0 }));
+    }
+
+    #[test]
+    fn parses_step_ranges() {
+        let range = parse_step_range(&json!({ "start_index": 1, "end_index": 3, "start_loop": 2 }), 4, 3).unwrap();
+        assert_eq!((range.start_index, range.end_index, range.start_loop), (1, Some(3), 2));
+        let range = parse_step_range(&json!({}), 0, 1).unwrap();
+        assert_eq!((range.start_index, range.end_index, range.start_loop), (0, None, 0));
+    }
+
+    #[test]
+    fn rejects_step_ranges_outside_the_run() {
+        let error = |data: Value| parse_step_range(&data, 4, 2).unwrap_err();
+        assert_eq!(error(json!({ "start_index": 4 })), "start_index 4 is out of range (4 steps)");
+        assert_eq!(error(json!({ "end_index": 4 })), "end_index 4 is out of range (4 steps)");
+        assert_eq!(error(json!({ "start_index": 3, "end_index": 1 })), "end_index 1 is before start_index 3");
+        assert_eq!(error(json!({ "start_loop": 2 })), "start_loop 2 is out of range (2 loops)");
+        assert_eq!(error(json!({ "start_index": -1 })), "start_index must be a non-negative integer, got -1");
+        assert_eq!(error(json!({ "end_index": "2" })), "end_index must be a non-negative integer, got \"2\"");
+    }
+
+    #[test]
+    fn parses_clipboard_content_from_set_clipboard() {
+        assert_eq!(parse_clipboard_content(&json!({ "text": "hi" })), Ok(Some(ClipboardContent::Text("hi".to_string()))));
+        assert_eq!(
+            parse_clipboard_content(&json!({ "html": "<b>hi</b>", "text": "hi" })),
+            Ok(Some(ClipboardContent::Html { html: "<b>hi</b>".to_string(), alt_text: Some("hi".to_string()) })),
+        );
+        assert_eq!(
+            parse_clipboard_content(&json!({ "image": { "data": "iVBORw==" } })),
+            Ok(Some(ClipboardContent::Image { mime: "image/png".to_string(), bytes: vec![0x89, b'P', b'N', b'G'] })),
+        );
+        assert_eq!(parse_clipboard_content(&json!({ "image": null })), Ok(None));
+    }
+
+    #[test]
+    fn rejects_images_without_valid_base64() {
+        let error = parse_clipboard_content(&json!({ "image": { "mime": "image/png", "data": "not base64!" } })).unwrap_err();
+        assert!(error.starts_with("image.data is not valid base64"), "{}", error);
+        assert_eq!(
+            parse_clipboard_content(&json!({ "image": { "data": 42 } })),
+            Err("image.data must be a base64 string".to_string()),
+        );
+    }
+
+    #[test]
+    fn text_and_html_json_round_trip_through_set_clipboard() {
+        let contents = [
+            ClipboardContent::Text("hi".to_string()),
+            ClipboardContent::Html { html: "<b>hi</b>".to_string(), alt_text: Some("hi".to_string()) },
+        ];
+        for content in contents {
+            let value = clipboard_content_json(content.format(), Some(&content));
+            assert_eq!(value["available"], true);
+            assert_eq!(parse_clipboard_content(&value), Ok(Some(content)));
+        }
+    }
+
+    #[test]
+    fn encodes_images_as_base64() {
+        let image = ClipboardContent::Image { mime: "image/bmp".to_string(), bytes: vec![1, 2, 3] };
+        let value = clipboard_content_json(ClipboardFormat::Image, Some(&image));
+        assert_eq!(value, json!({ "format": "image", "available": true, "mime": "image/bmp", "data": "AQID" }));
+        // set_clipboard รับรูปภาพในรูปแบบเดียวกันภายใต้ image
+        assert_eq!(parse_clipboard_content(&json!({ "image": value })), Ok(Some(image)));
+
+        assert_eq!(
+            clipboard_content_json(ClipboardFormat::Html, None),
+            json!({ "format": "html", "available": false }),
+        );
+    }
+}
@@ -0,0 +1,142 @@
+// จับการเลื่อน wheel ของเมาส์ระหว่างบันทึก (device_query อ่าน wheel ไม่ได้)
+
+// ค่า wheel ต่อหนึ่งรอย (ใช้ค่าเดียวกับ WHEEL_DELTA ของ Windows)
+pub const WHEEL_DELTA: i32 = 120;
+
+// เหตุการณ์ wheel ที่จับได้จากระบบ หน่วยเป็น 1/WHEEL_DELTA ของรอย ค่าบวกคือเลื่อนลง/ขวา
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelEvent {
+    pub dx: i32,
+    pub dy: i32,
+}
+
+static WHEEL_EVENTS: std::sync::OnceLock<tokio::sync::broadcast::Sender<WheelEvent>> = std::sync::OnceLock::new();
+
+// รับเหตุการณ์ wheel ของเมาส์ (device_query อ่าน wheel ไม่ได้) ตัวดักจับจะเริ่มทำงานในครั้งแรกที่เรียก
+pub fn subscribe_wheel_events() -> tokio::sync::broadcast::Receiver<WheelEvent> {
+    WHEEL_EVENTS
+        .get_or_init(|| {
+            let (tx, _) = tokio::sync::broadcast::channel(256);
+            std::thread::spawn(|| {
+                if let Err(e) = listen_wheel_events() {
+                    println!("Mouse wheel capture is unavailable: {}", e);
+                }
+            });
+            tx
+        })
+        .subscribe()
+}
+
+#[cfg_attr(not(any(target_os = "windows", all(target_os = "linux", feature = "x11-wheel"))), allow(dead_code))]
+fn send_wheel_event(event: WheelEvent) {
+    if let Some(tx) = WHEEL_EVENTS.get() {
+        // ไม่มีผู้รับ (ไม่ได้บันทึกอยู่) ก็ทิ้งไป
+        let _ = tx.send(event);
+    }
+}
+
+// Windows: ใช้ low-level mouse hook ซึ่งต้องมี message loop อยู่ใน thread เดียวกัน
+#[cfg(target_os = "windows")]
+fn listen_wheel_events() -> Result<(), String> {
+    use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, HC_ACTION, HHOOK, MSG, MSLLHOOKSTRUCT, WH_MOUSE_LL,
+        WM_MOUSEHWHEEL, WM_MOUSEWHEEL,
+    };
+
+    unsafe extern "system" fn hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 {
+            let message = wparam.0 as u32;
+            if message == WM_MOUSEWHEEL || message == WM_MOUSEHWHEEL {
+                let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+                // high word ของ mouseData คือระยะที่เลื่อน (ค่าบวกของ wheel แนวตั้งคือเลื่อนขึ้น)
+                let delta = (info.mouseData >> 16) as u16 as i16 as i32;
+                if message == WM_MOUSEWHEEL {
+                    send_wheel_event(WheelEvent { dx: 0, dy: -delta });
+                } else {
+                    send_wheel_event(WheelEvent { dx: delta, dy: 0 });
+                }
+            }
+        }
+        CallNextHookEx(HHOOK::default(), code, wparam, lparam)
+    }
+
+    unsafe {
+        SetWindowsHookExW(WH_MOUSE_LL, Some(hook), HINSTANCE::default(), 0).map_err(|e| e.to_string())?;
+        let mut message = MSG::default();
+        while GetMessageW(&mut message, HWND::default(), 0, 0).as_bool() {}
+    }
+    Ok(())
+}
+
+// Linux (X11): อ่าน raw button event ของ XInput2 จาก root window ปุ่ม 4/5 คือเลื่อนขึ้น/ลง ปุ่ม 6/7 คือเลื่อนซ้าย/ขวา
+// ต้อง build ด้วย feature x11-wheel ซึ่งต้องมี libXi (เช่น libxi-dev)
+#[cfg(all(target_os = "linux", feature = "x11-wheel"))]
+fn listen_wheel_events() -> Result<(), String> {
+    use std::os::raw::c_int;
+    use x11::{xinput2, xlib};
+
+    unsafe {
+        let display = xlib::XOpenDisplay(std::ptr::null());
+        if display.is_null() {
+            return Err("cannot open the X display".to_string());
+        }
+
+        let extension = std::ffi::CString::new("XInputExtension").unwrap();
+        let (mut opcode, mut first_event, mut first_error) = (0, 0, 0);
+        if xlib::XQueryExtension(display, extension.as_ptr(), &mut opcode, &mut first_event, &mut first_error) == 0 {
+            return Err("the X server has no XInput extension".to_string());
+        }
+        // ขอเวอร์ชัน 2.0 เพื่อให้ wheel ยังมาเป็นปุ่ม 4-7 (ไม่ใช่ smooth scrolling ของ 2.1)
+        let (mut major, mut minor): (c_int, c_int) = (2, 0);
+        if xinput2::XIQueryVersion(display, &mut major, &mut minor) != xlib::Success as c_int {
+            return Err("the X server does not support XInput 2".to_string());
+        }
+
+        let mut mask = [0u8; (xinput2::XI_LASTEVENT as usize >> 3) + 1];
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawButtonPress);
+        let mut event_mask = xinput2::XIEventMask {
+            deviceid: xinput2::XIAllMasterDevices,
+            mask_len: mask.len() as c_int,
+            mask: mask.as_mut_ptr(),
+        };
+        xinput2::XISelectEvents(display, xlib::XDefaultRootWindow(display), &mut event_mask, 1);
+        xlib::XFlush(display);
+
+        loop {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            xlib::XNextEvent(display, &mut event);
+            let mut cookie = event.generic_event_cookie;
+            if cookie.type_ != xlib::GenericEvent
+                || cookie.extension != opcode
+                || xlib::XGetEventData(display, &mut cookie) == 0
+            {
+                continue;
+            }
+            if cookie.evtype == xinput2::XI_RawButtonPress {
+                let raw = &*(cookie.data as *const xinput2::XIRawEvent);
+                let wheel = match raw.detail {
+                    4 => Some(WheelEvent { dx: 0, dy: -WHEEL_DELTA }),
+                    5 => Some(WheelEvent { dx: 0, dy: WHEEL_DELTA }),
+                    6 => Some(WheelEvent { dx: -WHEEL_DELTA, dy: 0 }),
+                    7 => Some(WheelEvent { dx: WHEEL_DELTA, dy: 0 }),
+                    _ => None,
+                };
+                if let Some(wheel) = wheel {
+                    send_wheel_event(wheel);
+                }
+            }
+            xlib::XFreeEventData(display, &mut cookie);
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", not(feature = "x11-wheel")))]
+fn listen_wheel_events() -> Result<(), String> {
+    Err("built without the x11-wheel feature (use AUTOMATION_INPUT_MONITOR=evdev or rebuild with --features x11-wheel)".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn listen_wheel_events() -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}